@@ -0,0 +1,50 @@
+use super::{Diagnostic, DiagnosticKind, Severity};
+use crate::{
+	fmt::{self, Display},
+	symbol,
+	term::color,
+};
+
+
+impl std::fmt::Display for Severity {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::Error => write!(f, "{}", color::Fg(color::Red, "Error")),
+			Self::Warning => write!(f, "{}", color::Fg(color::Yellow, "Warning")),
+		}
+	}
+}
+
+
+impl<'a> Display<'a> for Diagnostic {
+	type Context = &'a symbol::Interner;
+
+	fn fmt(&self, f: &mut std::fmt::Formatter, context: Self::Context) -> std::fmt::Result {
+		write!(f, "{} [{}]: ", self.severity, self.code)?;
+
+		match &self.kind {
+			DiagnosticKind::Syntax(error) => error.fmt(f, context)?,
+
+			DiagnosticKind::Semantic(kind) => {
+				write!(f, "{} - ", fmt::Show(self.span.start, context))?;
+				kind.fmt(f, context)?;
+			}
+
+			DiagnosticKind::Lint(lint) => {
+				write!(f, "{} - {}", fmt::Show(self.span.start, context), lint)?;
+			}
+		}
+
+		for related in &self.related {
+			write!(
+				f,
+				"\n  {}: {} - {}",
+				color::Fg(color::Blue, "Note"),
+				fmt::Show(related.pos, context),
+				related.message
+			)?;
+		}
+
+		Ok(())
+	}
+}
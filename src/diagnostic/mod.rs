@@ -0,0 +1,208 @@
+mod fmt;
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	lint::Lint,
+	semantic,
+	syntax::{self, lexer, parser, SourcePos, Span},
+};
+
+
+/// The severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	/// Prevents the program from running.
+	Error,
+	/// Reported for the user's attention, but doesn't prevent the program from running.
+	Warning,
+}
+
+
+/// What a diagnostic is about.
+#[derive(Debug)]
+pub enum DiagnosticKind {
+	/// A syntax error.
+	Syntax(syntax::Error),
+	/// A semantic error.
+	Semantic(semantic::ErrorKind),
+	/// A lint warning.
+	Lint(Lint),
+}
+
+
+/// A secondary position related to a diagnostic, such as where a symbol was first
+/// defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Related {
+	pub pos: SourcePos,
+	pub message: &'static str,
+}
+
+
+/// A diagnostic produced by the static analysis passes.
+#[derive(Debug)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	/// A stable identifier for the kind of diagnostic, for tooling.
+	pub code: &'static str,
+	pub kind: DiagnosticKind,
+	pub span: Span,
+	pub related: Vec<Related>,
+}
+
+
+impl Diagnostic {
+	/// Create a warning for the given lint.
+	pub fn lint(lint: Lint, pos: SourcePos) -> Self {
+		Self {
+			severity: Severity::Warning,
+			code: lint.code(),
+			kind: DiagnosticKind::Lint(lint),
+			span: pos.into(),
+			related: Vec::new(),
+		}
+	}
+}
+
+
+impl From<syntax::Error> for Diagnostic {
+	fn from(error: syntax::Error) -> Self {
+		let (code, pos) = match &error {
+			syntax::Error::Lexer(error) => {
+				let code = match error.error {
+					lexer::ErrorKind::UnexpectedEof => "unexpected-eof",
+					lexer::ErrorKind::Unexpected(_) => "unexpected-character",
+					lexer::ErrorKind::EmptyByteLiteral => "empty-byte-literal",
+					lexer::ErrorKind::InvalidEscapeSequence(_) => "invalid-escape-sequence",
+					lexer::ErrorKind::InvalidNumber(_) => "invalid-number",
+					lexer::ErrorKind::InvalidIdentifier(_) => "invalid-identifier",
+					lexer::ErrorKind::UnterminatedBlockComment => "unterminated-block-comment",
+				};
+
+				(code, error.pos)
+			}
+
+			syntax::Error::Parser(error) => match &error.kind {
+				// The parser doesn't track the position of the end of file.
+				parser::ErrorKind::UnexpectedEof => ("unexpected-eof", SourcePos::default()),
+				parser::ErrorKind::Unexpected { token, .. } => ("unexpected-token", token.pos),
+				parser::ErrorKind::EmptyCommandBlock { pos } => ("empty-command-block", *pos),
+				parser::ErrorKind::EmptyArgument { pos } => ("empty-argument", *pos),
+				parser::ErrorKind::EmptyProgram { pos } => ("empty-program", *pos),
+				parser::ErrorKind::AssignAsEquality { pos } => ("assign-as-equality", *pos),
+				parser::ErrorKind::DetachedTry { pos } => ("detached-try", *pos),
+				parser::ErrorKind::AssignCountMismatch { pos, .. } => ("assign-count-mismatch", *pos),
+				parser::ErrorKind::RequiredAfterDefault { pos } => ("required-after-default", *pos),
+				parser::ErrorKind::StatementInInterpolation { pos, .. } => ("statement-in-interpolation", *pos),
+				parser::ErrorKind::NestingTooDeep { pos } => ("nesting-too-deep", *pos),
+				parser::ErrorKind::Unterminated { pos, .. } => ("unterminated", *pos),
+				parser::ErrorKind::DuplicateMatchArm { pos, .. } => ("duplicate-match-arm", *pos),
+				parser::ErrorKind::TrailingInput { token } => ("trailing-input", token.pos),
+				parser::ErrorKind::InvalidEnvAssign => ("invalid-env-assign", SourcePos::default()),
+			},
+		};
+
+		let span = match &error {
+			syntax::Error::Parser(parser::Error { span: Some(span), .. }) => *span,
+			_ => pos.into(),
+		};
+
+		let related = match &error {
+			syntax::Error::Parser(parser::Error { kind: parser::ErrorKind::DuplicateMatchArm { first, .. }, .. }) => {
+				vec![Related { pos: *first, message: "first matched here" }]
+			}
+			_ => Vec::new(),
+		};
+
+		Self {
+			severity: Severity::Error,
+			code,
+			kind: DiagnosticKind::Syntax(error),
+			span,
+			related,
+		}
+	}
+}
+
+
+impl From<semantic::Error> for Diagnostic {
+	fn from(error: semantic::Error) -> Self {
+		let mut related = Vec::new();
+
+		let code = match error.kind {
+			semantic::ErrorKind::UndeclaredVariable(_) => "undeclared-variable",
+			semantic::ErrorKind::DuplicateVariable(_) => "duplicate-variable",
+			semantic::ErrorKind::DuplicateKey(_, original) => {
+				related.push(Related { pos: original, message: "first defined here" });
+				"duplicate-key"
+			}
+			semantic::ErrorKind::ReturnOutsideFunction => "return-outside-function",
+			semantic::ErrorKind::SelfOutsideFunction => "self-outside-function",
+			semantic::ErrorKind::TryOutsideFunction => "try-outside-function",
+			semantic::ErrorKind::BreakOutsideLoop => "break-outside-loop",
+			semantic::ErrorKind::ContinueOutsideLoop => "continue-outside-loop",
+			semantic::ErrorKind::InvalidAssignment => "invalid-assignment",
+			semantic::ErrorKind::ConstAssignment(_, declaration) => {
+				related.push(Related { pos: declaration, message: "declared here" });
+				"const-assignment"
+			}
+			semantic::ErrorKind::InvalidSpread => "invalid-spread",
+			semantic::ErrorKind::AsyncBuiltin => "async-builtin",
+		};
+
+		Self {
+			severity: Severity::Error,
+			code,
+			kind: DiagnosticKind::Semantic(error.kind),
+			span: error.pos.into(),
+			related,
+		}
+	}
+}
+
+
+/// A sink for diagnostics.
+pub trait Reporter {
+	fn report(&mut self, diagnostic: Diagnostic);
+}
+
+
+impl<F> Reporter for F
+where
+	F: FnMut(Diagnostic),
+{
+	fn report(&mut self, diagnostic: Diagnostic) {
+		self(diagnostic)
+	}
+}
+
+
+/// A reporter that collects all diagnostics.
+#[derive(Debug, Default)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+
+impl Diagnostics {
+	/// Sort the diagnostics by position, so that they may be displayed top to bottom,
+	/// grouped by file. The order of diagnostics in the same position
+	/// is preserved.
+	pub fn sort_by_position(&mut self) {
+		self.0.sort_by_key(|diagnostic| diagnostic.span.start);
+	}
+
+
+	/// Check if any of the diagnostics is an error.
+	pub fn has_errors(&self) -> bool {
+		self.0
+			.iter()
+			.any(|diagnostic| diagnostic.severity == Severity::Error)
+	}
+}
+
+
+impl Reporter for Diagnostics {
+	fn report(&mut self, diagnostic: Diagnostic) {
+		self.0.push(diagnostic)
+	}
+}
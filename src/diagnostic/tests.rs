@@ -0,0 +1,174 @@
+use assert_matches::assert_matches;
+
+use crate::{
+	lint::{self, Lint},
+	semantic::{self, Analyzer},
+	symbol,
+	syntax::{self, lexer, Source, SourcePos},
+};
+use super::{Diagnostic, DiagnosticKind, Diagnostics, Related, Reporter, Severity};
+
+
+#[test]
+fn test_duplicate_key() {
+	let input = "let dict = @[ key: 1, other: 2, key: 3 ]";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	let mut diagnostics = Diagnostics::default();
+
+	let ast = syntax::Analysis::analyze_with(&source, &mut interner, &mut diagnostics);
+	assert!(diagnostics.0.is_empty());
+
+	let program = Analyzer::analyze_with(ast, &mut interner, &mut diagnostics);
+	assert!(program.is_none());
+	assert!(diagnostics.has_errors());
+
+	let pos = |column| SourcePos { line: 1, column, offset: column as usize, path };
+
+	match diagnostics.0.as_slice() {
+		[ diagnostic ] => {
+			assert_eq!(diagnostic.severity, Severity::Error);
+			assert_eq!(diagnostic.code, "duplicate-key");
+			assert_matches!(
+				diagnostic.kind,
+				DiagnosticKind::Semantic(semantic::ErrorKind::DuplicateKey(_, _))
+			);
+			assert_eq!(diagnostic.span.start, pos(32));
+			assert_eq!(
+				diagnostic.related,
+				[ Related { pos: pos(14), message: "first defined here" } ]
+			);
+		}
+
+		diagnostics => panic!("unexpected diagnostics: {:?}", diagnostics),
+	}
+}
+
+
+#[test]
+fn test_duplicate_match_arm() {
+	let input = "match x with 1 then 2 with 1 then 3 end";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	let mut diagnostics = Diagnostics::default();
+
+	syntax::Analysis::analyze_with(&source, &mut interner, &mut diagnostics);
+	assert!(diagnostics.has_errors());
+
+	let pos = |column| SourcePos { line: 1, column, offset: column as usize, path };
+
+	match diagnostics.0.as_slice() {
+		[ diagnostic ] => {
+			assert_eq!(diagnostic.code, "duplicate-match-arm");
+			assert_eq!(diagnostic.span.start, pos(27));
+			assert_eq!(
+				diagnostic.related,
+				[ Related { pos: pos(13), message: "first matched here" } ]
+			);
+		}
+
+		diagnostics => panic!("unexpected diagnostics: {:?}", diagnostics),
+	}
+}
+
+
+#[test]
+fn test_const_assignment() {
+	let input = "const x = 1\nx = 2";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	let mut diagnostics = Diagnostics::default();
+
+	let ast = syntax::Analysis::analyze_with(&source, &mut interner, &mut diagnostics);
+	assert!(diagnostics.0.is_empty());
+
+	let program = Analyzer::analyze_with(ast, &mut interner, &mut diagnostics);
+	assert!(program.is_none());
+
+	let pos = |line, column, offset| SourcePos { line, column, offset, path };
+
+	match diagnostics.0.as_slice() {
+		[ diagnostic ] => {
+			assert_eq!(diagnostic.code, "const-assignment");
+			assert_matches!(
+				diagnostic.kind,
+				DiagnosticKind::Semantic(semantic::ErrorKind::ConstAssignment(_, _))
+			);
+			assert_eq!(diagnostic.span.start, pos(2, 0, 12));
+			assert_eq!(
+				diagnostic.related,
+				[ Related { pos: pos(1, 6, 6), message: "declared here" } ]
+			);
+		}
+
+		diagnostics => panic!("unexpected diagnostics: {:?}", diagnostics),
+	}
+}
+
+
+#[test]
+fn test_sort_by_position() {
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+
+	let pos = |line, column, offset| SourcePos { line, column, offset, path };
+
+	let mut diagnostics = Diagnostics::default();
+
+	for pos in [ pos(3, 1, 20), pos(1, 4, 4), pos(2, 0, 10), pos(1, 2, 2), pos(2, 0, 9) ] {
+		diagnostics.report(Diagnostic::lint(Lint::IfWithoutElse, pos));
+	}
+
+	diagnostics.sort_by_position();
+
+	let positions: Vec<SourcePos> = diagnostics.0
+		.iter()
+		.map(|diagnostic| diagnostic.span.start)
+		.collect();
+
+	assert_eq!(
+		positions,
+		[ pos(1, 2, 2), pos(1, 4, 4), pos(2, 0, 9), pos(2, 0, 10), pos(3, 1, 20) ]
+	);
+}
+
+
+#[test]
+fn test_warnings_are_not_fatal() {
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+
+	let analyze = |input: &str, interner: &mut symbol::Interner| {
+		let source = Source { path, contents: input.as_bytes().into() };
+		let config = lexer::Config { raw_tab_warning: true, ..lexer::Config::default() };
+		let analysis = syntax::Analysis::analyze_with_config(&source, interner, config);
+
+		let config = lint::Config { if_without_else: true, ..lint::Config::default() };
+		let mut diagnostics = Diagnostics::default();
+		lint::check(&analysis.ast, &config, &mut diagnostics);
+
+		(analysis, diagnostics)
+	};
+
+	// A raw tab in a string literal, and an if without else.
+	let input = "let x = if true then \"a\tb\" end";
+
+	let (analysis, lints) = analyze(input, &mut interner);
+	assert_eq!(analysis.warnings.len(), 1);
+	assert!(!analysis.has_fatal_errors());
+	assert_eq!(lints.0.len(), 1);
+	assert!(!lints.has_errors());
+
+	let (analysis, _) = analyze(&format!("{}\nlet = 2", input), &mut interner);
+	assert_eq!(analysis.warnings.len(), 1);
+	assert!(analysis.has_fatal_errors());
+}
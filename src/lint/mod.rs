@@ -0,0 +1,501 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	diagnostic::{self, Diagnostic},
+	syntax::{ast, SourcePos},
+};
+
+
+/// Lint configuration. All lints are opt-in.
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+	/// Warn when the value of an if expression without an else branch is used, as it
+	/// silently evaluates to nil when the condition is false.
+	pub if_without_else: bool,
+	/// Warn about statements following an unconditional break or return in the same
+	/// block, as they will never be executed.
+	pub unreachable_code: bool,
+	/// Warn about command blocks nested in arithmetic or indexing expressions, where
+	/// their side effects are easily overlooked.
+	pub command_in_expr: bool,
+	/// Warn about assignments to a for loop's variable inside the loop, as the loop
+	/// overwrites it on each iteration.
+	pub loop_variable_assign: bool,
+	/// Warn about equality comparisons between an integer literal and a float literal, as
+	/// integers and floats never compare equal.
+	pub int_float_equality: bool,
+	/// Warn about parenthesized operands which would be parsed the same way without the
+	/// parentheses, as in `(a) + b`.
+	pub redundant_parens: bool,
+}
+
+
+/// A lint warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lint {
+	/// The value of an if expression without an else branch is used.
+	IfWithoutElse,
+	/// A statement can never be executed.
+	UnreachableCode,
+	/// A command block is nested in an arithmetic or indexing expression.
+	CommandInExpr,
+	/// A for loop's variable is assigned inside the loop.
+	LoopVariableAssign,
+	/// An integer literal is compared for equality with a float literal.
+	IntFloatEquality,
+	/// An operand is enclosed in redundant parentheses.
+	RedundantParens,
+}
+
+
+impl Lint {
+	/// A stable identifier for the lint.
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::IfWithoutElse => "if-without-else",
+			Self::UnreachableCode => "unreachable-code",
+			Self::CommandInExpr => "command-in-expr",
+			Self::LoopVariableAssign => "loop-variable-assign",
+			Self::IntFloatEquality => "int-float-equality",
+			Self::RedundantParens => "redundant-parens",
+		}
+	}
+}
+
+
+impl std::fmt::Display for Lint {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::IfWithoutElse => write!(f, "if expression without else used as a value"),
+			Self::UnreachableCode => write!(f, "unreachable statement"),
+			Self::CommandInExpr => write!(
+				f,
+				"command block in arithmetic or index, consider binding it to a variable first"
+			),
+			Self::LoopVariableAssign => write!(f, "assignment to loop variable inside the loop"),
+			Self::IntFloatEquality => write!(
+				f,
+				"integer compared for equality with float, which is always unequal"
+			),
+			Self::RedundantParens => write!(f, "redundant parentheses"),
+		}
+	}
+}
+
+
+/// Check the enabled lints in the given AST, reporting warnings as diagnostics.
+pub fn check<R>(ast: &ast::Ast, config: &Config, reporter: &mut R)
+where
+	R: diagnostic::Reporter,
+{
+	let mut linter = Linter { config, reporter, pure: false, bindings: Vec::new() };
+	// The value of the top level block is discarded.
+	linter.check_block(&ast.statements, false);
+}
+
+
+/// The lint checker.
+struct Linter<'a, R> {
+	config: &'a Config,
+	reporter: &'a mut R,
+	/// Whether the walk is inside an arithmetic or indexing expression.
+	pure: bool,
+	/// The variables in scope, innermost last, and whether each is a loop variable.
+	bindings: Vec<(ast::Symbol, bool)>,
+}
+
+
+impl<'a, R> Linter<'a, R>
+where
+	R: diagnostic::Reporter,
+{
+	/// Report a lint warning.
+	fn report(&mut self, lint: Lint, pos: SourcePos) {
+		self.reporter.report(Diagnostic::lint(lint, pos));
+	}
+
+
+	/// Check an expression in an arithmetic or indexing context.
+	fn check_pure(&mut self, expr: &ast::Expr) {
+		let pure = std::mem::replace(&mut self.pure, true);
+		self.check_expr(expr, true);
+		self.pure = pure;
+	}
+
+
+	/// If the expression is a number literal, possibly negated, whether it is a float.
+	fn number_literal(expr: &ast::Expr) -> Option<bool> {
+		match expr {
+			ast::Expr::Literal { literal: ast::Literal::Int(_), .. } => Some(false),
+			ast::Expr::Literal { literal: ast::Literal::Float(_), .. } => Some(true),
+			ast::Expr::UnaryOp { op: ast::UnaryOp::Minus, operand, .. }
+			| ast::Expr::Parenthesized { expr: operand, .. } => Self::number_literal(operand),
+			_ => None,
+		}
+	}
+
+
+	/// Check whether the given variable resolves to a loop variable.
+	fn is_loop_variable(&self, symbol: ast::Symbol) -> bool {
+		let binding = self.bindings
+			.iter()
+			.rev()
+			.find(|(binding, _)| *binding == symbol);
+
+		matches!(binding, Some((_, true)))
+	}
+
+
+	/// Report the parenthesized operands of an operator which would bind as tightly
+	/// without the parentheses. Parentheses outside operators are not considered.
+	fn check_parens(&mut self, expr: &ast::Expr) {
+		// Left operands may have the operator's precedence, as binary operators are left
+		// associative, but right operands must have a higher one.
+		let operands: [Option<(&ast::Expr, u8)>; 2] = match expr {
+			ast::Expr::BinaryOp { left, op, right, .. } => [
+				Some((left, op.precedence())),
+				Some((right, op.precedence() + 1)),
+			],
+
+			ast::Expr::UnaryOp { op, operand, .. } if !op.is_postfix() => {
+				[Some((operand, ast::Expr::PREFIX_PRECEDENCE)), None]
+			}
+
+			// Parentheses around a call on the right side of a pipeline prevent inserting the
+			// piped value in its arguments, so only other functions are considered.
+			ast::Expr::Call { function, args, .. } if expr.is_pipeline() => [
+				Some((&args[0], ast::Expr::PIPELINE_PRECEDENCE)),
+				Some((function.as_ref(), ast::Expr::PIPELINE_PRECEDENCE + 1))
+					.filter(
+						|(function, _)| !matches!(
+							function,
+							ast::Expr::Parenthesized { expr, .. } if matches!(**expr, ast::Expr::Call { .. })
+						)
+					),
+			],
+
+			ast::Expr::UnaryOp { operand: object, .. }
+			| ast::Expr::Access { object, .. }
+			| ast::Expr::Field { object, .. }
+			| ast::Expr::Call { function: object, .. } => {
+				[Some((object, ast::Expr::POSTFIX_PRECEDENCE)), None]
+			}
+
+			// Range bounds may be any binary operation.
+			ast::Expr::Range { start, end, .. } => [Some((start, 1)), Some((end, 1))],
+
+			_ => [None, None],
+		};
+
+		for &(operand, precedence) in operands.iter().flatten() {
+			match operand {
+				ast::Expr::Parenthesized { expr, span, .. } if expr.precedence() >= precedence => {
+					self.report(Lint::RedundantParens, span.start);
+				}
+
+				_ => (),
+			}
+		}
+	}
+
+
+	/// Check a block, given whether its value is used.
+	/// The value of a block is the value of its last statement.
+	fn check_block(&mut self, block: &ast::Block, used: bool) {
+		// Variables declared in the block go out of scope at its end.
+		let scope = self.bindings.len();
+		self.check_block_statements(block, used);
+		self.bindings.truncate(scope);
+	}
+
+
+	/// Check the statements of a block, given whether its value is used.
+	fn check_block_statements(&mut self, block: &ast::Block, used: bool) {
+		if let ast::Block::Block(statements) = block {
+			if self.config.unreachable_code {
+				self.check_unreachable(statements);
+			}
+
+			if let Some((last, init)) = statements.split_last() {
+				for statement in init {
+					self.check_statement(statement, false);
+				}
+
+				self.check_statement(last, used);
+			}
+		}
+	}
+
+
+	/// Report the first statement following an unconditional break or return.
+	/// Only the first unreachable statement is reported, as the rest of the block is
+	/// dead as well.
+	fn check_unreachable(&mut self, statements: &[ast::Statement]) {
+		let dead = statements
+			.iter()
+			.position(Self::diverges)
+			.and_then(
+				|ix| statements[ix + 1 ..]
+					.iter()
+					.find_map(ast::Statement::pos)
+			);
+
+		if let Some(pos) = dead {
+			self.report(Lint::UnreachableCode, pos);
+		}
+	}
+
+
+	/// Whether a statement unconditionally transfers control out of its block.
+	fn diverges(statement: &ast::Statement) -> bool {
+		match statement {
+			ast::Statement::Return { .. }
+			| ast::Statement::Break { .. }
+			| ast::Statement::Continue { .. } => true,
+
+			// An explicit scope is executed unconditionally.
+			ast::Statement::Scope { block: ast::Block::Block(statements), .. } => {
+				statements.iter().any(Self::diverges)
+			}
+
+			_ => false,
+		}
+	}
+
+
+	/// Check a statement, given whether its value is used.
+	fn check_statement(&mut self, statement: &ast::Statement, used: bool) {
+		match statement {
+			ast::Statement::IllFormed => (),
+
+			ast::Statement::Let { identifier, init, .. } => {
+				self.check_expr(init, true);
+				self.bindings.push((*identifier, false));
+			}
+
+			ast::Statement::LetDict { fields, init, .. } => {
+				self.check_expr(init, true);
+
+				for binding in fields.iter() {
+					self.bindings.push((binding.identifier, false));
+				}
+			}
+
+			ast::Statement::LetArray { items, init, .. } => {
+				self.check_expr(init, true);
+
+				for binding in items.iter() {
+					self.bindings.push((binding.identifier, false));
+				}
+			}
+
+			ast::Statement::Assign { left, right, pos, .. } => {
+				if let ast::Expr::Identifier { identifier, .. } = left {
+					if self.config.loop_variable_assign && self.is_loop_variable(*identifier) {
+						self.report(Lint::LoopVariableAssign, *pos);
+					}
+				}
+
+				self.check_expr(left, false);
+				self.check_expr(right, true);
+			}
+
+			ast::Statement::MultiAssign { left, right, pos, .. } => {
+				for left in left.iter() {
+					if let ast::Expr::Identifier { identifier, .. } = left {
+						if self.config.loop_variable_assign && self.is_loop_variable(*identifier) {
+							self.report(Lint::LoopVariableAssign, *pos);
+						}
+					}
+
+					self.check_expr(left, false);
+				}
+
+				for right in right.iter() {
+					self.check_expr(right, true);
+				}
+			}
+
+			ast::Statement::Return { expr, .. } => self.check_expr(expr, true),
+
+			ast::Statement::Break { expr, .. } => {
+				if let Some(expr) = expr {
+					self.check_expr(expr, false);
+				}
+			}
+
+			ast::Statement::Continue { .. } => (),
+
+			ast::Statement::While { condition, block, .. } => {
+				self.check_expr(condition, true);
+				self.check_block(block, false);
+			}
+
+			ast::Statement::For { identifier, expr, block, .. } => {
+				self.check_expr(expr, true);
+
+				self.bindings.push((*identifier, true));
+				self.check_block(block, false);
+				self.bindings.pop();
+			}
+
+			ast::Statement::Scope { block, .. } => self.check_block(block, used),
+
+			ast::Statement::Assert { condition, message, .. } => {
+				self.check_expr(condition, true);
+
+				if let Some(message) = message {
+					self.check_expr(message, true);
+				}
+			}
+
+			ast::Statement::Expr(expr) => self.check_expr(expr, used),
+		}
+	}
+
+
+	/// Check an expression, given whether its value is used.
+	fn check_expr(&mut self, expr: &ast::Expr, used: bool) {
+		if self.config.redundant_parens {
+			self.check_parens(expr);
+		}
+
+		match expr {
+			ast::Expr::IllFormed
+			| ast::Expr::Self_ { .. }
+			| ast::Expr::Identifier { .. } => (),
+
+			ast::Expr::CommandBlock { pos, .. } => {
+				if self.pure && self.config.command_in_expr {
+					self.report(Lint::CommandInExpr, *pos);
+				}
+			}
+
+			ast::Expr::Literal { literal, .. } => match literal {
+				ast::Literal::Array(items) => {
+					for item in items.iter() {
+						self.check_expr(item, true);
+					}
+				}
+
+				ast::Literal::Dict(items) => {
+					for item in items.iter() {
+						if let ast::DictKey::Expr(key) = &item.key {
+							self.check_expr(key, true);
+						}
+
+						self.check_expr(&item.value, true);
+					}
+				}
+
+				// The value of the body is the function's return value. The body is not
+				// part of the enclosing expression's context.
+				ast::Literal::Function { params, body, .. } => {
+					let pure = std::mem::replace(&mut self.pure, false);
+					let scope = self.bindings.len();
+
+					self.bindings.extend(params.iter().map(|param| (param.identifier, false)));
+
+					for default in params.iter().filter_map(|param| param.default.as_ref()) {
+						self.check_expr(default, true);
+					}
+
+					self.check_block(body, true);
+
+					self.bindings.truncate(scope);
+					self.pure = pure;
+				}
+
+				_ => (),
+			},
+
+			ast::Expr::UnaryOp { op: ast::UnaryOp::Minus, operand, .. } => self.check_pure(operand),
+
+			ast::Expr::UnaryOp { operand, .. } => self.check_expr(operand, true),
+
+			ast::Expr::BinaryOp { left, op, right, .. } if op.is_arithmetic() => {
+				self.check_pure(left);
+				self.check_pure(right);
+			}
+
+			ast::Expr::BinaryOp { left, op, right, pos, .. } => {
+				let equality = matches!(op, ast::BinaryOp::Equals | ast::BinaryOp::NotEquals);
+				let mixed = matches!(
+					(Self::number_literal(left), Self::number_literal(right)),
+					(Some(false), Some(true)) | (Some(true), Some(false))
+				);
+
+				if self.config.int_float_equality && equality && mixed {
+					self.report(Lint::IntFloatEquality, *pos);
+				}
+
+				self.check_expr(left, true);
+				self.check_expr(right, true);
+			}
+
+			ast::Expr::If { condition, then, otherwise, pos, .. } => {
+				if used && self.config.if_without_else && otherwise.is_empty() {
+					self.report(Lint::IfWithoutElse, *pos);
+				}
+
+				self.check_expr(condition, true);
+				self.check_block(then, used);
+				self.check_block(otherwise, used);
+			}
+
+			ast::Expr::When { condition, value, .. } => {
+				self.check_expr(condition, true);
+				self.check_expr(value, used);
+			}
+
+			ast::Expr::Match { scrutinee, arms, otherwise, .. } => {
+				self.check_expr(scrutinee, true);
+
+				for (pattern, block) in arms.iter() {
+					self.check_expr(pattern, true);
+					self.check_block(block, used);
+				}
+
+				self.check_block(otherwise, used);
+			}
+
+			ast::Expr::Access { object, field, .. } => {
+				self.check_expr(object, true);
+				self.check_pure(field);
+			}
+
+			ast::Expr::Field { object, .. } => self.check_expr(object, true),
+
+			ast::Expr::Call { function, args, .. } => {
+				self.check_expr(function, true);
+
+				for arg in args.iter() {
+					self.check_expr(arg, true);
+				}
+			}
+
+			ast::Expr::Comprehension { output, identifier, source, filter, .. } => {
+				self.check_expr(source, true);
+
+				self.bindings.push((*identifier, false));
+				self.check_expr(output, true);
+
+				if let Some(filter) = filter {
+					self.check_expr(filter, true);
+				}
+
+				self.bindings.pop();
+			}
+
+			ast::Expr::Spread { operand, .. } => self.check_expr(operand, true),
+
+			ast::Expr::Parenthesized { expr, .. } => self.check_expr(expr, used),
+
+			ast::Expr::Range { start, end, .. } => {
+				self.check_pure(start);
+				self.check_pure(end);
+			}
+		}
+	}
+}
@@ -0,0 +1,227 @@
+use crate::{
+	diagnostic::{DiagnosticKind, Diagnostics, Severity},
+	symbol,
+	syntax::{self, Source},
+};
+use super::{check, Config, Lint};
+
+
+/// Run the lints with the given configuration, returning the reported lints and their
+/// lines.
+fn lint(input: &str, config: &Config) -> Vec<(Lint, u32)> {
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	let analysis = syntax::Analysis::analyze(&source, &mut interner);
+	assert!(analysis.is_ok());
+
+	let mut diagnostics = Diagnostics::default();
+	check(&analysis.ast, config, &mut diagnostics);
+
+	diagnostics.0
+		.into_iter()
+		.map(|diagnostic| {
+			assert_eq!(diagnostic.severity, Severity::Warning);
+
+			match diagnostic.kind {
+				DiagnosticKind::Lint(lint) => (lint, diagnostic.span.start.line),
+				kind => panic!("unexpected diagnostic: {:?}", kind),
+			}
+		})
+		.collect()
+}
+
+
+#[test]
+fn test_if_without_else() {
+	let input = r#"
+		let c = true
+		let x = if c then 1 end
+		let y = if c then 1 else 2 end
+
+		if c then
+			std.print(1)
+		end
+
+		function f()
+			if c then 1 end
+		end
+
+		let z = if c then 1 elseif not c then 2 end
+	"#;
+
+	let config = Config { if_without_else: true, ..Config::default() };
+	assert_eq!(
+		lint(input, &config),
+		[
+			(Lint::IfWithoutElse, 3),
+			(Lint::IfWithoutElse, 11),
+			(Lint::IfWithoutElse, 14),
+		]
+	);
+
+	// Disabled by default.
+	assert_eq!(lint(input, &Config::default()), []);
+}
+
+
+#[test]
+fn test_unreachable_code() {
+	let input = r#"
+		while true do
+			break
+			let y = 1
+			std.print(y)
+		end
+
+		for x in std.iter([1, 2]) do
+			if x == 1 then
+				break
+			end
+			std.print(x)
+		end
+
+		function f()
+			begin
+				return 1
+			end
+			let y = 2
+		end
+
+		while true do
+			begin
+				break
+			end
+		end
+	"#;
+
+	let config = Config { unreachable_code: true, ..Config::default() };
+	assert_eq!(
+		lint(input, &config),
+		[
+			(Lint::UnreachableCode, 4),
+			(Lint::UnreachableCode, 19),
+		]
+	);
+
+	// Disabled by default.
+	assert_eq!(lint(input, &Config::default()), []);
+}
+
+
+#[test]
+fn test_command_in_expr() {
+	let input = r#"
+		let arr = [ 1, 2 ]
+		let x = arr[{ echo 0 }]
+		let y = 1 + ${ echo 1 }.stdout
+		let z = { echo 2 }
+		let w = arr[function() return { echo 3 } end]
+		let v = -${ echo 4 }
+	"#;
+
+	let config = Config { command_in_expr: true, ..Config::default() };
+	assert_eq!(
+		lint(input, &config),
+		[
+			(Lint::CommandInExpr, 3),
+			(Lint::CommandInExpr, 4),
+			(Lint::CommandInExpr, 7),
+		]
+	);
+
+	// Disabled by default.
+	assert_eq!(lint(input, &Config::default()), []);
+}
+
+
+#[test]
+fn test_loop_variable_assign() {
+	let input = r#"
+		let x = 0
+		for i in std.iter([ 1, 2 ]) do
+			i = 3
+			x = i
+			for j in std.iter([ 3 ]) do
+				i = j
+			end
+			let f = function(i)
+				i = 4
+			end
+		end
+		for k in std.iter([]) do
+			let k = 1
+			k = 2
+		end
+	"#;
+
+	let config = Config { loop_variable_assign: true, ..Config::default() };
+	assert_eq!(
+		lint(input, &config),
+		[
+			(Lint::LoopVariableAssign, 4),
+			(Lint::LoopVariableAssign, 7),
+		]
+	);
+
+	// Disabled by default.
+	assert_eq!(lint(input, &Config::default()), []);
+}
+
+
+#[test]
+fn test_int_float_equality() {
+	let input = r#"
+		let x = 1 == 1.0
+		let y = 1 + 2.0
+		let z = -2.5 != 2
+		let w = 1 == 1 or 1.0 == 1.0
+		let v = x == 1.0
+	"#;
+
+	let config = Config { int_float_equality: true, ..Config::default() };
+	assert_eq!(
+		lint(input, &config),
+		[
+			(Lint::IntFloatEquality, 2),
+			(Lint::IntFloatEquality, 4),
+		]
+	);
+
+	// Disabled by default.
+	assert_eq!(lint(input, &Config::default()), []);
+}
+
+
+#[test]
+fn test_redundant_parens() {
+	let input = r#"
+		let x = (a) + b
+		let y = (a + b) * c
+		let z = a - (b - c)
+		let w = (a - b) - c
+		let v = -(a.b)
+		let u = (a or b) and c
+		let t = (f)(x)
+		let s = (a + b).c
+		let r = ((a))
+		let q = (x + 1) |> (f) |> (g(1))
+	"#;
+
+	let config = Config { redundant_parens: true, ..Config::default() };
+	assert_eq!(
+		lint(input, &config),
+		[
+			(Lint::RedundantParens, 2),
+			(Lint::RedundantParens, 5),
+			(Lint::RedundantParens, 6),
+			(Lint::RedundantParens, 8),
+			(Lint::RedundantParens, 11),
+			(Lint::RedundantParens, 11),
+		]
+	);
+
+	// Disabled by default.
+	assert_eq!(lint(input, &Config::default()), []);
+}
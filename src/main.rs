@@ -1,8 +1,10 @@
 #![allow(dead_code)] // This is temporarily used for the inital development.
 
 mod args;
+mod diagnostic;
 mod fmt;
 mod io;
+mod lint;
 mod runtime;
 mod semantic;
 mod symbol;
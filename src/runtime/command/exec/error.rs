@@ -159,6 +159,12 @@ impl PipelineErrors {
 	pub fn is_empty(&self) -> bool {
 		self.0.is_empty()
 	}
+
+
+	/// The exit status of the first failed command in the pipeline, if any.
+	pub fn status(&self) -> Option<i32> {
+		self.0.first().map(|error| error.status)
+	}
 }
 
 impl IntoValue for PipelineErrors {
@@ -131,6 +131,30 @@ impl Display for Command {
 					command.fmt(f)?;
 				}
 			}
+
+			Self::Conditional { condition, then, otherwise, .. } => {
+				"if ".fmt(f)?;
+				condition.fmt(f)?;
+				"; then".fmt(f)?;
+
+				for command in then.iter() {
+					" ".fmt(f)?;
+					command.fmt(f)?;
+					";".fmt(f)?;
+				}
+
+				if !otherwise.is_empty() {
+					" else".fmt(f)?;
+
+					for command in otherwise.iter() {
+						" ".fmt(f)?;
+						command.fmt(f)?;
+						";".fmt(f)?;
+					}
+				}
+
+				" end".fmt(f)?;
+			}
 		}
 
 		Ok(())
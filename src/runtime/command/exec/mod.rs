@@ -392,7 +392,7 @@ pub struct CommandExec {
 }
 
 
-/// Commands may be pipelines, or a single BasicCommand.
+/// Commands may be builtins, pipelines, or conditionals.
 #[derive(Debug)]
 pub enum Command {
 	Builtin {
@@ -410,99 +410,158 @@ pub enum Command {
 		head: BasicCommand,
 		/// The following commands, if any.
 		tail: Box<[BasicCommand]>
-	}
+	},
+	Conditional {
+		/// The command whose exit status selects the branch.
+		condition: Box<Command>,
+		/// The commands to execute if the condition succeeds.
+		then: Box<[Command]>,
+		/// The commands to execute if the condition fails.
+		otherwise: Box<[Command]>,
+		/// Source position of the conditional.
+		pos: SourcePos,
+	},
 }
 
 
 impl Command {
-	/// Returns a pair of result value and whether to abort.
-	pub fn exec(
+	/// Execute the command, collecting the errors of the failed pipelines.
+	/// Returns whether to abort.
+	pub fn exec<F, G>(
 		self,
-		stdout: os_pipe::PipeWriter,
-		stderr: os_pipe::PipeWriter,
-	) -> Result<CommandExec, Error> {
-		match self {
+		stdout: &mut F,
+		stderr: &mut G,
+		errors: &mut Vec<PipelineErrors>,
+	) -> Result<bool, Error>
+	where
+		F: FnMut() -> io::Result<os_pipe::PipeWriter>,
+		G: FnMut() -> io::Result<os_pipe::PipeWriter>,
+	{
+		let result = match self {
 			Command::Builtin { program, arguments, abort_on_error, pos } => {
 				let error = program.exec(arguments, pos)?;
 				let abort = abort_on_error && error.is_some();
-				Ok(
-					CommandExec {
-						errors: error.into(),
-						abort,
-					}
-				)
+
+				CommandExec {
+					errors: error.into(),
+					abort,
+				}
 			}
 
 			Command::External { head, tail } => {
-				let mut last_stdout = stdout;
-				let mut last_stderr = stderr;
-
-				let mut tail_children = Vec::new();
-				for cmd in tail.into_vec().into_iter().rev() {
-					let child_abort_on_error = cmd.abort_on_error;
+				let pos = head.pos.copy();
 
-					let (pipe_reader, pipe_writer) = os_pipe::pipe()
-						.map_err(|error| Error::io(error, cmd.pos.copy()))?;
+				Self::exec_pipeline(
+					head,
+					tail,
+					stdout()
+						.map_err(|error| Error::io(error, pos.copy()))?,
+					stderr()
+						.map_err(|error| Error::io(error, pos))?,
+				)?
+			}
 
-					let child = cmd.exec(
-						Stdio {
-							stdin: pipe_reader,
-							stdout: last_stdout,
-							stderr: last_stderr,
-						}
-					)?;
+			Command::Conditional { condition, then, otherwise, .. } => {
+				// The failure of the condition is consumed by the conditional.
+				let mut condition_errors = Vec::new();
+				condition.exec(stdout, stderr, &mut condition_errors)?;
 
-					last_stdout = pipe_writer;
-					last_stderr = os_pipe::dup_stderr()
-						.map_err(|error| Error::io(error, child.pos.copy()))?;
+				let branch = if condition_errors.is_empty() { then } else { otherwise };
 
-					tail_children.push((child, child_abort_on_error));
+				for command in branch.into_vec() { // Use vec's owned iterator.
+					if command.exec(stdout, stderr, errors)? {
+						return Ok(true);
+					}
 				}
 
-				let head_abort_on_error = head.abort_on_error;
+				return Ok(false);
+			}
+		};
 
-				let stdin = os_pipe::dup_stdin()
-					.map_err(|error| Error::io(error, head.pos.copy()))?;
+		if !result.errors.is_empty() {
+			errors.push(result.errors);
+		}
 
-				let head_child = head.exec(
-					Stdio {
-						stdin,
-						stdout: last_stdout,
-						stderr: last_stderr,
-					}
-				)?;
+		Ok(result.abort)
+	}
 
-				let mut abort = false;
-				let mut errors = Vec::new();
 
-				// Wait on head command.
-				if let Some(error) = ErrorStatus::wait_child(head_child) {
-					abort |= head_abort_on_error;
-					errors.push(error);
-				}
+	/// Execute a pipeline.
+	/// Returns a pair of result value and whether to abort.
+	fn exec_pipeline(
+		head: BasicCommand,
+		tail: Box<[BasicCommand]>,
+		stdout: os_pipe::PipeWriter,
+		stderr: os_pipe::PipeWriter,
+	) -> Result<CommandExec, Error> {
+		let mut last_stdout = stdout;
+		let mut last_stderr = stderr;
 
-				// Wait on tail commands.
-				for (child, abort_on_error) in tail_children.into_iter().rev() {
-					if let Some(error) = ErrorStatus::wait_child(child) {
-						abort |= abort_on_error;
-						errors.push(error);
-					}
+		let mut tail_children = Vec::new();
+		for cmd in tail.into_vec().into_iter().rev() {
+			let child_abort_on_error = cmd.abort_on_error;
+
+			let (pipe_reader, pipe_writer) = os_pipe::pipe()
+				.map_err(|error| Error::io(error, cmd.pos.copy()))?;
+
+			let child = cmd.exec(
+				Stdio {
+					stdin: pipe_reader,
+					stdout: last_stdout,
+					stderr: last_stderr,
 				}
+			)?;
 
-				Ok(
-					CommandExec {
-						errors: errors.into(),
-						abort,
-					}
-				)
+			last_stdout = pipe_writer;
+			last_stderr = os_pipe::dup_stderr()
+				.map_err(|error| Error::io(error, child.pos.copy()))?;
+
+			tail_children.push((child, child_abort_on_error));
+		}
+
+		let head_abort_on_error = head.abort_on_error;
+
+		let stdin = os_pipe::dup_stdin()
+			.map_err(|error| Error::io(error, head.pos.copy()))?;
+
+		let head_child = head.exec(
+			Stdio {
+				stdin,
+				stdout: last_stdout,
+				stderr: last_stderr,
+			}
+		)?;
+
+		let mut abort = false;
+		let mut errors = Vec::new();
+
+		// Wait on head command.
+		if let Some(error) = ErrorStatus::wait_child(head_child) {
+			abort |= head_abort_on_error;
+			errors.push(error);
+		}
+
+		// Wait on tail commands.
+		for (child, abort_on_error) in tail_children.into_iter().rev() {
+			if let Some(error) = ErrorStatus::wait_child(child) {
+				abort |= abort_on_error;
+				errors.push(error);
 			}
 		}
+
+		Ok(
+			CommandExec {
+				errors: errors.into(),
+				abort,
+			}
+		)
 	}
 
 	pub fn pos(&self) -> SourcePos {
 		match self {
 			Command::Builtin { pos, .. } => pos.copy(),
 			Command::External { head, .. } => head.pos.copy(),
+			Command::Conditional { pos, .. } => pos.copy(),
 		}
 	}
 }
@@ -545,36 +604,10 @@ impl Block {
 	{
 		let mut errors = Vec::new();
 
-		let pos = self.head.pos();
-		let head = self.head.exec(
-			stdout()
-				.map_err(|error| Error::io(error, pos.copy()))?,
-			stderr()
-				.map_err(|error| Error::io(error, pos.copy()))?,
-		)?;
-
-		if !head.errors.is_empty() {
-			errors.push(head.errors);
-		}
-
-		if head.abort {
-			return Ok(errors.into())
-		}
-
-		for command in self.tail.into_vec() { // Use vec's owned iterator.
-			let pos = command.pos();
-			let child = command.exec(
-				stdout()
-					.map_err(|error| Error::io(error, pos.copy()))?,
-				stderr()
-					.map_err(|error| Error::io(error, pos.copy()))?,
-			)?;
-
-			if !child.errors.is_empty() {
-				errors.push(child.errors);
-			}
+		let commands = std::iter::once(self.head).chain(self.tail.into_vec()); // Use vec's owned iterator.
 
-			if child.abort {
+		for command in commands {
+			if command.exec(&mut stdout, &mut stderr, &mut errors)? {
 				break;
 			}
 		}
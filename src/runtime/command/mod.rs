@@ -40,11 +40,12 @@ impl Runtime {
 					.map_err(Into::into)
 			}
 
-			program::CommandBlockKind::Capture => {
+			program::CommandBlockKind::Capture | program::CommandBlockKind::CombinedCapture => {
 				thread_local! {
 					pub static ERROR: Value = "error".into();
 					pub static STDOUT: Value = "stdout".into();
 					pub static STDERR: Value = "stderr".into();
+					pub static STATUS: Value = "status".into();
 				}
 
 				let (mut stdout_read, stdout_write) = os_pipe::pipe()
@@ -73,6 +74,11 @@ impl Runtime {
 					)
 					.map_err(Panic::from)?;
 
+				let status = errors
+					.iter()
+					.find_map(exec::PipelineErrors::status)
+					.unwrap_or(0);
+
 				let mut result = errors.into_value(self.interner());
 				let mut captures = {
 					let out = match stdout_reader.join() {
@@ -101,6 +107,15 @@ impl Runtime {
 					dict
 				};
 
+				// The combined capture reports failures through the status instead of an error.
+				if let program::CommandBlockKind::CombinedCapture = block.kind {
+					STATUS.with(
+						|key| captures.insert(key.copy(), Value::Int(status as i64))
+					);
+
+					return Ok(Dict::new(captures).into());
+				}
+
 				match &mut result {
 					Value::Nil => Ok(Dict::new(captures).into()),
 					Value::Error(error) => {
@@ -198,6 +213,31 @@ impl Runtime {
 
 				Ok(exec::Command::External { head, tail })
 			}
+
+			program::Command::Conditional { condition, then, otherwise, pos } => {
+				let condition = self.build_command(condition)?;
+				let then = then
+					.iter()
+					.map(
+						|cmd| self.build_command(cmd)
+					)
+					.collect::<Result<_, Panic>>()?;
+				let otherwise = otherwise
+					.iter()
+					.map(
+						|cmd| self.build_command(cmd)
+					)
+					.collect::<Result<_, Panic>>()?;
+
+				Ok(
+					exec::Command::Conditional {
+						condition: Box::new(condition),
+						then,
+						otherwise,
+						pos: pos.into(),
+					}
+				)
+			}
 		}
 	}
 
@@ -446,6 +486,22 @@ impl Runtime {
 					args.push_pattern(class);
 					args.push_pattern(b"]");
 				}
+
+				program::ArgPart::Splat { slot_ix, pos } => {
+					let value = self.stack.fetch(slot_ix.into());
+
+					let array = match value {
+						Value::Array(ref array) => array.borrow(),
+						other => return Err(Panic::type_error(other, "array", pos.into())),
+					};
+
+					let literals: Vec<Box<[u8]>> = array
+						.iter()
+						.map(|val| Self::build_basic_value(val.copy(), pos.into()))
+						.collect::<Result<_, Panic>>()?;
+
+					args.push_literals(literals.iter());
+				}
 			}
 		}
 
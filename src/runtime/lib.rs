@@ -17,6 +17,9 @@ use super::{
 };
 
 
+pub use range::iter as range;
+
+
 inventory::collect!(RustFun);
 
 
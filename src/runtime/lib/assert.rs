@@ -20,7 +20,7 @@ impl NativeFun for Assert {
 	fn call(&self, context: CallContext) -> Result<Value, Panic> {
 		match context.args() {
 			[ Value::Bool(true) ] => Ok(Value::default()),
-			[ Value::Bool(false) ] => Err(Panic::assertion_failed(context.pos)),
+			[ Value::Bool(false) ] => Err(Panic::assertion_failed(None, context.pos)),
 
 			[ other ] => Err(Panic::type_error(other.copy(), "bool", context.pos)),
 			args => Err(Panic::invalid_args(args.len() as u32, 1, context.pos))
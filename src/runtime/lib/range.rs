@@ -52,6 +52,21 @@ impl NativeFun for Range {
 }
 
 
+/// The iterator of a range expression, as in `1..10` or `1..=10`. The range counts down
+/// if the end is lower than the start.
+pub fn iter(from: i64, to: i64, inclusive: bool) -> Value {
+	let step = if to < from { -1 } else { 1 };
+
+	let to = match (inclusive, step) {
+		(false, _) => to,
+		(true, 1) => to.saturating_add(1),
+		(true, _) => to.saturating_sub(1),
+	};
+
+	RangeImpl { from: GcCell::new(from), to, step }.into()
+}
+
+
 #[derive(Trace, Finalize)]
 struct RangeImpl<T: 'static> {
 	from: GcCell<T>,
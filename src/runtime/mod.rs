@@ -190,11 +190,8 @@ impl Runtime {
 			program::Literal::Array(exprs) => {
 				let mut array = Vec::new();
 
-				for expr in exprs.iter() {
-					match self.eval_expr(expr)?.0 {
-						Flow::Regular(value) => array.push(value),
-						flow => return Ok(flow),
-					}
+				if let Some(flow) = self.eval_items(exprs, |_, value| array.push(value))? {
+					return Ok(flow);
 				}
 
 				Ok(Flow::Regular(Array::new(array).into()))
@@ -204,11 +201,18 @@ impl Runtime {
 			program::Literal::Dict(exprs) => {
 				let mut dict = HashMap::new();
 
-				for (symbol, expr) in exprs.iter() {
-					let key: Value = self.interner
-						.resolve(*symbol)
-						.expect("unresolved symbol")
-						.into();
+				for (key, expr) in exprs.iter() {
+					let key: Value = match key {
+						program::DictKey::Symbol(symbol) => self.interner
+							.resolve(*symbol)
+							.expect("unresolved symbol")
+							.into(),
+
+						program::DictKey::Expr(expr) => match self.eval_expr(expr)?.0 {
+							Flow::Regular(value) => value,
+							flow => return Ok(flow),
+						},
+					};
 
 					match self.eval_expr(expr)?.0 {
 						Flow::Regular(value) => dict.insert(key, value),
@@ -220,7 +224,7 @@ impl Runtime {
 			}
 
 			// Function.
-			program::Literal::Function { params, frame_info, body } => {
+			program::Literal::Function { params, defaults, frame_info, body } => {
 				let context = frame_info
 					.captures
 					.iter()
@@ -234,7 +238,7 @@ impl Runtime {
 
 				Ok(
 					Flow::Regular(
-						HushFun::new(*params, frame_info, body, context, pos.into()).into()
+						HushFun::new(*params, defaults, frame_info, body, context, pos.into()).into()
 					)
 				)
 			},
@@ -365,6 +369,18 @@ impl Runtime {
 				Ok((Flow::Regular(value), pos, obj))
 			}
 
+			// Item.
+			program::Expr::Item { array, index, pos } => {
+				let pos = pos.into();
+
+				let value = match regular_expr!(array, pos) {
+					(Value::Array(ref array), _) => array.index(*index).unwrap_or_default(),
+					(value, pos) => return Err(Panic::type_error(value, "array", pos)),
+				};
+
+				Ok((Flow::Regular(value), pos, Value::default()))
+			}
+
 			// Call.
 			program::Expr::Call { function, args, pos } => {
 				let pos = pos.into();
@@ -381,14 +397,11 @@ impl Runtime {
 				// keep track of when our arguments start.
 				let args_start = self.arguments.len();
 
-				for expr in args.iter() {
-					match self.eval_expr(expr)? {
-						(Flow::Regular(value), _, _) => self.arguments.push(value),
-						(flow, _, _) => {
-							self.arguments.truncate(args_start);
-							return Ok((flow, pos, Value::default()));
-						}
-					}
+				let flow = self.eval_items(args, |runtime, value| runtime.arguments.push(value))?;
+
+				if let Some(flow) = flow {
+					self.arguments.truncate(args_start);
+					return Ok((flow, pos, Value::default()));
 				}
 
 				tail_call(self);
@@ -403,6 +416,59 @@ impl Runtime {
 				let value = self.eval_command_block(block, pos.into())?;
 				Ok((Flow::Regular(value), pos.into(), Value::default()))
 			}
+
+			// Comprehension.
+			program::Expr::Comprehension { slot_ix, output, source, filter, pos } => {
+				let pos: SourcePos = pos.into();
+				let slot_ix: mem::SlotIx = slot_ix.into();
+
+				let (iter, iter_pos) = match self.eval_expr(source)? {
+					(Flow::Regular(Value::Function(ref iter)), pos, _) => (iter.copy(), pos),
+					(Flow::Regular(value), pos, _) => return Err(Panic::type_error(value, "function", pos)),
+					(flow, _, _) => return Ok((flow, pos, Value::default()))
+				};
+
+				let mut array = Vec::new();
+
+				while let Some(value) = self.next_item(&iter, iter_pos.copy())? {
+					self.stack.store(slot_ix.copy(), value);
+
+					if let Some(filter) = filter {
+						match self.eval_expr(filter)? {
+							(Flow::Regular(Value::Bool(true)), _, _) => (),
+							(Flow::Regular(Value::Bool(false)), _, _) => continue,
+							(Flow::Regular(value), pos, _) => return Err(Panic::invalid_condition(value, pos)),
+							(flow, _, _) => return Ok((flow, pos, Value::default()))
+						}
+					}
+
+					let (value, _) = regular_expr!(output, pos);
+
+					array.push(value);
+				}
+
+				Ok((Flow::Regular(Array::new(array).into()), pos, Value::default()))
+			}
+
+			// Spread, which is evaluated by eval_items.
+			program::Expr::Spread { .. } => panic!("spread outside array or call"),
+
+			// Range.
+			program::Expr::Range { start, end, inclusive, pos } => {
+				let pos = pos.into();
+
+				let start = match regular_expr!(start, pos) {
+					(Value::Int(start), _) => start,
+					(value, pos) => return Err(Panic::type_error(value, "int", pos)),
+				};
+
+				let end = match regular_expr!(end, pos) {
+					(Value::Int(end), _) => end,
+					(value, pos) => return Err(Panic::type_error(value, "int", pos)),
+				};
+
+				Ok((Flow::Regular(lib::range(start, end, *inclusive)), pos, Value::default()))
+			}
 		}
 	}
 
@@ -479,7 +545,18 @@ impl Runtime {
 			}
 
 			// Break.
-			program::Statement::Break => Ok(Flow::Break),
+			program::Statement::Break { expr } => {
+				if let Some(expr) = expr {
+					if let flow @ Flow::Return(_) = self.eval_expr(expr)?.0 {
+						return Ok(flow);
+					}
+				}
+
+				Ok(Flow::Break)
+			}
+
+			// Continue.
+			program::Statement::Continue => Ok(Flow::Continue),
 
 			// While.
 			program::Statement::While { condition, block } => {
@@ -495,7 +572,7 @@ impl Runtime {
 					}
 
 					match self.eval_block(block)? {
-						Flow::Regular(_) => (),
+						Flow::Regular(_) | Flow::Continue => (),
 						flow @ Flow::Return(_) => return Ok(flow),
 						Flow::Break => break,
 					}
@@ -514,42 +591,11 @@ impl Runtime {
 					(flow, _, _) => return Ok(flow)
 				};
 
-				loop {
-					// While evaluating arguments, we may need to call other functions, so we must
-					// keep track of when our arguments start.
-					let args_start = self.arguments.len();
-					match self.call(Value::default(), &iter, args_start, pos.copy())? {
-						Value::Dict(ref dict) => {
-							let finished = keys::FINISHED.with(
-								|finished| dict
-									.get(finished)
-									.map_err(|_| Panic::index_out_of_bounds(finished.copy(), pos.copy()))
-							)?;
-
-							match finished {
-								Value::Bool(false) => {
-									let value = keys::VALUE.with(
-										|value| dict
-											.get(value)
-											.map_err(|_| Panic::index_out_of_bounds(value.copy(), pos.copy()))
-									)?;
-
-									self.stack.store(slot_ix.copy(), value);
-								},
-
-								Value::Bool(true) => break,
-
-								other => return Err(Panic::type_error(other, "bool", pos))
-							}
-
-							Value::Nil
-						},
-
-						other => return Err(Panic::type_error(other, "dict", pos)),
-					};
+				while let Some(value) = self.next_item(&iter, pos.copy())? {
+					self.stack.store(slot_ix.copy(), value);
 
 					match self.eval_block(block)? {
-						Flow::Regular(_) => (),
+						Flow::Regular(_) | Flow::Continue => (),
 						flow @ Flow::Return(_) => return Ok(flow),
 						Flow::Break => break,
 					}
@@ -558,6 +604,32 @@ impl Runtime {
 				Ok(Flow::Regular(Value::default()))
 			}
 
+			// Scope.
+			program::Statement::Scope { block } => self.eval_block(block),
+
+			// Assert.
+			program::Statement::Assert { condition, message, pos } => {
+				let condition = match self.eval_expr(condition)? {
+					(Flow::Regular(Value::Bool(b)), _, _) => b,
+					(Flow::Regular(value), pos, _) => return Err(Panic::invalid_condition(value, pos)),
+					(flow, _, _) => return Ok(flow)
+				};
+
+				if condition {
+					return Ok(Flow::Regular(Value::default()));
+				}
+
+				let message = match message {
+					Some(message) => match self.eval_expr(message)?.0 {
+						Flow::Regular(message) => Some(message),
+						flow => return Ok(flow),
+					},
+					None => None,
+				};
+
+				Err(Panic::assertion_failed(message, pos.into()))
+			}
+
 			// Expr.
 			program::Statement::Expr(expr) => self
 				.eval_tail_expr(expr, tail_call)
@@ -566,6 +638,77 @@ impl Runtime {
 	}
 
 
+	/// Evaluate the items of an array literal or the arguments of a call, in order, passing
+	/// each value to the given function. Spread arrays are passed item by item.
+	/// Returns the flow if it is not regular, in which case evaluation stops.
+	fn eval_items<F>(
+		&mut self,
+		exprs: &'static [program::Expr],
+		mut push: F,
+	) -> Result<Option<Flow>, Panic>
+	where
+		F: FnMut(&mut Self, Value),
+	{
+		for expr in exprs.iter() {
+			match expr {
+				program::Expr::Spread { operand, .. } => match self.eval_expr(operand)? {
+					(Flow::Regular(Value::Array(ref array)), _, _) => {
+						for value in array.borrow().iter() {
+							push(self, value.copy());
+						}
+					}
+					(Flow::Regular(value), pos, _) => return Err(Panic::type_error(value, "array", pos)),
+					(flow, _, _) => return Ok(Some(flow)),
+				},
+
+				expr => match self.eval_expr(expr)? {
+					(Flow::Regular(value), _, _) => push(self, value),
+					(flow, _, _) => return Ok(Some(flow)),
+				},
+			}
+		}
+
+		Ok(None)
+	}
+
+
+	/// Fetch the next item from an iterator function.
+	/// Returns None when the iterator is finished.
+	fn next_item(&mut self, iter: &Function, pos: SourcePos) -> Result<Option<Value>, Panic> {
+		// While evaluating arguments, we may need to call other functions, so we must
+		// keep track of when our arguments start.
+		let args_start = self.arguments.len();
+
+		match self.call(Value::default(), iter, args_start, pos.copy())? {
+			Value::Dict(ref dict) => {
+				let finished = keys::FINISHED.with(
+					|finished| dict
+						.get(finished)
+						.map_err(|_| Panic::index_out_of_bounds(finished.copy(), pos.copy()))
+				)?;
+
+				match finished {
+					Value::Bool(false) => {
+						let value = keys::VALUE.with(
+							|value| dict
+								.get(value)
+								.map_err(|_| Panic::index_out_of_bounds(value.copy(), pos.copy()))
+						)?;
+
+						Ok(Some(value))
+					},
+
+					Value::Bool(true) => Ok(None),
+
+					other => Err(Panic::type_error(other, "bool", pos))
+				}
+			},
+
+			other => Err(Panic::type_error(other, "dict", pos)),
+		}
+	}
+
+
 	/// Call the given function.
 	/// The arguments are expected to be on the self.arguments vector.
 	fn call(
@@ -577,13 +720,19 @@ impl Runtime {
 	) -> Result<Value, Panic> {
 
 		let value = match function {
-			Function::Hush(HushFun { params, frame_info, body, context, .. }) => {
+			Function::Hush(HushFun { params, defaults, frame_info, body, context, .. }) => {
 				let args_count = (self.arguments.len() - args_start) as u32;
 
 				// Make sure we clean the arguments vector even when early returning.
 				let arguments = self.arguments.drain(args_start..);
 
-				if args_count != *params {
+				let required = *params - defaults.len() as u32;
+
+				if args_count < required {
+					return Err(Panic::invalid_args(args_count, required, pos));
+				}
+
+				if args_count > *params {
 					return Err(Panic::invalid_args(args_count, *params, pos));
 				}
 
@@ -606,6 +755,34 @@ impl Runtime {
 					self.stack.store(slot_ix.into(), obj);
 				}
 
+				// Place defaults for omitted arguments, which may refer to the previous ones.
+				for ix in args_count .. *params {
+					let default = &defaults[(ix - required) as usize];
+
+					let flow = self.eval_expr(default)
+						.map(|(flow, _, _)| flow);
+
+					let value = match flow {
+						Ok(Flow::Regular(value)) => value,
+
+						// A return in a default value returns from the function.
+						Ok(Flow::Return(value)) => {
+							self.stack.shrink(slots);
+							return Ok(value);
+						}
+
+						Ok(Flow::Break) => panic!("break outside loop"),
+						Ok(Flow::Continue) => panic!("continue outside loop"),
+
+						Err(panic) => {
+							self.stack.shrink(slots);
+							return Err(panic);
+						}
+					};
+
+					self.stack.store(mem::SlotIx(ix), value);
+				}
+
 				let mut shrinked = false;
 
 				let result = self.eval_tail_block(
@@ -627,6 +804,7 @@ impl Runtime {
 					Flow::Regular(value) => value,
 					Flow::Return(value) => value,
 					Flow::Break => panic!("break outside loop"),
+					Flow::Continue => panic!("continue outside loop"),
 				}
 			}
 
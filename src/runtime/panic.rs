@@ -79,8 +79,11 @@ pub enum Panic {
 		pattern: OsString,
 		pos: SourcePos,
 	},
-	/// Assertion failed.
-	AssertionFailed { pos: SourcePos },
+	/// Assertion failed, optionally with a message.
+	AssertionFailed {
+		message: Option<Value>,
+		pos: SourcePos,
+	},
 	/// Failed to import module.
 	ImportFailed {
 		pos: SourcePos,
@@ -103,9 +106,9 @@ impl Panic {
 	}
 
 
-	/// Assertion failed.
-	pub fn assertion_failed(pos: SourcePos) -> Self {
-		Self::AssertionFailed { pos }
+	/// Assertion failed, optionally with a message.
+	pub fn assertion_failed(message: Option<Value>, pos: SourcePos) -> Self {
+		Self::AssertionFailed { message, pos }
 	}
 
 
@@ -337,9 +340,18 @@ impl<'a> Display<'a> for Panic {
 					color::Fg(color::Yellow, fmt::Show(field, context))
 				),
 
-			Self::AssertionFailed { pos } =>
+			Self::AssertionFailed { message: None, pos } =>
 				write!(f, "{} in {}: assertion failed", panic, fmt::Show(pos, context)),
 
+			Self::AssertionFailed { message: Some(message), pos } =>
+				write!(
+					f,
+					"{} in {}: assertion failed ({})",
+					panic,
+					fmt::Show(pos, context),
+					color::Fg(color::Yellow, fmt::Show(message, context))
+				),
+
 			Self::ImportFailed { path, pos } =>
 				write!(
 					f,
@@ -66,6 +66,8 @@ impl<T: NativeFun> From<T> for Function {
 pub struct HushFun {
 	/// How many parameters the function expects.
 	pub params: u32,
+	/// The default values of the trailing parameters.
+	pub defaults: &'static [program::Expr],
 	pub frame_info: &'static program::mem::FrameInfo,
 	pub body: &'static program::Block,
 	/// Captured variables, if any.
@@ -78,6 +80,7 @@ pub struct HushFun {
 impl HushFun {
 	pub fn new (
 		params: u32,
+		defaults: &'static [program::Expr],
 		frame_info: &'static program::mem::FrameInfo,
 		body: &'static program::Block,
 		context: Box<[(Gc<GcCell<Value>>, mem::SlotIx)]>,
@@ -85,6 +88,7 @@ impl HushFun {
 	) -> Self {
 		Self {
 			params,
+			defaults,
 			frame_info,
 			body,
 			context: Gc::new(context),
@@ -97,6 +101,7 @@ impl HushFun {
 	pub fn copy(&self) -> Self {
 		Self {
 			params: self.params,
+			defaults: self.defaults,
 			frame_info: self.frame_info,
 			body: self.body,
 			context: self.context.clone(),
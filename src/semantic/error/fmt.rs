@@ -35,7 +35,7 @@ impl<'a> Display<'a> for ErrorKind {
 				"'".fmt(f)
 			}
 
-			Self::DuplicateKey(symbol) => {
+			Self::DuplicateKey(symbol, _) => {
 				"duplicate key '".fmt(f)?;
 				symbol.fmt(f, context)?;
 				"'".fmt(f)
@@ -49,8 +49,18 @@ impl<'a> Display<'a> for ErrorKind {
 
 			Self::BreakOutsideLoop => write!(f, "break statement outside loop"),
 
+			Self::ContinueOutsideLoop => write!(f, "continue statement outside loop"),
+
 			Self::InvalidAssignment => write!(f, "invalid assignment"),
 
+			Self::ConstAssignment(symbol, _) => {
+				"assignment to constant '".fmt(f)?;
+				symbol.fmt(f, context)?;
+				"'".fmt(f)
+			}
+
+			Self::InvalidSpread => write!(f, "spread operator outside array or call arguments"),
+
 			Self::AsyncBuiltin => write!(f, "use of built-in command in async context"),
 		}
 	}
@@ -12,8 +12,8 @@ pub enum ErrorKind {
 	/// Variable declared with the same name twice in the same scope.
 	/// Includes function parameters.
 	DuplicateVariable(Symbol),
-	/// Duplicate keys in dict literal.
-	DuplicateKey(Symbol),
+	/// Duplicate keys in dict literal, along with the position of the original key.
+	DuplicateKey(Symbol, SourcePos),
 	/// Return statement outside function.
 	ReturnOutsideFunction,
 	/// Self keyword outside function.
@@ -22,8 +22,14 @@ pub enum ErrorKind {
 	TryOutsideFunction,
 	/// Break statement outside loop.
 	BreakOutsideLoop,
+	/// Continue statement outside loop.
+	ContinueOutsideLoop,
 	/// Invalid assignment l-value.
 	InvalidAssignment,
+	/// Assignment to a constant variable, along with the position of its declaration.
+	ConstAssignment(Symbol, SourcePos),
+	/// Spread operator outside array literal or call arguments.
+	InvalidSpread,
 	/// Built-in command used in async context.
 	/// Async contexts include pipes, redirections and capture or async blocks.
 	AsyncBuiltin,
@@ -58,10 +64,10 @@ impl Error {
 	}
 
 
-	/// Duplicate keys in dict literal.
-	pub fn duplicate_key(symbol: Symbol, pos: SourcePos) -> Self {
+	/// Duplicate keys in dict literal, along with the position of the original key.
+	pub fn duplicate_key(symbol: Symbol, pos: SourcePos, original: SourcePos) -> Self {
 		Self {
-			kind: ErrorKind::DuplicateKey(symbol),
+			kind: ErrorKind::DuplicateKey(symbol, original),
 			pos
 		}
 	}
@@ -103,6 +109,15 @@ impl Error {
 	}
 
 
+	/// Continue statement outside loop.
+	pub fn continue_outside_loop(pos: SourcePos) -> Self {
+		Self {
+			kind: ErrorKind::ContinueOutsideLoop,
+			pos
+		}
+	}
+
+
 	/// Invalid assignment l-value.
 	pub fn invalid_assignment(pos: SourcePos) -> Self {
 		Self {
@@ -112,6 +127,24 @@ impl Error {
 	}
 
 
+	/// Assignment to a constant variable.
+	pub fn const_assignment(symbol: Symbol, pos: SourcePos, declaration: SourcePos) -> Self {
+		Self {
+			kind: ErrorKind::ConstAssignment(symbol, declaration),
+			pos
+		}
+	}
+
+
+	/// Spread operator outside array literal or call arguments.
+	pub fn invalid_spread(pos: SourcePos) -> Self {
+		Self {
+			kind: ErrorKind::InvalidSpread,
+			pos
+		}
+	}
+
+
 	/// Built-in command used in async context.
 	/// Async contexts include pipes redirections and capture or async blocks.
 	pub fn async_builtin(pos: SourcePos) -> Self {
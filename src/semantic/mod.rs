@@ -5,11 +5,12 @@ pub mod program;
 mod tests;
 
 use std::{
-	collections::HashSet,
+	collections::{hash_map::Entry, HashMap},
 	convert::TryFrom,
 };
 
 use crate::{
+	diagnostic,
 	symbol::{self, Symbol},
 	syntax::ast::{IllFormed, UnaryOp}
 };
@@ -24,6 +25,7 @@ use program::{
 	Block,
 	Command,
 	CommandBlock,
+	DictKey,
 	Expr,
 	Literal,
 	Lvalue,
@@ -42,8 +44,8 @@ pub struct Analyzer<'a> {
 	errors: &'a mut Errors,
 	/// Scope stack to check declared variables.
 	scope: &'a mut scope::Stack,
-	/// Hashset to check duplicate symbols in dict keys.
-	dict_keys: &'a mut HashSet<Symbol>,
+	/// Hashmap to check duplicate symbols in dict keys, along with their positions.
+	dict_keys: &'a mut HashMap<Symbol, SourcePos>,
 	/// The symbol interner.
 	interner: &'a mut symbol::Interner,
 	/// Whether the analyzer is inside a function.
@@ -62,7 +64,7 @@ impl<'a> Analyzer<'a> {
 	/// syntactic analysis.
 	pub fn analyze(ast: ast::Ast, interner: &mut symbol::Interner) -> Result<Program, Errors> {
 		let mut scope = scope::Stack::default();
-		let mut dict_keys = HashSet::default();
+		let mut dict_keys = HashMap::default();
 		let mut errors = Errors::default();
 
 		let (result, root_frame) = {
@@ -87,6 +89,25 @@ impl<'a> Analyzer<'a> {
 	}
 
 
+	/// Perform static semantic analysis in the given AST, emitting errors as diagnostics.
+	pub fn analyze_with<R>(
+		ast: ast::Ast,
+		interner: &mut symbol::Interner,
+		reporter: &mut R
+	) -> Option<Program>
+	where
+		R: diagnostic::Reporter,
+	{
+		Self::analyze(ast, interner)
+			.map_err(
+				|errors| errors
+					.into_iter()
+					.for_each(|error| reporter.report(error.into()))
+			)
+			.ok()
+	}
+
+
 	/// Analyze a block.
 	/// None is returned if any error is detected.
 	fn analyze_block(&mut self, block: ast::Block) -> Option<Block> {
@@ -110,9 +131,15 @@ impl<'a> Analyzer<'a> {
 	fn analyze_statement(&mut self, statement: ast::Statement) -> Option<Statement> {
 		match statement {
 			// Let.
-			ast::Statement::Let { identifier, init, pos } => {
-				let slot_ix = self.scope
-					.declare(identifier, pos)
+			ast::Statement::Let { identifier, init, is_const, pos, .. } => {
+				let slot_ix =
+					if is_const {
+						self.scope.declare_const(identifier, pos)
+					} else {
+						self.scope.declare(identifier, pos)
+					};
+
+				let slot_ix = slot_ix
 					.map_err(
 						|error| self.report(error)
 					)
@@ -127,8 +154,139 @@ impl<'a> Analyzer<'a> {
 				Some(Statement::Assign { left, right })
 			}
 
+			// Dict destructuring, which is lowered into assignments from a hidden variable
+			// holding the dict. The assignments are wrapped in an unconditional if, which
+			// provides the scope for the hidden variable. Missing fields panic at runtime.
+			ast::Statement::LetDict { fields, init, pos, .. } => {
+				let init = self.analyze_expr(init);
+
+				let bindings = self.analyze_items(
+					|analyzer, binding| {
+						let slot_ix = analyzer.scope
+							.declare(binding.identifier, binding.pos)
+							.map_err(
+								|error| analyzer.report(error)
+							)
+							.ok()?;
+
+						let field = Self::analyze_field_name(binding.field, binding.pos)?;
+
+						Some((slot_ix, field, binding.pos))
+					},
+					fields.into_vec(), // Use vec's owned iterator.
+				);
+
+				let (init, bindings) = init.zip(bindings)?;
+
+				let analyzer = self.enter_block();
+
+				// This is not a valid identifier, so it can't clash with user variables.
+				let symbol = analyzer.interner.get_or_intern("<destructure>");
+				let dict_ix = analyzer.scope
+					.declare(symbol, pos)
+					.expect("the dict should be the only variable in its scope");
+
+				let statements: Vec<Statement> = std::iter::once(
+					Statement::Assign {
+						left: Lvalue::Identifier { slot_ix: dict_ix, pos },
+						right: init,
+					}
+				)
+					.chain(
+						bindings
+							.into_vec()
+							.into_iter()
+							.map(
+								|(slot_ix, field, pos)| Statement::Assign {
+									left: Lvalue::Identifier { slot_ix, pos },
+									right: Expr::Access {
+										object: Box::new(Expr::Identifier { slot_ix: dict_ix, pos }),
+										field: Box::new(field),
+										pos,
+									},
+								}
+							)
+					)
+					.collect();
+
+				Some(
+					Statement::Expr(
+						Expr::If {
+							condition: Box::new(Expr::Literal { literal: Literal::Bool(true), pos }),
+							then: Block::from(statements.into_boxed_slice()),
+							otherwise: Block::default(),
+							pos,
+						}
+					)
+				)
+			}
+
+			// Array destructuring, which is lowered like dict destructuring. Missing items are
+			// nil.
+			ast::Statement::LetArray { items, init, pos, .. } => {
+				let init = self.analyze_expr(init);
+
+				let bindings = self.analyze_items(
+					|analyzer, binding| {
+						analyzer.scope
+							.declare(binding.identifier, binding.pos)
+							.map_err(
+								|error| analyzer.report(error)
+							)
+							.ok()
+							.map(|slot_ix| (slot_ix, binding.pos))
+					},
+					items.into_vec(), // Use vec's owned iterator.
+				);
+
+				let (init, bindings) = init.zip(bindings)?;
+
+				let analyzer = self.enter_block();
+
+				// This is not a valid identifier, so it can't clash with user variables.
+				let symbol = analyzer.interner.get_or_intern("<destructure>");
+				let array_ix = analyzer.scope
+					.declare(symbol, pos)
+					.expect("the array should be the only variable in its scope");
+
+				let statements: Vec<Statement> = std::iter::once(
+					Statement::Assign {
+						left: Lvalue::Identifier { slot_ix: array_ix, pos },
+						right: init,
+					}
+				)
+					.chain(
+						bindings
+							.into_vec()
+							.into_iter()
+							.zip(0..)
+							.map(
+								|((slot_ix, pos), index)| Statement::Assign {
+									left: Lvalue::Identifier { slot_ix, pos },
+									right: Expr::Item {
+										array: Box::new(Expr::Identifier { slot_ix: array_ix, pos }),
+										index,
+										pos,
+									},
+								}
+							)
+					)
+					.collect();
+
+				Some(
+					Statement::Expr(
+						Expr::If {
+							condition: Box::new(Expr::Literal { literal: Literal::Bool(true), pos }),
+							then: Block::from(statements.into_boxed_slice()),
+							otherwise: Block::default(),
+							pos,
+						}
+					)
+				)
+			}
+
 			// Assign.
-			ast::Statement::Assign { left, right, pos } => {
+			ast::Statement::Assign { left, right, pos, .. } => {
 				let left = self
 					.analyze_lvalue(left)
 					.map_err(
@@ -145,8 +303,85 @@ impl<'a> Analyzer<'a> {
 				Some(Statement::Assign { left, right })
 			}
 
+			// Multiple assignment, which is lowered into assignments to hidden variables holding
+			// the values, followed by assignments from those. The assignments are wrapped in an
+			// unconditional if, which provides the scope for the hidden variables.
+			ast::Statement::MultiAssign { left, right, pos, .. } => {
+				let left = self.analyze_items(
+					|analyzer, left| analyzer
+						.analyze_lvalue(left)
+						.map_err(
+							|lvalue| if !lvalue {
+								analyzer.report(Error::invalid_assignment(pos));
+							}
+						)
+						.ok(),
+					left.into_vec(), // Use vec's owned iterator.
+				);
+
+				let right = self.analyze_items(
+					Self::analyze_expr,
+					right.into_vec(), // Use vec's owned iterator.
+				);
+
+				let (left, right) = left.zip(right)?;
+
+				let analyzer = self.enter_block();
+
+				let values: Vec<(mem::SlotIx, Expr)> = right
+					.into_vec()
+					.into_iter()
+					.enumerate()
+					.map(
+						|(ix, expr)| {
+							// This is not a valid identifier, so it can't clash with user variables.
+							let symbol = analyzer.interner.get_or_intern(format!("<assign{}>", ix));
+							let slot_ix = analyzer.scope
+								.declare(symbol, pos)
+								.expect("the values should be the only variables in their scope");
+
+							(slot_ix, expr)
+						}
+					)
+					.collect();
+
+				let loads: Vec<Statement> = left
+					.into_vec()
+					.into_iter()
+					.zip(values.iter())
+					.map(
+						|(left, (slot_ix, _))| Statement::Assign {
+							left,
+							right: Expr::Identifier { slot_ix: *slot_ix, pos },
+						}
+					)
+					.collect();
+
+				let statements: Vec<Statement> = values
+					.into_iter()
+					.map(
+						|(slot_ix, right)| Statement::Assign {
+							left: Lvalue::Identifier { slot_ix, pos },
+							right,
+						}
+					)
+					.chain(loads)
+					.collect();
+
+				Some(
+					Statement::Expr(
+						Expr::If {
+							condition: Box::new(Expr::Literal { literal: Literal::Bool(true), pos }),
+							then: Block::from(statements.into_boxed_slice()),
+							otherwise: Block::default(),
+							pos,
+						}
+					)
+				)
+			}
+
 			// Return.
-			ast::Statement::Return { expr, pos } => {
+			ast::Statement::Return { expr, pos, .. } => {
 				let ret =
 					if self.in_function {
 						Some(())
@@ -163,11 +398,31 @@ impl<'a> Analyzer<'a> {
 			}
 
 			// Break.
-			ast::Statement::Break { pos } => {
+			ast::Statement::Break { expr, pos, .. } => {
+				let brk =
+					if self.in_loop {
+						Some(())
+					} else {
+						self.report(Error::break_outside_loop(pos));
+						None
+					};
+
+				let expr = match expr {
+					Some(expr) => self.analyze_expr(expr).map(Some),
+					None => Some(None),
+				};
+
+				let (_, expr) = brk.zip(expr)?;
+
+				Some(Statement::Break { expr })
+			}
+
+			// Continue.
+			ast::Statement::Continue { pos, .. } => {
 				if self.in_loop {
-					Some(Statement::Break)
+					Some(Statement::Continue)
 				} else {
-					self.report(Error::break_outside_loop(pos));
+					self.report(Error::continue_outside_loop(pos));
 					None
 				}
 			}
@@ -185,7 +440,7 @@ impl<'a> Analyzer<'a> {
 			}
 
 			// For.
-			ast::Statement::For { identifier, expr, block, pos } => {
+			ast::Statement::For { identifier, expr, block, pos, .. } => {
 				let expr = self.analyze_expr(expr);
 				let id_block = {
 					let mut analyzer = self.enter_loop();
@@ -212,6 +467,25 @@ impl<'a> Analyzer<'a> {
 				Some(Statement::For { slot_ix, expr, block })
 			}
 
+			// Scope.
+			ast::Statement::Scope { block, .. } => {
+				let block = self.enter_block().analyze_block(block)?;
+				Some(Statement::Scope { block })
+			}
+
+			// Assert.
+			ast::Statement::Assert { condition, message, pos, .. } => {
+				let condition = self.analyze_expr(condition);
+				let message = match message {
+					Some(message) => self.analyze_expr(message).map(Some),
+					None => Some(None),
+				};
+
+				let (condition, message) = condition.zip(message)?;
+
+				Some(Statement::Assert { condition, message, pos })
+			}
+
 			// Expr.
 			ast::Statement::Expr(expr) => {
 				let expr = self.analyze_expr(expr)?;
@@ -229,7 +503,7 @@ impl<'a> Analyzer<'a> {
 	fn analyze_expr(&mut self, expr: ast::Expr) -> Option<Expr> {
 		match expr {
 			// Self
-			ast::Expr::Self_ { pos } => {
+			ast::Expr::Self_ { pos, .. } => {
 				if self.in_function {
 					let slot_ix = self.scope.resolve_or_insert_self();
 					Some(Expr::Identifier { slot_ix, pos })
@@ -240,7 +514,7 @@ impl<'a> Analyzer<'a> {
 			}
 
 			// Identifier.
-			ast::Expr::Identifier { identifier, pos } => {
+			ast::Expr::Identifier { identifier, pos, .. } => {
 				let slot_ix =
 					if identifier.is_ill_formed() {
 						None
@@ -257,13 +531,13 @@ impl<'a> Analyzer<'a> {
 			}
 
 			// Literal.
-			ast::Expr::Literal { literal, pos } => {
+			ast::Expr::Literal { literal, pos, .. } => {
 				let literal = self.analyze_literal(literal)?;
 				Some(Expr::Literal { literal, pos })
 			}
 
 			// UnaryOp.
-			ast::Expr::UnaryOp { op, operand, pos } => {
+			ast::Expr::UnaryOp { op, operand, pos, .. } => {
 				let operand = self.analyze_expr(*operand)?;
 
 				match op {
@@ -284,7 +558,7 @@ impl<'a> Analyzer<'a> {
 			}
 
 			// BinaryOp.
-			ast::Expr::BinaryOp { left, op, right, pos } => {
+			ast::Expr::BinaryOp { left, op, right, pos, .. } => {
 				let left = self.analyze_expr(*left);
 				let right = self.analyze_expr(*right);
 
@@ -301,7 +575,7 @@ impl<'a> Analyzer<'a> {
 			}
 
 			// If.
-			ast::Expr::If { condition, then, otherwise, pos } => {
+			ast::Expr::If { condition, then, otherwise, pos, .. } => {
 				let condition = self.analyze_expr(*condition);
 				let then = {
 					self.enter_block().analyze_block(then)
@@ -322,8 +596,98 @@ impl<'a> Analyzer<'a> {
 				)
 			}
 
+			// When, which is lowered into an if without else.
+			ast::Expr::When { condition, value, pos, .. } => {
+				let condition = self.analyze_expr(*condition);
+				let value = {
+					self.enter_block().analyze_expr(*value)
+				};
+
+				let (condition, value) = condition.zip(value)?;
+
+				Some(
+					Expr::If {
+						condition: Box::new(condition),
+						then: Block::from(vec![Statement::Expr(value)].into_boxed_slice()),
+						otherwise: Block::default(),
+						pos
+					}
+				)
+			}
+
+			// Match, which is lowered into a chain of ifs comparing a hidden variable that holds
+			// the scrutinee. The whole chain is wrapped in an unconditional if, which provides
+			// the scope for the variable.
+			ast::Expr::Match { scrutinee, arms, otherwise, pos, .. } => {
+				let scrutinee = self.analyze_expr(*scrutinee);
+
+				let mut analyzer = self.enter_block();
+
+				// This is not a valid identifier, so it can't clash with user variables.
+				let symbol = analyzer.interner.get_or_intern("<match>");
+				let slot_ix = analyzer.scope
+					.declare(symbol, pos)
+					.expect("the scrutinee should be the only variable in its scope");
+
+				let arms = analyzer.analyze_items(
+					|analyzer, (pattern, block)| {
+						let pattern = analyzer.analyze_expr(pattern);
+						let block = analyzer.enter_block().analyze_block(block);
+						pattern.zip(block)
+					},
+					arms.into_vec(), // Use vec's owned iterator.
+				);
+				let otherwise = {
+					analyzer.enter_block().analyze_block(otherwise)
+				};
+
+				let (scrutinee, (arms, otherwise)) = scrutinee.zip(arms.zip(otherwise))?;
+
+				let chain = arms
+					.into_vec()
+					.into_iter()
+					.rev()
+					.fold(
+						otherwise,
+						|otherwise, (pattern, then)| {
+							let condition = Expr::BinaryOp {
+								left: Box::new(Expr::Identifier { slot_ix, pos }),
+								op: program::BinaryOp::Equals,
+								right: Box::new(pattern),
+								pos,
+							};
+
+							Block::from(
+								vec![
+									Statement::Expr(
+										Expr::If { condition: Box::new(condition), then, otherwise, pos }
+									)
+								].into_boxed_slice()
+							)
+						}
+					);
+
+				let statements: Vec<Statement> = std::iter::once(
+					Statement::Assign {
+						left: Lvalue::Identifier { slot_ix, pos },
+						right: scrutinee,
+					}
+				)
+					.chain(chain.0.into_vec())
+					.collect();
+
+				Some(
+					Expr::If {
+						condition: Box::new(Expr::Literal { literal: Literal::Bool(true), pos }),
+						then: Block::from(statements.into_boxed_slice()),
+						otherwise: Block::default(),
+						pos,
+					}
+				)
+			}
+
 			// Access.
-			ast::Expr::Access { object, field, pos } => {
+			ast::Expr::Access { object, field, pos, .. } => {
 				let object = self.analyze_expr(*object);
 				let field = self.analyze_expr(*field);
 
@@ -338,12 +702,28 @@ impl<'a> Analyzer<'a> {
 				)
 			}
 
+			// Field.
+			ast::Expr::Field { object, name, pos, .. } => {
+				let object = self.analyze_expr(*object);
+				let field = Self::analyze_field_name(name, pos);
+
+				let (object, field) = object.zip(field)?;
+
+				Some(
+					Expr::Access {
+						object: Box::new(object),
+						field: Box::new(field),
+						pos
+					}
+				)
+			}
+
 			// Call.
-			ast::Expr::Call { function, args, pos } => {
+			ast::Expr::Call { function, args, pos, .. } => {
 				let function = self.analyze_expr(*function);
 
 				let args = self.analyze_items(
-					Self::analyze_expr,
+					Self::analyze_item,
 					args.into_vec(), // Use vec's owned iterator.
 				);
 
@@ -359,28 +739,115 @@ impl<'a> Analyzer<'a> {
 			}
 
 			// Command block.
-			ast::Expr::CommandBlock { block, pos } => {
-				let block = self.analyze_command_block(block)?;
+			ast::Expr::CommandBlock { block, pos, .. } => {
+				let block = self.analyze_command_block(*block)?;
 
 				Some(Expr::CommandBlock { block, pos })
 			},
 
+			// Comprehension.
+			ast::Expr::Comprehension { output, identifier, source, filter, pos, .. } => {
+				let source = self.analyze_expr(*source);
+				let comprehension = {
+					let mut analyzer = self.enter_block();
+
+					let slot_ix =
+						if identifier.is_ill_formed() {
+							None
+						} else {
+							analyzer.scope
+								.declare(identifier, pos)
+								.map_err(
+									|error| analyzer.report(error)
+								)
+								.ok()
+						};
+
+					let output = analyzer.analyze_expr(*output);
+
+					let filter = match filter {
+						Some(filter) => analyzer
+							.analyze_expr(*filter)
+							.map(|filter| Some(Box::new(filter))),
+						None => Some(None),
+					};
+
+					slot_ix.zip(output.zip(filter))
+				};
+
+				let (source, (slot_ix, (output, filter))) = source.zip(comprehension)?;
+
+				Some(
+					Expr::Comprehension {
+						slot_ix,
+						output: Box::new(output),
+						source: Box::new(source),
+						filter,
+						pos,
+					}
+				)
+			}
+
+			// Spread, which is only valid as an item. See analyze_item.
+			ast::Expr::Spread { operand, pos, .. } => {
+				self.analyze_expr(*operand);
+				self.report(Error::invalid_spread(pos));
+				None
+			}
+
+			// Range.
+			ast::Expr::Range { start, end, inclusive, pos, .. } => {
+				let start = self.analyze_expr(*start);
+				let end = self.analyze_expr(*end);
+
+				let (start, end) = start.zip(end)?;
+
+				Some(
+					Expr::Range {
+						start: Box::new(start),
+						end: Box::new(end),
+						inclusive,
+						pos,
+					}
+				)
+			}
+
+			// Parenthesized.
+			ast::Expr::Parenthesized { expr, .. } => self.analyze_expr(*expr),
+
 			// Ill-formed.
 			ast::Expr::IllFormed => None,
 		}
 	}
 
 
+	/// Analyze an array item or a call argument, which may be spread.
+	/// None is returned if any error is detected.
+	fn analyze_item(&mut self, expr: ast::Expr) -> Option<Expr> {
+		match expr {
+			ast::Expr::Spread { operand, pos, .. } => {
+				let operand = self.analyze_expr(*operand)?;
+				Some(Expr::Spread { operand: Box::new(operand), pos })
+			}
+
+			expr => self.analyze_expr(expr),
+		}
+	}
+
+
 	/// Analyze an l-value expression.
 	/// Err is returned if any error is detected. The boolean indicates if the expression is
 	/// a valid l-value.
 	fn analyze_lvalue(&mut self, expr: ast::Expr) -> Result<Lvalue, bool> {
 		match expr {
 			// Identifier.
-			ast::Expr::Identifier { identifier, pos } => {
+			ast::Expr::Identifier { identifier, pos, .. } => {
 				let slot_ix =
 					if identifier.is_ill_formed() {
 						Err(false)
+					} else if let Some(declaration) = self.scope.constant(identifier) {
+						self.report(Error::const_assignment(identifier, pos, declaration));
+						Err(true)
 					} else {
 						self.scope
 							.resolve(identifier, pos, self.interner)
@@ -396,7 +863,7 @@ impl<'a> Analyzer<'a> {
 			}
 
 			// Access.
-			ast::Expr::Access { object, field, pos } => {
+			ast::Expr::Access { object, field, pos, .. } => {
 				let object = self.analyze_expr(*object);
 				let field = self.analyze_expr(*field);
 
@@ -413,6 +880,27 @@ impl<'a> Analyzer<'a> {
 				)
 			}
 
+			// Field.
+			ast::Expr::Field { object, name, pos, .. } => {
+				let object = self.analyze_expr(*object);
+				let field = Self::analyze_field_name(name, pos);
+
+				let (object, field) = object
+					.zip(field)
+					.ok_or(true)?;
+
+				Ok(
+					Lvalue::Access {
+						object: Box::new(object),
+						field: Box::new(field),
+						pos
+					}
+				)
+			}
+
+			// Parenthesized.
+			ast::Expr::Parenthesized { expr, .. } => self.analyze_lvalue(*expr),
+
 			// Ill-formed.
 			ast::Expr::IllFormed => Err(false),
 
@@ -447,7 +935,7 @@ impl<'a> Analyzer<'a> {
 			// Array.
 			ast::Literal::Array(array) => {
 				let array = self.analyze_items(
-					Self::analyze_expr,
+					Self::analyze_item,
 					array.into_vec(), // Use vec's owned iterator.
 				)?;
 
@@ -459,22 +947,33 @@ impl<'a> Analyzer<'a> {
 				self.dict_keys.clear();
 
 				let items = self.analyze_items(
-					|analyzer, ((symbol, pos), expr)| {
-						let symbol =
-							if symbol.is_ill_formed() {
-								None
-							} else if analyzer.dict_keys.insert(symbol) {
-								Some(symbol)
-							} else { // Duplicate symbol.
-								analyzer.report(Error::duplicate_key(symbol, pos));
-								None
-							};
+					|analyzer, ast::DictEntry { key, pos, value: expr, .. }| {
+						let key = match key {
+							ast::DictKey::Symbol(symbol) if symbol.is_ill_formed() => None,
+
+							ast::DictKey::Symbol(symbol) => match analyzer.dict_keys.entry(symbol) {
+								Entry::Vacant(entry) => {
+									entry.insert(pos);
+									Some(DictKey::Symbol(symbol))
+								}
+
+								Entry::Occupied(entry) => { // Duplicate symbol.
+									let original = *entry.get();
+									analyzer.report(Error::duplicate_key(symbol, pos, original));
+									None
+								}
+							},
+
+							// Computed keys are only known at runtime, and are not checked for
+							// duplicates.
+							ast::DictKey::Expr(key) => analyzer.analyze_expr(*key).map(DictKey::Expr),
+						};
 
 						let expr = analyzer.analyze_expr(expr);
 
-						let (symbol, expr) = symbol.zip(expr)?;
+						let (key, expr) = key.zip(expr)?;
 
-						Some((symbol, expr))
+						Some((key, expr))
 					},
 					items.into_vec(), // Use vec's owned iterator.
 				)?;
@@ -483,14 +982,23 @@ impl<'a> Analyzer<'a> {
 			}
 
 			// Function.
-			ast::Literal::Function { params, body } => {
+			ast::Literal::Function { params, body, .. } => {
 				let mut analyzer = self.enter_frame();
 
+				let params_count = params.len() as u32;
+
+				// Only trailing defaulted parameters may be omitted. Defaults before a required
+				// parameter have already been reported by the parser.
+				let required = params
+					.iter()
+					.rposition(|param| param.default.is_none())
+					.map_or(0, |ix| ix + 1);
+
 				let params_result = params
 					.iter()
 					.fold(
 						Some(()),
-						|acc, &(symbol ,pos)| {
+						|acc, &ast::Param { identifier: symbol, pos, .. }| {
 							let result = if symbol.is_ill_formed() {
 								None
 							} else {
@@ -507,29 +1015,53 @@ impl<'a> Analyzer<'a> {
 						}
 					);
 
+				// Parameters must be declared before anything else in the frame, as arguments are
+				// placed in the first slots. Therefore defaults are analyzed after all parameters.
+				// They may refer to any of them, but the following ones are still nil at runtime.
+				let mut defaults = Some(Vec::new());
+
+				for (ix, param) in params.into_vec().into_iter().enumerate() { // Use vec's owned iterator.
+					if let Some(default) = param.default {
+						match (&mut defaults, analyzer.analyze_expr(default)) {
+							(Some(defaults), Some(default)) if ix >= required => defaults.push(default),
+							(_, None) => defaults = None,
+							_ => (),
+						}
+					}
+				}
+
 				let body = analyzer.analyze_block(body);
 
 				let frame_info = analyzer.exit_frame();
 
-				let (_, body) = params_result.zip(body)?;
+				let ((_, defaults), body) = params_result.zip(defaults).zip(body)?;
 
 				Some(
 					Literal::Function {
-						params: params.len() as u32,
+						params: params_count,
+						defaults: defaults.into(),
 						frame_info,
 						body
 					}
 				)
 			}
 
-			// Literal identifier.
-			ast::Literal::Identifier(identifier) => {
-				if identifier.is_ill_formed() {
-					None
-				} else {
-					Some(Literal::Identifier(identifier))
+		}
+	}
+
+
+	/// Analyze the name in a dot access, which is a literal field name.
+	/// None is returned if the name is ill-formed.
+	fn analyze_field_name(name: Symbol, pos: SourcePos) -> Option<Expr> {
+		if name.is_ill_formed() {
+			None
+		} else {
+			Some(
+				Expr::Literal {
+					literal: Literal::Identifier(name),
+					pos,
 				}
-			}
+			)
 		}
 	}
 
@@ -560,38 +1092,67 @@ impl<'a> Analyzer<'a> {
 	/// Analyze a command.
 	/// None is returned if any error is detected.
 	fn analyze_command(&mut self, command: ast::Command, in_async: bool) -> Option<Command> {
-		match command::Builtin::try_from(&command.head.program) {
+		let (head, tail) = match command {
+			ast::Command::Pipeline { head, tail } => (head, tail),
+
+			ast::Command::Conditional { condition, then, otherwise, pos } => {
+				let condition = self.analyze_command(*condition, in_async);
+
+				let then = self.analyze_items(
+					move |analyzer, cmd| analyzer.analyze_command(cmd, in_async),
+					then.into_vec(), // Use vec's owned iterator.
+				);
+
+				let otherwise = self.analyze_items(
+					move |analyzer, cmd| analyzer.analyze_command(cmd, in_async),
+					otherwise.into_vec(), // Use vec's owned iterator.
+				);
+
+				let (condition, (then, otherwise)) = condition.zip(then.zip(otherwise))?;
+
+				return Some(
+					Command::Conditional {
+						condition: Box::new(condition),
+						then,
+						otherwise,
+						pos,
+					}
+				);
+			}
+		};
+
+		match command::Builtin::try_from(&head.program) {
 			Ok(_)
 				if in_async // Block is async.
-				|| !command.tail.is_empty() // Command is pipeline.
-				|| !command.head.redirections.is_empty() // Command contains redirections.
+				|| !tail.is_empty() // Command is pipeline.
+				|| !head.redirections.is_empty() // Command contains redirections.
 				=> {
-				self.report(Error::async_builtin(command.head.pos));
+				self.report(Error::async_builtin(head.pos));
 				None
 			}
 
 			Ok(builtin) => {
 				let arguments = self.analyze_items(
 					Self::analyze_argument,
-					command.head.arguments.into_vec(), // Use vec's owned iterator.
+					head.arguments.into_vec(), // Use vec's owned iterator.
 				)?;
 
 				Some(
 					Command::Builtin {
 						program: builtin,
 						arguments,
-						abort_on_error: command.head.abort_on_error,
-						pos: command.head.pos,
+						abort_on_error: head.abort_on_error,
+						pos: head.pos,
 					}
 				)
 			}
 
 			Err(_) => {
-				let head = self.analyze_basic_command(command.head);
+				let head = self.analyze_basic_command(head);
 
 				let tail = self.analyze_items(
 					Self::analyze_basic_command,
-					command.tail.into_vec(), // Use vec's owned iterator.
+					tail.into_vec(), // Use vec's owned iterator.
 				);
 
 				let (head, tail) = head.zip(tail)?;
@@ -702,6 +1263,20 @@ impl<'a> Analyzer<'a> {
 			ast::ArgExpansion::Star => Some(ArgPart::Star),
 			ast::ArgExpansion::Percent => Some(ArgPart::Percent),
 			ast::ArgExpansion::CharClass(chars) => Some(ArgPart::CharClass(chars)),
+			ast::ArgExpansion::Splat { symbol, pos } => {
+				if symbol.is_ill_formed() {
+					None
+				} else {
+					let slot_ix = self.scope
+						.resolve(symbol, pos, self.interner)
+						.map_err(
+							|error| self.report(error)
+						)
+						.ok()?;
+
+					Some(ArgPart::Splat { slot_ix, pos })
+				}
+			}
 		}
 	}
 
@@ -793,7 +1368,7 @@ impl<'a> Analyzer<'a> {
 	fn new(
 		interner: &'a mut symbol::Interner,
 		scope: &'a mut scope::Stack,
-		dict_keys: &'a mut HashSet<Symbol>,
+		dict_keys: &'a mut HashMap<Symbol, SourcePos>,
 		errors: &'a mut Errors
 	) -> Self {
 		let std_symbol = interner.get_or_intern("std");
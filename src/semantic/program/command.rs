@@ -29,6 +29,12 @@ pub enum ArgPart {
 	Star, // *
 	Percent, // %
 	CharClass(Box<[u8]>), // [...]
+
+	/// Array splat: `@args`.
+	Splat {
+		slot_ix: mem::SlotIx,
+		pos: SourcePos,
+	},
 }
 
 
@@ -102,7 +108,10 @@ impl<'a> TryFrom<&'a ast::Argument> for Builtin {
 
 	fn try_from(arg: &'a ast::Argument) -> Result<Self, Self::Error> {
 		match arg.parts.as_ref() {
-			[ ast::ArgPart::Unit(ast::ArgUnit::Literal(ref lit)) ] => Self::try_from(lit.as_ref()),
+			[ part ] => part
+				.as_bytes()
+				.ok_or(InvalidBuiltin)
+				.and_then(Self::try_from),
 			_ => Err(InvalidBuiltin),
 		}
 	}
@@ -122,7 +131,7 @@ pub struct BasicCommand {
 }
 
 
-/// Commands may be pipelines, or a single BasicCommand.
+/// Commands may be builtins, pipelines, or conditionals.
 #[derive(Debug)]
 pub enum Command {
 	Builtin {
@@ -134,7 +143,14 @@ pub enum Command {
 	External {
 		head: BasicCommand,
 		tail: Box<[BasicCommand]>
-	}
+	},
+	/// Execute one of the branches depending on whether the condition succeeds.
+	Conditional {
+		condition: Box<Command>,
+		then: Box<[Command]>,
+		otherwise: Box<[Command]>,
+		pos: SourcePos,
+	},
 }
 
 
@@ -150,9 +166,10 @@ pub struct CommandBlock {
 /// The kinds of command blocks.
 #[derive(Debug)]
 pub enum CommandBlockKind {
-	Synchronous,  // {}
-	Asynchronous, // &{}
-	Capture,      // ${}
+	Synchronous,     // {}
+	Asynchronous,    // &{}
+	Capture,         // ${}
+	CombinedCapture, // @{}
 }
 
 
@@ -162,6 +179,7 @@ impl From<ast::CommandBlockKind> for CommandBlockKind {
 			ast::CommandBlockKind::Synchronous => CommandBlockKind::Synchronous,
 			ast::CommandBlockKind::Asynchronous => CommandBlockKind::Asynchronous,
 			ast::CommandBlockKind::Capture => CommandBlockKind::Capture,
+			ast::CommandBlockKind::CombinedCapture => CommandBlockKind::CombinedCapture,
 		}
 	}
 }
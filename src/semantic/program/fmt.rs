@@ -14,6 +14,7 @@ use super::{
 	Command,
 	CommandBlock,
 	CommandBlockKind,
+	DictKey,
 	Expr,
 	Literal,
 	Lvalue,
@@ -135,7 +136,14 @@ impl<'a> Display<'a> for Literal {
 					f,
 					|(k, v), f| {
 						step(f, nested)?;
-						k.fmt(f, nested.interner)?;
+						match k {
+							DictKey::Symbol(symbol) => symbol.fmt(f, nested.interner)?,
+							DictKey::Expr(expr) => {
+								"[".fmt(f)?;
+								expr.fmt(f, nested)?;
+								"]".fmt(f)?;
+							}
+						}
 						": ".fmt(f)?;
 						v.fmt(f, nested)
 					},
@@ -149,7 +157,7 @@ impl<'a> Display<'a> for Literal {
 				"]".fmt(f)
 			},
 
-			Self::Function { params, frame_info, body } => {
+			Self::Function { params, defaults, frame_info, body } => {
 				let step = if context.indentation.is_some() { "\n" } else { " " };
 
 				Keyword::Function.fmt(f)?;
@@ -157,6 +165,11 @@ impl<'a> Display<'a> for Literal {
 
 				params.fmt(f)?;
 
+				for default in defaults.iter() {
+					", ".fmt(f)?;
+					default.fmt(f, context.inlined())?;
+				}
+
 				")".fmt(f)?;
 
 				if context.indentation.is_some() {
@@ -315,6 +328,50 @@ impl<'a> Display<'a> for Expr {
 			}
 
 			Self::CommandBlock { block, .. } => block.fmt(f, context),
+
+			Self::Comprehension { slot_ix, output, source, filter, .. } => {
+				"[ ".fmt(f)?;
+				output.fmt(f, context.inlined())?;
+				" ".fmt(f)?;
+				Keyword::For.fmt(f)?;
+				" ".fmt(f)?;
+				slot_ix.fmt(f)?;
+				" ".fmt(f)?;
+				Keyword::In.fmt(f)?;
+				" ".fmt(f)?;
+				source.fmt(f, context.inlined())?;
+
+				if let Some(filter) = filter {
+					" ".fmt(f)?;
+					Keyword::If.fmt(f)?;
+					" ".fmt(f)?;
+					filter.fmt(f, context.inlined())?;
+				}
+
+				" ]".fmt(f)
+			}
+
+			Self::Spread { operand, .. } => {
+				Operator::Spread.fmt(f)?;
+				operand.fmt(f, context.inlined())
+			}
+
+			Self::Item { array, index, .. } => {
+				array.fmt(f, context.inlined())?;
+				"[".fmt(f)?;
+				index.fmt(f)?;
+				"]?".fmt(f)
+			}
+
+			Self::Range { start, end, inclusive, .. } => {
+				let op = if *inclusive { Operator::RangeInclusive } else { Operator::Range };
+
+				"(".fmt(f)?;
+				start.fmt(f, context.inlined())?;
+				op.fmt(f)?;
+				end.fmt(f, context.inlined())?;
+				")".fmt(f)
+			}
 		}
 	}
 }
@@ -355,7 +412,18 @@ impl<'a> Display<'a> for Statement {
 				expr.fmt(f, context)
 			}
 
-			Self::Break => Keyword::Break.fmt(f),
+			Self::Break { expr } => {
+				Keyword::Break.fmt(f)?;
+
+				if let Some(expr) = expr {
+					" ".fmt(f)?;
+					expr.fmt(f, context)?;
+				}
+
+				Ok(())
+			}
+
+			Self::Continue => Keyword::Continue.fmt(f),
 
 			Self::While { condition, block } => {
 				let step = if context.indentation.is_some() { "\n" } else { " " };
@@ -405,6 +473,37 @@ impl<'a> Display<'a> for Statement {
 				Keyword::End.fmt(f)
 			}
 
+			Self::Scope { block, .. } => {
+				let step = if context.indentation.is_some() { "\n" } else { " " };
+
+				Keyword::Begin.fmt(f)?;
+				step.fmt(f)?;
+
+				if !block.0.is_empty() {
+					block.fmt(f, context.indent())?;
+					step.fmt(f)?;
+				}
+
+				if let Some(indent) = context.indentation {
+					indent.fmt(f)?;
+				}
+
+				Keyword::End.fmt(f)
+			}
+
+			Self::Assert { condition, message, .. } => {
+				Keyword::Assert.fmt(f)?;
+				" ".fmt(f)?;
+				condition.fmt(f, context)?;
+
+				if let Some(message) = message {
+					", ".fmt(f)?;
+					message.fmt(f, context)?;
+				}
+
+				Ok(())
+			}
+
 			Self::Expr(expr) => expr.fmt(f, context),
 		}
 	}
@@ -466,6 +565,10 @@ impl std::fmt::Display for ArgPart {
 
 				color::Fg(color::Yellow, "]").fmt(f)
 			},
+			Self::Splat { slot_ix, .. } => {
+				color::Fg(color::Yellow, "@").fmt(f)?;
+				slot_ix.fmt(f)
+			},
 		}
 	}
 }
@@ -602,6 +705,34 @@ impl std::fmt::Display for Command {
 					command.fmt(f)?;
 				}
 			},
+
+			Command::Conditional { condition, then, otherwise, .. } => {
+				Keyword::If.fmt(f)?;
+				" ".fmt(f)?;
+				condition.fmt(f)?;
+				"; ".fmt(f)?;
+				Keyword::Then.fmt(f)?;
+
+				for command in then.iter() {
+					" ".fmt(f)?;
+					command.fmt(f)?;
+					";".fmt(f)?;
+				}
+
+				if !otherwise.is_empty() {
+					" ".fmt(f)?;
+					Keyword::Else.fmt(f)?;
+
+					for command in otherwise.iter() {
+						" ".fmt(f)?;
+						command.fmt(f)?;
+						";".fmt(f)?;
+					}
+				}
+
+				" ".fmt(f)?;
+				Keyword::End.fmt(f)?;
+			},
 		};
 
 		Ok(())
@@ -615,6 +746,7 @@ impl std::fmt::Display for CommandBlockKind {
 			Self::Synchronous => "{",
 			Self::Asynchronous => "&{",
 			Self::Capture => "${",
+			Self::CombinedCapture => "@{",
 		}.fmt(f)
 	}
 }
@@ -43,10 +43,12 @@ pub enum Literal {
 	Byte(u8),
 	String(Box<[u8]>),
 	Array(Box<[Expr]>),
-	Dict(Box<[(Symbol, Expr)]>),
+	Dict(Box<[(DictKey, Expr)]>),
 	Function {
 		/// The number of parameters.
 		params: u32,
+		/// The default values of the trailing parameters, which may be omitted.
+		defaults: Box<[Expr]>,
 		frame_info: mem::FrameInfo,
 		body: Block,
 	},
@@ -57,6 +59,15 @@ pub enum Literal {
 }
 
 
+/// The key of a dict literal entry.
+#[derive(Debug)]
+pub enum DictKey {
+	Symbol(Symbol),
+	/// A computed key, evaluated at runtime.
+	Expr(Expr),
+}
+
+
 /// Unary operators.
 #[derive(Debug)]
 pub enum UnaryOp {
@@ -168,6 +179,13 @@ pub enum Expr {
 		field: Box<Expr>,
 		pos: SourcePos,
 	},
+	/// Array item at a constant index, as in destructuring. Evaluates to nil if the index
+	/// is out of bounds.
+	Item {
+		array: Box<Expr>,
+		index: i64,
+		pos: SourcePos,
+	},
 	/// Function call (()) operator.
 	Call {
 		function: Box<Expr>,
@@ -178,6 +196,26 @@ pub enum Expr {
 		block: CommandBlock,
 		pos: SourcePos,
 	},
+	/// Array comprehension. Also introduces an identifier.
+	Comprehension {
+		slot_ix: mem::SlotIx,
+		output: Box<Expr>,
+		source: Box<Expr>,
+		filter: Option<Box<Expr>>,
+		pos: SourcePos,
+	},
+	/// Spread of an array into the enclosing array literal or call arguments.
+	Spread {
+		operand: Box<Expr>,
+		pos: SourcePos,
+	},
+	/// Numeric range, which evaluates to an iterator function.
+	Range {
+		start: Box<Expr>,
+		end: Box<Expr>,
+		inclusive: bool,
+		pos: SourcePos,
+	},
 }
 
 
@@ -209,7 +247,13 @@ pub enum Statement {
 	Return {
 		expr: Expr,
 	},
-	Break,
+	/// Break from a loop. As loops are not expressions, the value is evaluated but
+	/// discarded.
+	Break {
+		expr: Option<Expr>,
+	},
+	/// Skip to the next iteration of a loop.
+	Continue,
 	/// While loop.
 	While {
 		condition: Expr,
@@ -221,6 +265,16 @@ pub enum Statement {
 		expr: Expr,
 		block: Block,
 	},
+	/// Explicit scope. Evaluates to the value of the block.
+	Scope {
+		block: Block,
+	},
+	/// Assertion. Panics if the condition is false.
+	Assert {
+		condition: Expr,
+		message: Option<Expr>,
+		pos: SourcePos,
+	},
 	Expr(Expr),
 }
 
@@ -12,6 +12,8 @@ use super::{
 #[derive(Debug, Default)]
 struct Scope {
 	variables: HashMap<Symbol, SlotIx>,
+	/// The declaration positions of the constant variables.
+	constants: HashMap<Symbol, SourcePos>,
 }
 
 
@@ -36,6 +38,12 @@ impl Scope {
 			.get(&symbol)
 			.copied()
 	}
+
+
+	/// Check whether a variable is declared in this scope.
+	fn contains(&self, symbol: Symbol) -> bool {
+		self.variables.contains_key(&symbol)
+	}
 }
 
 
@@ -94,6 +102,21 @@ impl Frame {
 	}
 
 
+	/// Declares a constant symbol in the current scope.
+	/// Panics if the stack is empty.
+	fn declare_const(&mut self, symbol: Symbol, pos: SourcePos) -> Result<SlotIx, Error> {
+		let slot_ix = self.declare(symbol, pos)?;
+
+		self.scopes
+			.last_mut()
+			.expect("attempt to declare in empty stack")
+			.constants
+			.insert(symbol, pos);
+
+		Ok(slot_ix)
+	}
+
+
 	/// Resolve a symbol in the current frame.
 	fn resolve(&mut self, symbol: Symbol) -> Option<SlotIx> {
 		self.scopes
@@ -214,6 +237,25 @@ impl Stack {
 	}
 
 
+	/// Declares a constant symbol in the current scope.
+	/// Panics if the stack is empty.
+	pub fn declare_const(&mut self, symbol: Symbol, pos: SourcePos) -> Result<SlotIx, Error> {
+		self.top().declare_const(symbol, pos)
+	}
+
+
+	/// If the given symbol resolves to a constant, get the position of its declaration.
+	/// Shadowing declarations in nested scopes are not constant unless declared as such.
+	pub fn constant(&self, symbol: Symbol) -> Option<SourcePos> {
+		self.frames
+			.iter()
+			.rev()
+			.flat_map(|frame| frame.scopes.iter().rev())
+			.find(|scope| scope.contains(symbol))
+			.and_then(|scope| scope.constants.get(&symbol).copied())
+	}
+
+
 	/// Resolve a symbol in the current scope.
 	/// If the symbol is being closed from a parent frame, the capturing of such symbol is
 	/// installed.
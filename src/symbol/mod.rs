@@ -1,6 +1,8 @@
 mod fmt;
 
 use intaglio::{Symbol as SymbolInner, bytes::SymbolTable};
+#[cfg(feature = "serde")]
+use serde::{de, ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
 
 
 /// A symbol is a reference to an value stored in the symbol interner.
@@ -24,6 +26,31 @@ impl From<Symbol> for usize {
 }
 
 
+/// Symbols are serialized as their raw ids, which are meaningless without the interner.
+/// To serialize standalone data, the interner must be serialized as well.
+#[cfg(feature = "serde")]
+impl Serialize for Symbol {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_u32(self.0.id())
+	}
+}
+
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Symbol {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		u32::deserialize(deserializer)
+			.map(|id| Self(SymbolInner::new(id)))
+	}
+}
+
+
 /// A symbol interner, used to store identifiers, paths, etc.
 #[derive(Debug)]
 pub struct Interner(SymbolTable);
@@ -76,8 +103,58 @@ impl Interner {
 
 	/// Get the number of interned strings.
 	/// This does not include the dummy symbol.
-	#[cfg(test)]
 	pub fn len(&self) -> usize {
 		self.0.len() - 1
 	}
+
+
+	/// Iterate over the interned strings, in the order they were interned.
+	/// This does not include the dummy symbol.
+	pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+		(1 .. self.0.len())
+			.filter_map(move |id| self.0.get(SymbolInner::new(id as u32)))
+	}
+}
+
+
+/// The interner is serialized as the sequence of interned strings, so that deserializing
+/// it produces the same symbols.
+#[cfg(feature = "serde")]
+impl Serialize for Interner {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let mut seq = serializer.serialize_seq(Some(self.len()))?;
+
+		for value in self.iter() {
+			seq.serialize_element(value)?;
+		}
+
+		seq.end()
+	}
+}
+
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Interner {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let values = Vec::<Box<[u8]>>::deserialize(deserializer)?;
+
+		let mut interner = Self::new();
+
+		for (ix, value) in values.iter().enumerate() {
+			let symbol = interner.get_or_intern(value);
+
+			// Duplicate values would shift the following symbols.
+			if usize::from(symbol) != ix + 1 {
+				return Err(de::Error::custom("duplicate symbol in interner"));
+			}
+		}
+
+		Ok(interner)
+	}
 }
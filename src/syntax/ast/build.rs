@@ -0,0 +1,189 @@
+//! Helpers for building AST nodes programmatically, such as in tests or code generation.
+//! Nodes are built with an ill-formed position and span, which may be replaced using `at`.
+
+use super::{
+	BinaryOp,
+	Block,
+	Expr,
+	IllFormed,
+	Literal,
+	SourcePos,
+	Span,
+	Statement,
+	Symbol,
+	UnaryOp,
+};
+
+
+/// Replace the position of an expression, which also becomes its span. Ill-formed
+/// expressions are kept as is.
+pub fn at(mut expr: Expr, new_pos: SourcePos) -> Expr {
+	if let Some(span) = expr.span_mut() {
+		*span = Span::point(new_pos);
+	}
+
+	match &mut expr {
+		Expr::IllFormed => (),
+		Expr::Self_ { pos, .. }
+		| Expr::Identifier { pos, .. }
+		| Expr::Literal { pos, .. }
+		| Expr::UnaryOp { pos, .. }
+		| Expr::BinaryOp { pos, .. }
+		| Expr::If { pos, .. }
+		| Expr::When { pos, .. }
+		| Expr::Match { pos, .. }
+		| Expr::Access { pos, .. }
+		| Expr::Field { pos, .. }
+		| Expr::Call { pos, .. }
+		| Expr::CommandBlock { pos, .. }
+		| Expr::Comprehension { pos, .. }
+		| Expr::Spread { pos, .. }
+		| Expr::Range { pos, .. }
+		| Expr::Parenthesized { pos, .. } => *pos = new_pos,
+	}
+
+	expr
+}
+
+
+/// A literal expression.
+pub fn literal(literal: Literal) -> Expr {
+	Expr::Literal { literal, pos: SourcePos::ill_formed(), span: Span::default() }
+}
+
+
+/// The nil literal.
+pub fn nil() -> Expr {
+	literal(Literal::Nil)
+}
+
+
+/// A bool literal.
+pub fn bool(value: bool) -> Expr {
+	literal(Literal::Bool(value))
+}
+
+
+/// An int literal.
+pub fn int(value: i64) -> Expr {
+	literal(Literal::Int(value))
+}
+
+
+/// A float literal.
+pub fn float(value: f64) -> Expr {
+	literal(Literal::Float(value))
+}
+
+
+/// A string literal.
+pub fn string<S: AsRef<[u8]>>(value: S) -> Expr {
+	literal(Literal::String(value.as_ref().into()))
+}
+
+
+/// An array literal.
+pub fn array<I: IntoIterator<Item = Expr>>(items: I) -> Expr {
+	literal(Literal::Array(items.into_iter().collect()))
+}
+
+
+/// An identifier expression.
+pub fn ident(identifier: Symbol) -> Expr {
+	Expr::Identifier { identifier, pos: SourcePos::ill_formed(), span: Span::default() }
+}
+
+
+/// A unary operation.
+pub fn unop(op: UnaryOp, operand: Expr) -> Expr {
+	Expr::UnaryOp { op, operand: operand.into(), pos: SourcePos::ill_formed(), span: Span::default() }
+}
+
+
+/// A binary operation.
+pub fn binop(left: Expr, op: BinaryOp, right: Expr) -> Expr {
+	Expr::BinaryOp {
+		left: left.into(),
+		op,
+		right: right.into(),
+		pos: SourcePos::ill_formed(),
+		span: Span::default(),
+	}
+}
+
+
+/// A subscript access, as in `object[field]`.
+pub fn access(object: Expr, field: Expr) -> Expr {
+	Expr::Access {
+		object: object.into(),
+		field: field.into(),
+		pos: SourcePos::ill_formed(),
+		span: Span::default(),
+	}
+}
+
+
+/// A dot access, as in `object.name`.
+pub fn field(object: Expr, name: Symbol) -> Expr {
+	Expr::Field { object: object.into(), name, pos: SourcePos::ill_formed(), span: Span::default() }
+}
+
+
+/// A function call.
+pub fn call<I: IntoIterator<Item = Expr>>(function: Expr, args: I) -> Expr {
+	Expr::Call {
+		function: function.into(),
+		args: args.into_iter().collect(),
+		pos: SourcePos::ill_formed(),
+		span: Span::default(),
+	}
+}
+
+
+/// An if-else expression.
+pub fn if_(condition: Expr, then: Block, otherwise: Block) -> Expr {
+	Expr::If {
+		condition: condition.into(),
+		then,
+		otherwise,
+		pos: SourcePos::ill_formed(),
+		span: Span::default(),
+	}
+}
+
+
+/// A block of statements.
+pub fn block<I: IntoIterator<Item = Statement>>(statements: I) -> Block {
+	Block::Block(statements.into_iter().collect())
+}
+
+
+/// A let statement, without a type annotation.
+pub fn let_(identifier: Symbol, init: Expr) -> Statement {
+	Statement::Let {
+		identifier,
+		annotation: None,
+		init,
+		is_const: false,
+		pos: SourcePos::ill_formed(),
+		span: Span::default(),
+	}
+}
+
+
+/// An assignment statement.
+pub fn assign(left: Expr, right: Expr) -> Statement {
+	Statement::Assign { left, right, pos: SourcePos::ill_formed(), span: Span::default() }
+}
+
+
+/// A return statement.
+pub fn return_(expr: Expr) -> Statement {
+	Statement::Return { expr, pos: SourcePos::ill_formed(), span: Span::default() }
+}
+
+
+/// An expression statement.
+pub fn expr(expr: Expr) -> Statement {
+	Statement::Expr(expr)
+}
@@ -1,9 +1,15 @@
-use crate::{io::FileDescriptor, symbol::Symbol};
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{io::FileDescriptor, symbol::{Interner, Symbol}};
 use super::{lexer, IllFormed, SourcePos};
 
 
 /// The most basic part of an argument.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ArgUnit {
 	Literal(Box<[u8]>),
 	Dollar {
@@ -13,6 +19,23 @@ pub enum ArgUnit {
 }
 
 
+impl ArgUnit {
+	/// Whether the unit is a literal.
+	pub fn is_literal(&self) -> bool {
+		matches!(self, Self::Literal(_))
+	}
+
+
+	/// The contents of the unit, if it is a literal.
+	pub fn as_bytes(&self) -> Option<&[u8]> {
+		match self {
+			Self::Literal(lit) => Some(lit),
+			Self::Dollar { .. } => None,
+		}
+	}
+}
+
+
 impl From<lexer::ArgUnit> for ArgUnit {
 	fn from(unit: lexer::ArgUnit) -> Self {
 		match unit {
@@ -24,7 +47,8 @@ impl From<lexer::ArgUnit> for ArgUnit {
 
 
 /// An argument expansion.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ArgExpansion {
 	Home, // ~/
 	Range(i64, i64), // {x..y}
@@ -33,6 +57,9 @@ pub enum ArgExpansion {
 	Star, // *
 	Percent, // %
 	CharClass(Box<[u8]>), // [...]
+
+	/// Each item of an array variable as a separate argument: `@args`.
+	Splat { symbol: Symbol, pos: SourcePos },
 }
 
 
@@ -51,27 +78,194 @@ impl From<lexer::ArgExpansion> for ArgExpansion {
 			lexer::ArgExpansion::Star => Self::Star,
 			lexer::ArgExpansion::Percent => Self::Percent,
 			lexer::ArgExpansion::CharClass(class) => Self::CharClass(class),
+			lexer::ArgExpansion::Splat { symbol, pos } => Self::Splat { symbol, pos },
 		}
 	}
 }
 
 
 /// The most basic part of an argument.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ArgPart {
 	Unit(ArgUnit),
 	Expansion(ArgExpansion),
 }
 
 
+impl ArgPart {
+	/// Whether the part is a literal unit.
+	pub fn is_literal(&self) -> bool {
+		matches!(self, Self::Unit(unit) if unit.is_literal())
+	}
+
+
+	/// Whether the part is an expansion.
+	pub fn is_expansion(&self) -> bool {
+		matches!(self, Self::Expansion(_))
+	}
+
+
+	/// The contents of the part, if it is a literal unit.
+	pub fn as_bytes(&self) -> Option<&[u8]> {
+		match self {
+			Self::Unit(unit) => unit.as_bytes(),
+			Self::Expansion(_) => None,
+		}
+	}
+
+
+	/// The unit, if the part is not an expansion.
+	pub fn unit(&self) -> Option<&ArgUnit> {
+		match self {
+			Self::Unit(unit) => Some(unit),
+			Self::Expansion(_) => None,
+		}
+	}
+
+
+	/// The expansion, if the part is one.
+	pub fn expansion(&self) -> Option<&ArgExpansion> {
+		match self {
+			Self::Unit(_) => None,
+			Self::Expansion(expansion) => Some(expansion),
+		}
+	}
+}
+
+
 /// An argument may consist of several argument parts.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Argument {
 	pub parts: Box<[ArgPart]>,
 	pub pos: SourcePos,
 }
 
 
+impl Argument {
+	/// Reconstruct the source text of the argument, such that lexing it produces an
+	/// equivalent argument. Literals are single quoted unless they consist only of plain
+	/// word characters. Dollars and expansions are kept unquoted, and dollars are always
+	/// braced so that they can't absorb a following literal.
+	pub fn to_source(&self, interner: &Interner) -> Vec<u8> {
+		let mut source = Vec::new();
+
+		match self.parts.as_ref() {
+			// Empty arguments can only be produced by quotes.
+			[] => source.extend(b"''"),
+
+			// A lone literal might be mistaken for a command keyword or a file descriptor.
+			[ ArgPart::Unit(ArgUnit::Literal(literal)) ]
+				if lexer::Keyword::from_bytes(literal).is_some()
+				|| literal.iter().all(u8::is_ascii_digit) => quote_literal(literal, &mut source),
+
+			parts => for part in parts.iter() {
+				match part {
+					ArgPart::Unit(unit) => unit_source(unit, interner, &mut source),
+					ArgPart::Expansion(expansion) => expansion_source(expansion, interner, &mut source),
+				}
+			}
+		}
+
+		source
+	}
+}
+
+
+/// Write the source text of an argument unit.
+fn unit_source(unit: &ArgUnit, interner: &Interner, source: &mut Vec<u8>) {
+	match unit {
+		ArgUnit::Literal(literal) if is_plain_word(literal) => source.extend(literal.iter()),
+
+		ArgUnit::Literal(literal) => quote_literal(literal, source),
+
+		ArgUnit::Dollar { symbol, .. } => {
+			source.extend(b"${");
+			source.extend(
+				interner
+					.resolve(*symbol)
+					.expect("unresolved symbol")
+			);
+			source.push(b'}');
+		}
+	}
+}
+
+
+/// Write the source text of an argument expansion.
+fn expansion_source(expansion: &ArgExpansion, interner: &Interner, source: &mut Vec<u8>) {
+	match expansion {
+		ArgExpansion::Home => source.extend(b"~/"),
+
+		ArgExpansion::Range(from, to) => {
+			source.extend(format!("{{{}..{}}}", from, to).as_bytes())
+		}
+
+		ArgExpansion::Collection(items) => {
+			source.push(b'{');
+
+			for (ix, item) in items.iter().enumerate() {
+				if ix > 0 {
+					source.push(b',');
+				}
+
+				unit_source(item, interner, source);
+			}
+
+			source.push(b'}');
+		}
+
+		ArgExpansion::Star => source.push(b'*'),
+
+		ArgExpansion::Percent => source.push(b'%'),
+
+		ArgExpansion::CharClass(class) => {
+			source.push(b'[');
+			source.extend(class.iter());
+			source.push(b']');
+		}
+
+		ArgExpansion::Splat { symbol, .. } => {
+			source.push(b'@');
+			source.extend(
+				interner
+					.resolve(*symbol)
+					.expect("unresolved symbol")
+			);
+		}
+	}
+}
+
+
+/// Whether a literal may be written unquoted. This is a conservative set, excluding all
+/// characters that are special in commands, including expansion starters. A leading `@`
+/// could start an array splat.
+fn is_plain_word(literal: &[u8]) -> bool {
+	!literal.is_empty()
+		&& literal[0] != b'@'
+		&& literal
+			.iter()
+			.all(|&c| c.is_ascii_alphanumeric() || b"-_./,:+@".contains(&c))
+}
+
+
+/// Write a literal in single quotes, where only quotes and backslashes must be escaped.
+fn quote_literal(literal: &[u8], source: &mut Vec<u8>) {
+	source.push(b'\'');
+
+	for &c in literal.iter() {
+		if c == b'\'' || c == b'\\' {
+			source.push(b'\\');
+		}
+
+		source.push(c);
+	}
+
+	source.push(b'\'');
+}
+
+
 impl IllFormed for Argument {
 	fn ill_formed() -> Self {
 		Self {
@@ -87,7 +281,8 @@ impl IllFormed for Argument {
 
 
 /// The target of a redirection operation.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RedirectionTarget {
 	/// Redirect to a file descriptor.
 	Fd(FileDescriptor),
@@ -99,7 +294,8 @@ pub enum RedirectionTarget {
 
 
 /// Redirection operation.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Redirection {
 	/// An ill-formed redirection, produced by a parse error.
 	IllFormed,
@@ -129,8 +325,11 @@ impl IllFormed for Redirection {
 
 
 /// A single command, including possible redirections and try operator.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BasicCommand {
+	/// The label of the command, as in `build: make all`, which identifies it in logs.
+	pub label: Option<Symbol>,
 	pub program: Argument,
 	/// Key-value pairs of environment variables.
 	pub env: Box<[(ArgUnit, Argument)]>,
@@ -144,6 +343,7 @@ pub struct BasicCommand {
 impl IllFormed for BasicCommand {
 	fn ill_formed() -> Self {
 		Self {
+			label: None,
 			program: Argument::ill_formed(),
 			env: Default::default(),
 			arguments: Default::default(),
@@ -159,34 +359,53 @@ impl IllFormed for BasicCommand {
 }
 
 
-/// Commands may be pipelines, or a single BasicCommand.
-#[derive(Debug)]
-pub struct Command {
-	pub head: BasicCommand,
-	pub tail: Box<[BasicCommand]>,
+/// Commands may be pipelines, or conditionals.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Command {
+	/// A pipeline, or a single BasicCommand.
+	Pipeline {
+		head: BasicCommand,
+		tail: Box<[BasicCommand]>,
+	},
+	/// Conditional execution on the exit status of a command:
+	/// `if condition; then commands; else commands; end`.
+	/// Pipes bind tighter, so the condition may be a whole pipeline, which is successful if
+	/// no command in it fails. A failed condition never aborts the block. The conditional
+	/// itself is a single command in the block, and may not be piped.
+	Conditional {
+		condition: Box<Command>,
+		then: Box<[Command]>,
+		otherwise: Box<[Command]>,
+		pos: SourcePos,
+	},
 }
 
 
 impl IllFormed for Command {
 	fn ill_formed() -> Self {
-		Self {
+		Self::Pipeline {
 			head: BasicCommand::ill_formed(),
 			tail: Default::default(),
 		}
 	}
 
 	fn is_ill_formed(&self) -> bool {
-		self.head.is_ill_formed()
+		matches!(self, Self::Pipeline { head, .. } if head.is_ill_formed())
 	}
 }
 
 
 /// A command block.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CommandBlock {
 	pub kind: CommandBlockKind,
 	pub head: Command,
 	pub tail: Box<[Command]>,
+	/// The maximum duration of the block, given as `{ ... }: seconds`. This is not
+	/// enforced yet.
+	pub timeout: Option<Duration>,
 }
 
 
@@ -196,6 +415,7 @@ impl IllFormed for CommandBlock {
 			kind: CommandBlockKind::Synchronous,
 			head: Command::ill_formed(),
 			tail: Default::default(),
+			timeout: None,
 		}
 	}
 
@@ -206,11 +426,16 @@ impl IllFormed for CommandBlock {
 
 
 /// The kinds of command blocks.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CommandBlockKind {
-	Synchronous,  // {}
-	Asynchronous, // &{}
-	Capture,      // ${}
+	Synchronous,     // {}
+	Asynchronous,    // &{}
+	Capture,         // ${}
+	/// Captures both the output and the exit status. The block evaluates to a dict
+	/// `@[ stdout: string, stderr: string, status: int ]`, where status is the exit
+	/// status of the first failed command, or zero if all succeeded.
+	CombinedCapture, // @{}
 }
 
 
@@ -220,6 +445,7 @@ impl CommandBlockKind {
 			lexer::TokenKind::Command => Some(Self::Synchronous),
 			lexer::TokenKind::AsyncCommand => Some(Self::Asynchronous),
 			lexer::TokenKind::CaptureCommand => Some(Self::Capture),
+			lexer::TokenKind::CombinedCaptureCommand => Some(Self::CombinedCapture),
 			_ => None,
 		}
 	}
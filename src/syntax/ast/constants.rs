@@ -0,0 +1,117 @@
+//! Extraction of the literal constants in an AST, for constant pooling and for tooling
+//! such as magic number lints.
+
+use std::collections::HashMap;
+
+use super::{
+	visit::{self, Visitor},
+	Ast,
+	Expr,
+	Literal,
+	SourcePos,
+};
+
+
+/// A scalar literal constant. Floats are kept as their bits so that constants can be
+/// compared and hashed, which distinguishes `0.0` from `-0.0`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Constant {
+	Bool(bool),
+	Int(i64),
+	/// The bits of the float. See f64::from_bits.
+	Float(u64),
+	Byte(u8),
+	String(Box<[u8]>),
+}
+
+
+impl Constant {
+	/// The constant for the given literal, if it is a scalar other than nil. Nil is not
+	/// considered a constant, as the parser also produces it for omitted values.
+	pub fn from_literal(literal: &Literal) -> Option<Self> {
+		match literal {
+			Literal::Bool(value) => Some(Self::Bool(*value)),
+			Literal::Int(value) => Some(Self::Int(*value)),
+			Literal::Float(value) => Some(Self::Float(value.to_bits())),
+			Literal::Byte(value) => Some(Self::Byte(*value)),
+			Literal::String(value) => Some(Self::String(value.clone())),
+			_ => None,
+		}
+	}
+}
+
+
+/// The unique constants in an AST, each with the positions of its occurrences. Constants
+/// are kept in order of first occurrence, and occurrences in source order.
+#[derive(Debug, Default)]
+pub struct ConstantTable {
+	constants: Vec<(Constant, Vec<SourcePos>)>,
+	indices: HashMap<Constant, usize>,
+}
+
+
+impl ConstantTable {
+	/// The number of unique constants.
+	pub fn len(&self) -> usize {
+		self.constants.len()
+	}
+
+
+	pub fn is_empty(&self) -> bool {
+		self.constants.is_empty()
+	}
+
+
+	/// The occurrences of the given constant, which are empty if it doesn't occur.
+	pub fn occurrences(&self, constant: &Constant) -> &[SourcePos] {
+		self.indices
+			.get(constant)
+			.map(|&ix| self.constants[ix].1.as_slice())
+			.unwrap_or_default()
+	}
+
+
+	/// Iterate the unique constants, with their occurrences.
+	pub fn iter(&self) -> impl Iterator<Item = (&Constant, &[SourcePos])> {
+		self.constants
+			.iter()
+			.map(|(constant, occurrences)| (constant, occurrences.as_slice()))
+	}
+
+
+	fn insert(&mut self, constant: Constant, pos: SourcePos) {
+		let constants = &mut self.constants;
+
+		let ix = *self.indices
+			.entry(constant)
+			.or_insert_with_key(
+				|constant| {
+					constants.push((constant.clone(), Vec::new()));
+					constants.len() - 1
+				}
+			);
+
+		constants[ix].1.push(pos);
+	}
+}
+
+
+/// Collect the literal constants in the given AST.
+pub fn constants(ast: &Ast) -> ConstantTable {
+	let mut table = ConstantTable::default();
+	table.visit_block(&ast.statements);
+	table
+}
+
+
+impl<'a> Visitor<'a> for ConstantTable {
+	fn visit_expr(&mut self, expr: &'a Expr) {
+		if let Expr::Literal { literal, pos, .. } = expr {
+			if let Some(constant) = Constant::from_literal(literal) {
+				self.insert(constant, *pos);
+			}
+		}
+
+		visit::walk_expr(self, expr)
+	}
+}
@@ -0,0 +1,125 @@
+//! Structural diffing of ASTs, to allow re-evaluating only the changed top-level statements.
+//! Source positions are ignored, so moving a statement around does not change it.
+
+use std::collections::HashMap;
+
+use crate::symbol::Interner;
+use super::{hash, Ast, Block, Statement, Symbol};
+
+
+/// A change in a top-level statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+	/// The statement is in both ASTs, but differs. Holds the index in the new AST.
+	Modified(usize),
+	/// The statement is only in the new AST. Holds the index in the new AST.
+	Added(usize),
+	/// The statement is only in the old AST. Holds the index in the old AST.
+	Removed(usize),
+}
+
+
+/// Diff the top-level statements of two ASTs.
+/// Bindings are matched by name, which handles reordering. Other statements are matched
+/// by their order among the unnamed statements. Both ASTs must have been parsed with the
+/// given interner.
+pub fn diff(old: &Ast, new: &Ast, interner: &Interner) -> Vec<Change> {
+	let old_statements = statements(&old.statements);
+	let new_statements = statements(&new.statements);
+
+	let old_keys = keys(old_statements);
+	let new_keys = keys(new_statements);
+
+	let old_indices: HashMap<Key, usize> = old_keys
+		.iter()
+		.enumerate()
+		.map(|(ix, &key)| (key, ix))
+		.collect();
+
+	let mut changes = Vec::new();
+	let mut matched = vec![false; old_statements.len()];
+
+	for (ix, (key, statement)) in new_keys.iter().zip(new_statements).enumerate() {
+		match old_indices.get(key) {
+			Some(&old_ix) => {
+				matched[old_ix] = true;
+
+				if !same_structure(&old_statements[old_ix], statement, interner) {
+					changes.push(Change::Modified(ix));
+				}
+			}
+
+			None => changes.push(Change::Added(ix)),
+		}
+	}
+
+	changes.extend(
+		matched
+			.iter()
+			.enumerate()
+			.filter(|(_, &matched)| !matched)
+			.map(|(ix, _)| Change::Removed(ix))
+	);
+
+	changes
+}
+
+
+/// The key used to match top-level statements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Key {
+	/// A binding with a unique name in the AST, including function definitions.
+	Binding(Symbol),
+	/// The ordinal among statements that are not uniquely named bindings.
+	Unnamed(usize),
+}
+
+
+/// The statements of a block. Ill-formed blocks have no statements.
+fn statements(block: &Block) -> &[Statement] {
+	match block {
+		Block::IllFormed => &[],
+		Block::Block(statements) => statements,
+	}
+}
+
+
+/// Compute the matching key for each statement.
+fn keys(statements: &[Statement]) -> Vec<Key> {
+	let binding = |statement: &Statement| match statement {
+		Statement::Let { identifier, .. } => Some(*identifier),
+		_ => None,
+	};
+
+	let mut occurrences: HashMap<Symbol, usize> = HashMap::new();
+	for identifier in statements.iter().filter_map(binding) {
+		*occurrences.entry(identifier).or_default() += 1;
+	}
+
+	let mut unnamed = 0;
+
+	statements
+		.iter()
+		.map(|statement| match binding(statement) {
+			// Shadowed bindings can't be matched by name.
+			Some(identifier) if occurrences[&identifier] == 1 => Key::Binding(identifier),
+			_ => {
+				unnamed += 1;
+				Key::Unnamed(unnamed)
+			}
+		})
+		.collect()
+}
+
+
+/// Compare two statements, ignoring source positions. Structural hashes quickly tell
+/// most changed statements apart, but equal hashes are confirmed by the full encoding, as
+/// hashes may collide.
+fn same_structure(old: &Statement, new: &Statement, interner: &Interner) -> bool {
+	if old == new {
+		return true;
+	}
+
+	hash::hash_statement(old, interner) == hash::hash_statement(new, interner)
+		&& hash::encode_statement(old, interner) == hash::encode_statement(new, interner)
+}
@@ -13,12 +13,15 @@ use super::{
 	Command,
 	CommandBlock,
 	CommandBlockKind,
+	DictKey,
 	Expr,
 	IllFormed,
 	Literal,
+	Param,
 	Redirection,
 	RedirectionTarget,
 	Statement,
+	TypeExpr,
 	UnaryOp,
 };
 use crate::{
@@ -89,6 +92,72 @@ impl<'a> Display<'a> for Block {
 }
 
 
+impl<'a> Display<'a> for TypeExpr {
+	type Context = &'a symbol::Interner;
+
+	fn fmt(&self, f: &mut std::fmt::Formatter, context: Self::Context) -> std::fmt::Result {
+		match self {
+			Self::IllFormed => ILL_FORMED.fmt(f),
+
+			Self::Named { name, .. } => name.fmt(f, context),
+
+			Self::Array { item, .. } => {
+				"[".fmt(f)?;
+				item.fmt(f, context)?;
+				"]".fmt(f)
+			}
+
+			Self::Dict { key, value, .. } => {
+				"@[".fmt(f)?;
+				key.fmt(f, context)?;
+				": ".fmt(f)?;
+				value.fmt(f, context)?;
+				"]".fmt(f)
+			}
+
+			Self::Function { params, return_type, .. } => {
+				"function(".fmt(f)?;
+				fmt::sep_by(params.iter(), f, |param, f| param.fmt(f, context), ", ")?;
+				")".fmt(f)?;
+
+				if let Some(return_type) = return_type {
+					": ".fmt(f)?;
+					return_type.fmt(f, context)?;
+				}
+
+				Ok(())
+			}
+
+			Self::Nullable { inner, .. } => {
+				inner.fmt(f, context)?;
+				"?".fmt(f)
+			}
+		}
+	}
+}
+
+
+impl<'a> Display<'a> for Param {
+	type Context = Context<'a>;
+
+	fn fmt(&self, f: &mut std::fmt::Formatter, context: Self::Context) -> std::fmt::Result {
+		self.identifier.fmt(f, context.interner)?;
+
+		if let Some(annotation) = &self.annotation {
+			": ".fmt(f)?;
+			annotation.fmt(f, context.interner)?;
+		}
+
+		if let Some(default) = &self.default {
+			" = ".fmt(f)?;
+			default.fmt(f, context.inlined())?;
+		}
+
+		Ok(())
+	}
+}
+
+
 impl<'a> Display<'a> for Literal {
 	type Context = Context<'a>;
 
@@ -140,11 +209,18 @@ impl<'a> Display<'a> for Literal {
 				fmt::sep_by(
 					dict.iter(),
 					f,
-					|((k, _), v), f| {
+					|item, f| {
 						step(f, nested)?;
-						k.fmt(f, nested.interner)?;
+						match &item.key {
+							DictKey::Symbol(symbol) => symbol.fmt(f, nested.interner)?,
+							DictKey::Expr(expr) => {
+								"[".fmt(f)?;
+								expr.fmt(f, nested)?;
+								"]".fmt(f)?;
+							}
+						}
 						": ".fmt(f)?;
-						v.fmt(f, nested)
+						item.value.fmt(f, nested)
 					},
 					",",
 				)?;
@@ -156,21 +232,26 @@ impl<'a> Display<'a> for Literal {
 				"]".fmt(f)
 			},
 
-			Self::Function { params, body } => {
+			Self::Function { params, return_type, body } => {
 				Keyword::Function.fmt(f)?;
 				"(".fmt(f)?;
 
 				fmt::sep_by(
 					params.iter(),
 					f,
-					|(ident, _), f| ident.fmt(f, context.interner),
+					|param, f| param.fmt(f, context),
 					", "
 				)?;
 
+				")".fmt(f)?;
+
+				if let Some(return_type) = return_type {
+					": ".fmt(f)?;
+					return_type.fmt(f, context.interner)?;
+				}
+
 				if context.indentation.is_some() {
-					")\n".fmt(f)?;
-				} else {
-					")".fmt(f)?;
+					"\n".fmt(f)?;
 				}
 
 				body.fmt(f, context.indent())?;
@@ -179,8 +260,6 @@ impl<'a> Display<'a> for Literal {
 
 				Keyword::End.fmt(f)
 			}
-
-			Self::Identifier(identifier) => identifier.fmt(f, context.interner),
 		}
 	}
 }
@@ -296,11 +375,57 @@ impl<'a> Display<'a> for Expr {
 				Keyword::End.fmt(f)
 			}
 
-			Self::Access { object, field, .. }
-			if matches!(field.as_ref(), Self::Literal { literal: Literal::Identifier(..), .. }) => {
-				object.fmt(f, context.inlined())?;
-				".".fmt(f)?;
-				field.fmt(f, context.inlined())
+			Self::When { condition, value, .. } => {
+				Keyword::When.fmt(f)?;
+				" ".fmt(f)?;
+				condition.fmt(f, context.inlined())?;
+				": ".fmt(f)?;
+				value.fmt(f, context)
+			}
+
+			Self::Match { scrutinee, arms, otherwise, .. } => {
+				let step = if context.indentation.is_some() { "\n" } else { " " };
+
+				Keyword::Match.fmt(f)?;
+				" ".fmt(f)?;
+				scrutinee.fmt(f, context.inlined())?;
+				step.fmt(f)?;
+
+				for (pattern, block) in arms.iter() {
+					if let Some(indent) = context.indentation {
+						indent.fmt(f)?;
+					}
+
+					Keyword::With.fmt(f)?;
+					" ".fmt(f)?;
+					pattern.fmt(f, context.inlined())?;
+					" ".fmt(f)?;
+					Keyword::Then.fmt(f)?;
+					step.fmt(f)?;
+
+					if !block.is_empty() {
+						block.fmt(f, context.indent())?;
+						step.fmt(f)?;
+					}
+				}
+
+				if let Some(indent) = context.indentation {
+					indent.fmt(f)?;
+				}
+
+				if !otherwise.is_empty() {
+					Keyword::Else.fmt(f)?;
+					step.fmt(f)?;
+
+					otherwise.fmt(f, context.indent())?;
+					step.fmt(f)?;
+
+					if let Some(indent) = context.indentation {
+						indent.fmt(f)?;
+					}
+				}
+
+				Keyword::End.fmt(f)
 			}
 
 			Self::Access { object, field, .. } => {
@@ -310,6 +435,12 @@ impl<'a> Display<'a> for Expr {
 				"]".fmt(f)
 			}
 
+			Self::Field { object, name, .. } => {
+				object.fmt(f, context.inlined())?;
+				".".fmt(f)?;
+				name.fmt(f, context.interner)
+			}
+
 			Self::Call { function, args, .. } => {
 				function.fmt(f, context.inlined())?;
 				"(".fmt(f)?;
@@ -325,6 +456,46 @@ impl<'a> Display<'a> for Expr {
 			}
 
 			Self::CommandBlock { block, .. } => block.fmt(f, context),
+
+			Self::Comprehension { output, identifier, source, filter, .. } => {
+				"[ ".fmt(f)?;
+				output.fmt(f, context.inlined())?;
+				" ".fmt(f)?;
+				Keyword::For.fmt(f)?;
+				" ".fmt(f)?;
+				identifier.fmt(f, context.interner)?;
+				" ".fmt(f)?;
+				Keyword::In.fmt(f)?;
+				" ".fmt(f)?;
+				source.fmt(f, context.inlined())?;
+
+				if let Some(filter) = filter {
+					" ".fmt(f)?;
+					Keyword::If.fmt(f)?;
+					" ".fmt(f)?;
+					filter.fmt(f, context.inlined())?;
+				}
+
+				" ]".fmt(f)
+			}
+
+			Self::Spread { operand, .. } => {
+				Operator::Spread.fmt(f)?;
+				operand.fmt(f, context.inlined())
+			}
+
+			Self::Range { start, end, inclusive, .. } => {
+				let op = if *inclusive { Operator::RangeInclusive } else { Operator::Range };
+
+				"(".fmt(f)?;
+				start.fmt(f, context.inlined())?;
+				op.fmt(f)?;
+				end.fmt(f, context.inlined())?;
+				")".fmt(f)
+			}
+
+			// Operations are already fully parenthesized.
+			Self::Parenthesized { expr, .. } => expr.fmt(f, context),
 		}
 	}
 }
@@ -337,27 +508,94 @@ impl<'a> Display<'a> for Statement {
 		match self {
 			Self::IllFormed => ILL_FORMED.fmt(f),
 
-			Self::Let { identifier, init, .. } => {
-				Keyword::Let.fmt(f)?;
+			Self::Let { identifier, annotation, init, is_const, .. } => {
+				if *is_const {
+					Keyword::Const.fmt(f)?;
+				} else {
+					Keyword::Let.fmt(f)?;
+				}
+
 				" ".fmt(f)?;
 				identifier.fmt(f, context.interner)?;
+
+				if let Some(annotation) = annotation {
+					": ".fmt(f)?;
+					annotation.fmt(f, context.interner)?;
+				}
+
 				" = ".fmt(f)?;
 				init.fmt(f, context)
 			}
 
+			Self::LetDict { fields, init, .. } => {
+				Keyword::Let.fmt(f)?;
+				" @[ ".fmt(f)?;
+
+				fmt::sep_by(
+					fields.iter(),
+					f,
+					|binding, f| {
+						binding.field.fmt(f, context.interner)?;
+
+						if binding.identifier != binding.field {
+							": ".fmt(f)?;
+							binding.identifier.fmt(f, context.interner)?;
+						}
+
+						Ok(())
+					},
+					", ",
+				)?;
+
+				" ] = ".fmt(f)?;
+				init.fmt(f, context)
+			}
+
+			Self::LetArray { items, init, .. } => {
+				Keyword::Let.fmt(f)?;
+				" [ ".fmt(f)?;
+
+				fmt::sep_by(
+					items.iter(),
+					f,
+					|binding, f| binding.identifier.fmt(f, context.interner),
+					", ",
+				)?;
+
+				" ] = ".fmt(f)?;
+				init.fmt(f, context)
+			}
+
 			Self::Assign { left, right, .. } => {
 				left.fmt(f, context.inlined())?;
 				" = ".fmt(f)?;
 				right.fmt(f, context)
 			}
 
+			Self::MultiAssign { left, right, .. } => {
+				fmt::sep_by(left.iter(), f, |expr, f| expr.fmt(f, context.inlined()), ", ")?;
+				" = ".fmt(f)?;
+				fmt::sep_by(right.iter(), f, |expr, f| expr.fmt(f, context), ", ")
+			}
+
 			Self::Return { expr, .. } => {
 				Keyword::Return.fmt(f)?;
 				" ".fmt(f)?;
 				expr.fmt(f, context)
 			}
 
-			Self::Break { .. } => Keyword::Break.fmt(f),
+			Self::Break { expr, .. } => {
+				Keyword::Break.fmt(f)?;
+
+				if let Some(expr) = expr {
+					" ".fmt(f)?;
+					expr.fmt(f, context)?;
+				}
+
+				Ok(())
+			}
+
+			Self::Continue { .. } => Keyword::Continue.fmt(f),
 
 			Self::While { condition, block, .. } => {
 				let step = if context.indentation.is_some() { "\n" } else { " " };
@@ -407,6 +645,37 @@ impl<'a> Display<'a> for Statement {
 				Keyword::End.fmt(f)
 			}
 
+			Self::Scope { block, .. } => {
+				let step = if context.indentation.is_some() { "\n" } else { " " };
+
+				Keyword::Begin.fmt(f)?;
+				step.fmt(f)?;
+
+				if !block.is_empty() {
+					block.fmt(f, context.indent())?;
+					step.fmt(f)?;
+				}
+
+				if let Some(indent) = context.indentation {
+					indent.fmt(f)?;
+				}
+
+				Keyword::End.fmt(f)
+			}
+
+			Self::Assert { condition, message, .. } => {
+				Keyword::Assert.fmt(f)?;
+				" ".fmt(f)?;
+				condition.fmt(f, context)?;
+
+				if let Some(message) = message {
+					", ".fmt(f)?;
+					message.fmt(f, context)?;
+				}
+
+				Ok(())
+			}
+
 			Self::Expr(expr) => expr.fmt(f, context),
 		}
 	}
@@ -470,6 +739,10 @@ impl<'a> Display<'a> for ArgExpansion {
 
 				color::Fg(color::Yellow, "]").fmt(f)
 			},
+			Self::Splat { symbol, .. } => {
+				color::Fg(color::Yellow, "@").fmt(f)?;
+				symbol.fmt(f, context)
+			},
 		}
 	}
 }
@@ -494,13 +767,7 @@ impl<'a> Display<'a> for Argument {
 		if self.pos == SourcePos::ill_formed() {
 			ILL_FORMED.fmt(f)
 		} else {
-			'"'.fmt(f)?;
-
-			for part in self.parts.iter() {
-				part.fmt(f, context)?;
-			}
-
-			'"'.fmt(f)
+			String::from_utf8_lossy(&self.to_source(context)).fmt(f)
 		}
 	}
 }
@@ -557,6 +824,11 @@ impl<'a> Display<'a> for BasicCommand {
 	type Context = &'a symbol::Interner;
 
 	fn fmt(&self, f: &mut std::fmt::Formatter, context: Self::Context) -> std::fmt::Result {
+		if let Some(label) = self.label {
+			TokenKind::Label(label).fmt(f, context)?;
+			" ".fmt(f)?;
+		}
+
 		for (key, value) in self.env.iter() {
 			key.fmt(f, context)?;
 			lexer::ArgPart::EnvAssign.fmt(f, context)?;
@@ -590,13 +862,45 @@ impl<'a> Display<'a> for Command {
 	type Context = &'a symbol::Interner;
 
 	fn fmt(&self, f: &mut std::fmt::Formatter, context: Self::Context) -> std::fmt::Result {
-		self.head.fmt(f, context)?;
+		match self {
+			Self::Pipeline { head, tail } => {
+				head.fmt(f, context)?;
+
+				for command in tail.iter() {
+					" ".fmt(f)?;
+					TokenKind::Pipe.fmt(f, context)?;
+					" ".fmt(f)?;
+					command.fmt(f, context)?;
+				}
+			}
 
-		for command in self.tail.iter() {
-			" ".fmt(f)?;
-			TokenKind::Pipe.fmt(f, context)?;
-			" ".fmt(f)?;
-			command.fmt(f, context)?;
+			Self::Conditional { condition, then, otherwise, .. } => {
+				Keyword::If.fmt(f)?;
+				" ".fmt(f)?;
+				condition.fmt(f, context)?;
+				"; ".fmt(f)?;
+				Keyword::Then.fmt(f)?;
+
+				for command in then.iter() {
+					" ".fmt(f)?;
+					command.fmt(f, context)?;
+					";".fmt(f)?;
+				}
+
+				if !otherwise.is_empty() {
+					" ".fmt(f)?;
+					Keyword::Else.fmt(f)?;
+
+					for command in otherwise.iter() {
+						" ".fmt(f)?;
+						command.fmt(f, context)?;
+						";".fmt(f)?;
+					}
+				}
+
+				" ".fmt(f)?;
+				Keyword::End.fmt(f)?;
+			}
 		}
 
 		Ok(())
@@ -610,6 +914,7 @@ impl std::fmt::Display for CommandBlockKind {
 			Self::Synchronous => "{",
 			Self::Asynchronous => "&{",
 			Self::Capture => "${",
+			Self::CombinedCapture => "@{",
 		}.fmt(f)
 	}
 }
@@ -635,7 +940,13 @@ impl<'a> Display<'a> for CommandBlock {
 
 		step(f, context)?;
 
-		"}".fmt(f)
+		"}".fmt(f)?;
+
+		if let Some(timeout) = self.timeout {
+			write!(f, ": {}", timeout.as_secs_f64())?;
+		}
+
+		Ok(())
 	}
 }
 
@@ -0,0 +1,555 @@
+//! Canonical structural hashing of ASTs, to be used as cache keys.
+//! Symbols are hashed by their resolved strings, so the hash does not depend on the
+//! interner. Source positions are ignored, as in structural diffing.
+
+use crate::symbol::{Interner, Symbol};
+use super::{
+	visit::{self, Visitor},
+	ArgExpansion,
+	ArgPart,
+	ArgUnit,
+	Argument,
+	Ast,
+	BasicCommand,
+	Block,
+	Command,
+	CommandBlock,
+	DictKey,
+	Expr,
+	Literal,
+	Param,
+	Redirection,
+	RedirectionTarget,
+	Statement,
+	TypeExpr,
+};
+
+
+/// Compute the structural hash of an AST. Structurally equal ASTs have the same hash,
+/// even if parsed with different interners. The hash is stable across runs and builds.
+pub fn hash(ast: &Ast, interner: &Interner) -> u64 {
+	let mut hasher = Hasher::new(interner);
+	hasher.visit_block(&ast.statements);
+	hasher.state
+}
+
+
+/// Compute the structural hash of a single statement. See hash.
+pub fn hash_statement(statement: &Statement, interner: &Interner) -> u64 {
+	let mut hasher = Hasher::new(interner);
+	hasher.visit_statement(statement);
+	hasher.state
+}
+
+
+/// Encode a single statement as the byte sequence its structural hash is computed from.
+/// Unlike hashes, encodings are equal only if the statements are structurally equal.
+pub fn encode_statement(statement: &Statement, interner: &Interner) -> Vec<u8> {
+	let mut hasher = Hasher::new(interner);
+	hasher.encoding = Some(Vec::new());
+	hasher.visit_statement(statement);
+	hasher.encoding.unwrap_or_default()
+}
+
+
+/// A FNV-1a hasher over the AST. The standard library hashers are not guaranteed to be
+/// stable, which makes them unsuitable for persistent cache keys.
+struct Hasher<'i> {
+	interner: &'i Interner,
+	state: u64,
+	/// The hashed bytes, if they should be kept.
+	encoding: Option<Vec<u8>>,
+}
+
+
+impl<'i> Hasher<'i> {
+	const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+	const PRIME: u64 = 0x0100_0000_01b3;
+
+
+	fn new(interner: &'i Interner) -> Self {
+		Self { interner, state: Self::OFFSET_BASIS, encoding: None }
+	}
+
+
+	fn write(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.state ^= byte as u64;
+			self.state = self.state.wrapping_mul(Self::PRIME);
+		}
+
+		if let Some(encoding) = &mut self.encoding {
+			encoding.extend_from_slice(bytes);
+		}
+	}
+
+
+	/// Variant tags and lengths prevent distinct trees from producing the same byte
+	/// sequence.
+	fn tag(&mut self, tag: u8) {
+		self.write(&[tag]);
+	}
+
+
+	fn len(&mut self, len: usize) {
+		self.write(&(len as u64).to_le_bytes());
+	}
+
+
+	fn bytes(&mut self, bytes: &[u8]) {
+		self.len(bytes.len());
+		self.write(bytes);
+	}
+
+
+	fn symbol(&mut self, symbol: Symbol) {
+		let interner = self.interner;
+		let value = interner
+			.resolve(symbol)
+			.expect("unresolved symbol");
+		self.bytes(value);
+	}
+
+
+	/// Fieldless enums are hashed by their variant names.
+	fn variant<T: std::fmt::Debug>(&mut self, value: &T) {
+		self.bytes(format!("{:?}", value).as_bytes());
+	}
+
+
+	fn option<T, F>(&mut self, value: Option<T>, mut hash: F)
+	where
+		F: FnMut(&mut Self, T),
+	{
+		match value {
+			None => self.tag(0),
+			Some(value) => {
+				self.tag(1);
+				hash(self, value);
+			}
+		}
+	}
+
+
+	/// Hash the data of a literal. Nested expressions and blocks are hashed by the walk.
+	fn literal(&mut self, literal: &Literal) {
+		match literal {
+			Literal::Nil => self.tag(0),
+
+			Literal::Bool(value) => {
+				self.tag(1);
+				self.tag(*value as u8);
+			}
+
+			Literal::Int(value) => {
+				self.tag(2);
+				self.write(&value.to_le_bytes());
+			}
+
+			Literal::Float(value) => {
+				self.tag(3);
+				self.write(&value.to_bits().to_le_bytes());
+			}
+
+			Literal::Byte(value) => {
+				self.tag(4);
+				self.tag(*value);
+			}
+
+			Literal::String(value) => {
+				self.tag(5);
+				self.bytes(value);
+			}
+
+			Literal::Array(items) => {
+				self.tag(6);
+				self.len(items.len());
+			}
+
+			Literal::Dict(items) => {
+				self.tag(7);
+				self.len(items.len());
+
+				// Keys are hashed before the values, which are hashed by the walk, as are
+				// computed keys.
+				for item in items.iter() {
+					match &item.key {
+						DictKey::Symbol(symbol) => {
+							self.tag(0);
+							self.symbol(*symbol);
+						}
+
+						DictKey::Expr(_) => self.tag(1),
+					}
+				}
+			}
+
+			Literal::Function { params, return_type, .. } => {
+				self.tag(8);
+				self.len(params.len());
+				params.iter().for_each(|param| self.param(param));
+				self.option(return_type.as_ref(), Self::type_expr);
+			}
+		}
+	}
+
+
+	fn type_expr(&mut self, type_expr: &TypeExpr) {
+		match type_expr {
+			TypeExpr::IllFormed => self.tag(0),
+
+			TypeExpr::Named { name, .. } => {
+				self.tag(1);
+				self.symbol(*name);
+			}
+
+			TypeExpr::Array { item, .. } => {
+				self.tag(2);
+				self.type_expr(item);
+			}
+
+			TypeExpr::Dict { key, value, .. } => {
+				self.tag(3);
+				self.type_expr(key);
+				self.type_expr(value);
+			}
+
+			TypeExpr::Function { params, return_type, .. } => {
+				self.tag(4);
+				self.len(params.len());
+				params.iter().for_each(|param| self.type_expr(param));
+				self.option(return_type.as_deref(), Self::type_expr);
+			}
+
+			TypeExpr::Nullable { inner, .. } => {
+				self.tag(5);
+				self.type_expr(inner);
+			}
+		}
+	}
+
+
+	fn param(&mut self, param: &Param) {
+		self.symbol(param.identifier);
+		self.option(param.annotation.as_ref(), Self::type_expr);
+		// Default values are hashed by the walk.
+		self.tag(param.default.is_some() as u8);
+	}
+
+
+	fn command_block(&mut self, block: &CommandBlock) {
+		self.variant(&block.kind);
+		self.option(block.timeout, |hasher, timeout| {
+			hasher.write(&timeout.as_nanos().to_le_bytes())
+		});
+		self.len(block.tail.len());
+
+		for command in std::iter::once(&block.head).chain(block.tail.iter()) {
+			self.command(command);
+		}
+	}
+
+
+	fn command(&mut self, command: &Command) {
+		match command {
+			Command::Pipeline { head, tail } => {
+				self.tag(0);
+				self.len(tail.len());
+
+				for command in std::iter::once(head).chain(tail.iter()) {
+					self.basic_command(command);
+				}
+			}
+
+			Command::Conditional { condition, then, otherwise, .. } => {
+				self.tag(1);
+				self.command(condition);
+
+				for commands in [then, otherwise].iter() {
+					self.len(commands.len());
+					commands.iter().for_each(|command| self.command(command));
+				}
+			}
+		}
+	}
+
+
+	fn basic_command(&mut self, command: &BasicCommand) {
+		self.option(command.label, Self::symbol);
+		self.argument(&command.program);
+
+		self.len(command.env.len());
+		for (key, value) in command.env.iter() {
+			self.arg_unit(key);
+			self.argument(value);
+		}
+
+		self.len(command.arguments.len());
+		command.arguments.iter().for_each(|argument| self.argument(argument));
+
+		self.len(command.redirections.len());
+		for redirection in command.redirections.iter() {
+			self.redirection(redirection);
+		}
+
+		self.tag(command.abort_on_error as u8);
+	}
+
+
+	fn argument(&mut self, argument: &Argument) {
+		self.len(argument.parts.len());
+
+		for part in argument.parts.iter() {
+			match part {
+				ArgPart::Unit(unit) => {
+					self.tag(0);
+					self.arg_unit(unit);
+				}
+
+				ArgPart::Expansion(expansion) => {
+					self.tag(1);
+					self.arg_expansion(expansion);
+				}
+			}
+		}
+	}
+
+
+	fn arg_unit(&mut self, unit: &ArgUnit) {
+		match unit {
+			ArgUnit::Literal(literal) => {
+				self.tag(0);
+				self.bytes(literal);
+			}
+
+			ArgUnit::Dollar { symbol, .. } => {
+				self.tag(1);
+				self.symbol(*symbol);
+			}
+		}
+	}
+
+
+	fn arg_expansion(&mut self, expansion: &ArgExpansion) {
+		match expansion {
+			ArgExpansion::Home => self.tag(0),
+
+			ArgExpansion::Range(from, to) => {
+				self.tag(1);
+				self.write(&from.to_le_bytes());
+				self.write(&to.to_le_bytes());
+			}
+
+			ArgExpansion::Collection(items) => {
+				self.tag(2);
+				self.len(items.len());
+				items.iter().for_each(|item| self.arg_unit(item));
+			}
+
+			ArgExpansion::Star => self.tag(3),
+
+			ArgExpansion::Percent => self.tag(4),
+
+			ArgExpansion::CharClass(class) => {
+				self.tag(5);
+				self.bytes(class);
+			}
+
+			ArgExpansion::Splat { symbol, .. } => {
+				self.tag(6);
+				self.symbol(*symbol);
+			}
+		}
+	}
+
+
+	fn redirection(&mut self, redirection: &Redirection) {
+		match redirection {
+			Redirection::IllFormed => self.tag(0),
+
+			Redirection::Output { source, target } => {
+				self.tag(1);
+				self.write(&source.to_le_bytes());
+
+				match target {
+					RedirectionTarget::Fd(fd) => {
+						self.tag(0);
+						self.write(&fd.to_le_bytes());
+					}
+
+					RedirectionTarget::Overwrite(argument) => {
+						self.tag(1);
+						self.argument(argument);
+					}
+
+					RedirectionTarget::Append(argument) => {
+						self.tag(2);
+						self.argument(argument);
+					}
+				}
+			}
+
+			Redirection::Input { literal, source } => {
+				self.tag(2);
+				self.tag(*literal as u8);
+				self.argument(source);
+			}
+		}
+	}
+}
+
+
+/// Each node hashes its own tag and data, and the walk functions then hash its children in
+/// order. Optional children and child lists are prefixed with their presence or length.
+impl<'a, 'i> Visitor<'a> for Hasher<'i> {
+	fn visit_block(&mut self, block: &'a Block) {
+		match block {
+			Block::IllFormed => self.tag(0),
+			Block::Block(statements) => {
+				self.tag(1);
+				self.len(statements.len());
+			}
+		}
+
+		visit::walk_block(self, block)
+	}
+
+
+	fn visit_statement(&mut self, statement: &'a Statement) {
+		match statement {
+			Statement::IllFormed => self.tag(0),
+
+			Statement::Let { identifier, annotation, is_const, .. } => {
+				self.tag(1);
+				self.symbol(*identifier);
+				self.tag(*is_const as u8);
+				self.option(annotation.as_ref(), Self::type_expr);
+			}
+
+			Statement::LetDict { fields, .. } => {
+				self.tag(11);
+				self.len(fields.len());
+
+				for binding in fields.iter() {
+					self.symbol(binding.field);
+					self.symbol(binding.identifier);
+				}
+			}
+
+			Statement::LetArray { items, .. } => {
+				self.tag(12);
+				self.len(items.len());
+
+				for binding in items.iter() {
+					self.symbol(binding.identifier);
+				}
+			}
+
+			Statement::Assign { .. } => self.tag(2),
+
+			Statement::MultiAssign { left, .. } => {
+				self.tag(13);
+				self.len(left.len());
+			}
+
+			Statement::Return { .. } => self.tag(3),
+
+			Statement::Break { expr, .. } => {
+				self.tag(4);
+				self.tag(expr.is_some() as u8);
+			}
+
+			Statement::Continue { .. } => self.tag(5),
+
+			Statement::While { .. } => self.tag(6),
+
+			Statement::For { identifier, .. } => {
+				self.tag(7);
+				self.symbol(*identifier);
+			}
+
+			Statement::Scope { .. } => self.tag(8),
+
+			Statement::Assert { message, .. } => {
+				self.tag(9);
+				self.tag(message.is_some() as u8);
+			}
+
+			Statement::Expr(_) => self.tag(10),
+		}
+
+		visit::walk_statement(self, statement)
+	}
+
+
+	fn visit_expr(&mut self, expr: &'a Expr) {
+		match expr {
+			Expr::IllFormed => self.tag(0),
+
+			Expr::Self_ { .. } => self.tag(1),
+
+			Expr::Identifier { identifier, .. } => {
+				self.tag(2);
+				self.symbol(*identifier);
+			}
+
+			Expr::Literal { literal, .. } => {
+				self.tag(3);
+				self.literal(literal);
+			}
+
+			Expr::UnaryOp { op, .. } => {
+				self.tag(4);
+				self.variant(op);
+			}
+
+			Expr::BinaryOp { op, .. } => {
+				self.tag(5);
+				self.variant(op);
+			}
+
+			Expr::If { .. } => self.tag(6),
+
+			Expr::When { .. } => self.tag(7),
+
+			Expr::Match { arms, .. } => {
+				self.tag(13);
+				self.len(arms.len());
+			}
+
+			Expr::Access { .. } => self.tag(8),
+
+			Expr::Field { name, .. } => {
+				self.tag(9);
+				self.symbol(*name);
+			}
+
+			Expr::Call { args, .. } => {
+				self.tag(10);
+				self.len(args.len());
+			}
+
+			Expr::CommandBlock { block, .. } => {
+				self.tag(11);
+				self.command_block(block);
+			}
+
+			Expr::Comprehension { identifier, filter, .. } => {
+				self.tag(12);
+				self.symbol(*identifier);
+				self.tag(filter.is_some() as u8);
+			}
+
+			Expr::Spread { .. } => self.tag(15),
+
+			Expr::Range { inclusive, .. } => {
+				self.tag(14);
+				self.tag(*inclusive as u8);
+			}
+
+			Expr::Parenthesized { .. } => self.tag(16),
+		}
+
+		visit::walk_expr(self, expr)
+	}
+}
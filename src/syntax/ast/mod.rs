@@ -1,7 +1,19 @@
+pub mod build;
 mod command;
+pub mod constants;
+pub mod diff;
+pub mod hash;
 pub mod fmt;
+#[cfg(feature = "serde")]
+pub mod serialize;
+#[cfg(test)]
+mod tests;
+pub mod visit;
 
-use super::{lexer, SourcePos};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{lexer, SourcePos, Span};
 pub use crate::symbol::Symbol;
 pub use command::{
 	ArgPart,
@@ -55,7 +67,7 @@ where
 
 impl IllFormed for SourcePos {
 	fn ill_formed() -> Self {
-		Self { line: 0, column: 0, path: Symbol::default() }
+		Self { line: 0, column: 0, offset: 0, path: Symbol::default() }
 	}
 
 	fn is_ill_formed(&self) -> bool {
@@ -76,7 +88,8 @@ impl IllFormed for Symbol {
 
 
 /// A block is a list of statements, constituting a new scope.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Block {
 	IllFormed,
 	Block(Box<[Statement]>),
@@ -87,6 +100,20 @@ impl Block {
 	pub fn is_empty(&self) -> bool {
 		matches!(self, Self::Block(block) if block.is_empty())
 	}
+
+
+	/// Iterate the statements which are not ill-formed, skipping the placeholders left by
+	/// error recovery. An ill-formed block has no statements.
+	pub fn iter_well_formed(&self) -> impl Iterator<Item = &Statement> {
+		let statements: &[Statement] = match self {
+			Self::Block(statements) => statements,
+			Self::IllFormed => &[],
+		};
+
+		statements
+			.iter()
+			.filter(|statement| !statement.is_ill_formed())
+	}
 }
 
 
@@ -115,9 +142,193 @@ impl IllFormed for Block {
 }
 
 
+/// A type annotation. Annotations are parsed and stored, but not enforced yet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TypeExpr {
+	/// An ill-formed type, produced by a parse error.
+	IllFormed,
+	/// A named type, such as `Int`.
+	Named {
+		name: Symbol,
+		pos: SourcePos,
+	},
+	/// An array type: `[Int]`.
+	Array {
+		item: Box<TypeExpr>,
+		pos: SourcePos,
+	},
+	/// A dict type: `@[String: Int]`.
+	Dict {
+		key: Box<TypeExpr>,
+		value: Box<TypeExpr>,
+		pos: SourcePos,
+	},
+	/// A function type: `function(Int): Bool`. The return type may be omitted.
+	Function {
+		params: Box<[TypeExpr]>,
+		return_type: Option<Box<TypeExpr>>,
+		pos: SourcePos,
+	},
+	/// A nullable type: `Int?`.
+	Nullable {
+		inner: Box<TypeExpr>,
+		pos: SourcePos,
+	},
+}
+
+
+impl IllFormed for TypeExpr {
+	fn ill_formed() -> Self {
+		Self::IllFormed
+	}
+
+	fn is_ill_formed(&self) -> bool {
+		matches!(self, Self::IllFormed)
+	}
+}
+
+
+/// A function parameter.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Param {
+	pub identifier: Symbol,
+	/// The optional type annotation.
+	pub annotation: Option<TypeExpr>,
+	/// The default value, used when the argument is omitted.
+	pub default: Option<Expr>,
+	pub pos: SourcePos,
+	/// The trailing comment on the same line, if comments are preserved.
+	pub comment: Option<Box<[u8]>>,
+}
+
+
+impl IllFormed for Param {
+	fn ill_formed() -> Self {
+		Self {
+			identifier: Symbol::ill_formed(),
+			annotation: None,
+			default: None,
+			pos: SourcePos::ill_formed(),
+			comment: None,
+		}
+	}
+
+	fn is_ill_formed(&self) -> bool {
+		self.identifier.is_ill_formed()
+	}
+}
+
+
+/// The key of a dict literal entry.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DictKey {
+	/// An identifier key: `key: value`.
+	Symbol(Symbol),
+	/// A computed key: `[expr]: value`.
+	Expr(Box<Expr>),
+}
+
+
+impl IllFormed for DictKey {
+	fn ill_formed() -> Self {
+		Self::Symbol(Symbol::ill_formed())
+	}
+
+	fn is_ill_formed(&self) -> bool {
+		match self {
+			Self::Symbol(symbol) => symbol.is_ill_formed(),
+			Self::Expr(expr) => expr.is_ill_formed(),
+		}
+	}
+}
+
+
+/// A dict literal entry: `key: value`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DictEntry {
+	pub key: DictKey,
+	/// The position of the key.
+	pub pos: SourcePos,
+	pub value: Expr,
+	/// The trailing comment on the same line, if comments are preserved.
+	pub comment: Option<Box<[u8]>>,
+}
+
+
+impl IllFormed for DictEntry {
+	fn ill_formed() -> Self {
+		Self {
+			key: DictKey::ill_formed(),
+			pos: SourcePos::ill_formed(),
+			value: Expr::ill_formed(),
+			comment: None,
+		}
+	}
+
+	fn is_ill_formed(&self) -> bool {
+		self.key.is_ill_formed() || self.value.is_ill_formed()
+	}
+}
+
+
+/// A field binding in a dict destructuring pattern: `field` or `field: identifier`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldBinding {
+	/// The dict key.
+	pub field: Symbol,
+	/// The introduced identifier. Same as the field if not renamed.
+	pub identifier: Symbol,
+	pub pos: SourcePos,
+}
+
+
+impl IllFormed for FieldBinding {
+	fn ill_formed() -> Self {
+		Self {
+			field: Symbol::ill_formed(),
+			identifier: Symbol::ill_formed(),
+			pos: SourcePos::ill_formed(),
+		}
+	}
+
+	fn is_ill_formed(&self) -> bool {
+		self.field.is_ill_formed() || self.identifier.is_ill_formed()
+	}
+}
+
+
+/// An item binding in an array destructuring pattern.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ItemBinding {
+	pub identifier: Symbol,
+	pub pos: SourcePos,
+}
+
+
+impl IllFormed for ItemBinding {
+	fn ill_formed() -> Self {
+		Self {
+			identifier: Symbol::ill_formed(),
+			pos: SourcePos::ill_formed(),
+		}
+	}
+
+	fn is_ill_formed(&self) -> bool {
+		self.identifier.is_ill_formed()
+	}
+}
+
+
 /// Literals of all types in the language.
 /// Note that there are no literals for the error type.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Literal {
 	Nil,
 	Bool(bool),
@@ -126,16 +337,14 @@ pub enum Literal {
 	Byte(u8),
 	String(Box<[u8]>),
 	Array(Box<[Expr]>),
-	Dict(Box<[((Symbol, SourcePos), Expr)]>),
+	Dict(Box<[DictEntry]>),
 	Function {
-		/// A list of parameters (identifiers).
-		params: Box<[(Symbol, SourcePos)]>,
+		/// A list of parameters.
+		params: Box<[Param]>,
+		/// The optional return type annotation.
+		return_type: Option<TypeExpr>,
 		body: Block,
 	},
-	/// For the dot access operator, we want to be able to have identifiers as literal
-	/// strings instead of names for variables. This variant should only be used in such
-	/// case.
-	Identifier(Symbol),
 }
 
 
@@ -162,7 +371,8 @@ impl From<lexer::Literal> for Literal {
 
 
 /// Unary operators.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum UnaryOp {
 	Minus, // -
 	Not,   // not
@@ -193,7 +403,8 @@ impl From<lexer::Operator> for UnaryOp {
 /// Binary operators.
 /// Assignment/Access are not represented as operators, but directly as
 /// statements/expressions instead.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BinaryOp {
 	Plus,  // +
 	Minus, // -
@@ -215,6 +426,30 @@ pub enum BinaryOp {
 }
 
 
+impl BinaryOp {
+	/// Check whether the operator is an arithmetic operator.
+	pub fn is_arithmetic(&self) -> bool {
+		matches!(self, Self::Plus | Self::Minus | Self::Times | Self::Div | Self::Mod)
+	}
+
+
+	/// The precedence of the operator, from 1 for `or` up to 8 for the multiplicative
+	/// operators. All binary operators are left associative. The pipeline operator, which
+	/// is parsed as a call, sits between comparisons and concatenation.
+	pub fn precedence(&self) -> u8 {
+		match self {
+			Self::Or => 1,
+			Self::And => 2,
+			Self::Equals | Self::NotEquals => 3,
+			Self::Greater | Self::GreaterEquals | Self::Lower | Self::LowerEquals => 4,
+			Self::Concat => 6,
+			Self::Plus | Self::Minus => 7,
+			Self::Times | Self::Div | Self::Mod => 8,
+		}
+	}
+}
+
+
 /// Warning, the following instance may panic if used with unmapped operators.
 impl From<lexer::Operator> for BinaryOp {
 	fn from(op: lexer::Operator) -> Self {
@@ -240,32 +475,38 @@ impl From<lexer::Operator> for BinaryOp {
 
 
 /// Expressions of all kinds in the language.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Expr {
 	/// An ill-formed expr, produced by a parse error.
 	IllFormed,
 	/// The `self` keyword.
 	Self_ {
 		pos: SourcePos,
+		span: Span,
 	},
 	Identifier {
 		identifier: Symbol,
 		pos: SourcePos,
+		span: Span,
 	},
 	Literal {
 		literal: Literal,
 		pos: SourcePos,
+		span: Span,
 	},
 	UnaryOp {
 		op: UnaryOp,
 		operand: Box<Expr>,
 		pos: SourcePos,
+		span: Span,
 	},
 	BinaryOp {
 		left: Box<Expr>,
 		op: BinaryOp,
 		right: Box<Expr>,
 		pos: SourcePos,
+		span: Span,
 	},
 	/// If-else expression.
 	If {
@@ -273,26 +514,265 @@ pub enum Expr {
 		then: Block,
 		otherwise: Block,
 		pos: SourcePos,
+		span: Span,
+	},
+	/// Guarded value: `when condition: value`. Evaluates to the value if the condition
+	/// holds, and to nil otherwise. The value extends as far as possible, so the guard
+	/// has the lowest precedence.
+	When {
+		condition: Box<Expr>,
+		value: Box<Expr>,
+		pos: SourcePos,
+		span: Span,
+	},
+	/// Match expression: `match scrutinee with pattern then block ... else block end`.
+	/// Each pattern is an expression compared for equality against the scrutinee, which is
+	/// evaluated only once. Arms are tried in order, and the first matching arm is taken.
+	Match {
+		scrutinee: Box<Expr>,
+		arms: Box<[(Expr, Block)]>,
+		otherwise: Block,
+		pos: SourcePos,
+		span: Span,
 	},
 	/// Field access ([]) operator.
 	Access {
 		object: Box<Expr>,
 		field: Box<Expr>,
 		pos: SourcePos,
+		span: Span,
+	},
+	/// Dot access operator. The name is a literal field name, and not a variable. Hence,
+	/// `var.name` is equivalent to `var["name"]`, and not to `var[name]`. The position is
+	/// the one of the name.
+	Field {
+		object: Box<Expr>,
+		name: Symbol,
+		pos: SourcePos,
+		span: Span,
 	},
 	/// Function call (()) operator.
 	Call {
 		function: Box<Expr>,
 		args: Box<[Expr]>,
 		pos: SourcePos,
+		span: Span,
 	},
 	CommandBlock {
-		block: CommandBlock,
+		block: Box<CommandBlock>,
 		pos: SourcePos,
+		span: Span,
+	},
+	/// Array comprehension: `[ output for identifier in source if filter ]`.
+	Comprehension {
+		output: Box<Expr>,
+		identifier: Symbol,
+		source: Box<Expr>,
+		filter: Option<Box<Expr>>,
+		pos: SourcePos,
+		span: Span,
+	},
+	/// Spread operator: `...operand`. Expands an array into the items of the enclosing array
+	/// literal, or into the arguments of the enclosing call. Spreads are not allowed anywhere
+	/// else, but there may be many of them in any position of the items or arguments.
+	Spread {
+		operand: Box<Expr>,
+		pos: SourcePos,
+		span: Span,
+	},
+	/// Numeric range: `start..end`, or `start..=end` if inclusive. Evaluates to an iterator
+	/// function, counting down if the end is lower than the start.
+	Range {
+		start: Box<Expr>,
+		end: Box<Expr>,
+		inclusive: bool,
+		pos: SourcePos,
+		span: Span,
+	},
+	/// Parenthesized expression: `(expr)`. The position is the one of the inner expression,
+	/// but the span includes the parentheses.
+	Parenthesized {
+		expr: Box<Expr>,
+		pos: SourcePos,
+		span: Span,
 	},
 }
 
 
+impl Expr {
+	/// The precedence of the pipeline operator, as in `x |> f`.
+	pub const PIPELINE_PRECEDENCE: u8 = 5;
+	/// The precedence of prefix operators, which is higher than any binary operator.
+	pub const PREFIX_PRECEDENCE: u8 = 9;
+	/// The precedence of postfix operators, calls, accesses and primary expressions.
+	pub const POSTFIX_PRECEDENCE: u8 = 10;
+
+
+	/// The precedence of the outermost operator of the expression. An operand must be
+	/// parenthesized if its precedence is lower than the operator's. Ranges, spreads and
+	/// ternary conditionals have the lowest precedence, zero. As ternaries are parsed as if
+	/// expressions, the same applies to all if expressions.
+	pub fn precedence(&self) -> u8 {
+		match self {
+			Self::IllFormed
+			| Self::If { .. }
+			| Self::Spread { .. }
+			| Self::Range { .. } => 0,
+
+			Self::BinaryOp { op, .. } => op.precedence(),
+
+			Self::Call { .. } if self.is_pipeline() => Self::PIPELINE_PRECEDENCE,
+
+			Self::UnaryOp { op, .. } if !op.is_postfix() => Self::PREFIX_PRECEDENCE,
+
+			Self::Self_ { .. }
+			| Self::Identifier { .. }
+			| Self::Literal { .. }
+			| Self::UnaryOp { .. }
+			| Self::When { .. }
+			| Self::Match { .. }
+			| Self::Access { .. }
+			| Self::Field { .. }
+			| Self::Call { .. }
+			| Self::CommandBlock { .. }
+			| Self::Comprehension { .. }
+			| Self::Parenthesized { .. } => Self::POSTFIX_PRECEDENCE,
+		}
+	}
+
+
+	/// Whether the expression is a call parsed from the pipeline operator, as in `x |> f`.
+	/// In such calls, the first argument precedes the function in the source.
+	pub fn is_pipeline(&self) -> bool {
+		match self {
+			Self::Call { function, args, .. } => {
+				let function = function.span();
+				let first = args.first().and_then(Self::span);
+
+				matches!((first, function), (Some(first), Some(function)) if first.start < function.start)
+			}
+
+			_ => false,
+		}
+	}
+
+
+	/// The position of the expression, if it is well formed.
+	pub fn pos(&self) -> Option<SourcePos> {
+		match self {
+			Self::IllFormed => None,
+			Self::Self_ { pos, .. }
+			| Self::Identifier { pos, .. }
+			| Self::Literal { pos, .. }
+			| Self::UnaryOp { pos, .. }
+			| Self::BinaryOp { pos, .. }
+			| Self::If { pos, .. }
+			| Self::When { pos, .. }
+			| Self::Match { pos, .. }
+			| Self::Access { pos, .. }
+			| Self::Field { pos, .. }
+			| Self::Call { pos, .. }
+			| Self::CommandBlock { pos, .. }
+			| Self::Comprehension { pos, .. }
+			| Self::Spread { pos, .. }
+			| Self::Range { pos, .. }
+			| Self::Parenthesized { pos, .. } => Some(*pos),
+		}
+	}
+
+
+	/// The range covered by the expression, if it is well formed. Unlike the position, this
+	/// starts at the first token of the expression, as in the left operand of an operator.
+	pub fn span(&self) -> Option<Span> {
+		match self {
+			Self::IllFormed => None,
+			Self::Self_ { span, .. }
+			| Self::Identifier { span, .. }
+			| Self::Literal { span, .. }
+			| Self::UnaryOp { span, .. }
+			| Self::BinaryOp { span, .. }
+			| Self::If { span, .. }
+			| Self::When { span, .. }
+			| Self::Match { span, .. }
+			| Self::Access { span, .. }
+			| Self::Field { span, .. }
+			| Self::Call { span, .. }
+			| Self::CommandBlock { span, .. }
+			| Self::Comprehension { span, .. }
+			| Self::Spread { span, .. }
+			| Self::Range { span, .. }
+			| Self::Parenthesized { span, .. } => Some(*span),
+		}
+	}
+
+
+	/// Mutable access to the span of the expression, if it is well formed.
+	pub fn span_mut(&mut self) -> Option<&mut Span> {
+		match self {
+			Self::IllFormed => None,
+			Self::Self_ { span, .. }
+			| Self::Identifier { span, .. }
+			| Self::Literal { span, .. }
+			| Self::UnaryOp { span, .. }
+			| Self::BinaryOp { span, .. }
+			| Self::If { span, .. }
+			| Self::When { span, .. }
+			| Self::Match { span, .. }
+			| Self::Access { span, .. }
+			| Self::Field { span, .. }
+			| Self::Call { span, .. }
+			| Self::CommandBlock { span, .. }
+			| Self::Comprehension { span, .. }
+			| Self::Spread { span, .. }
+			| Self::Range { span, .. }
+			| Self::Parenthesized { span, .. } => Some(span),
+		}
+	}
+
+
+	/// Flatten a chain of dot and bracket accesses into the root expression and the
+	/// ordered access steps. For instance, `a.b[c]` is flattened into `a` and the steps
+	/// `.b` and `[c]`. Parentheses in the chain are skipped. Expressions which are not
+	/// accesses have no steps.
+	pub fn access_path(&self) -> (&Expr, Vec<AccessStep<'_>>) {
+		let mut steps = Vec::new();
+		let mut expr = self;
+
+		loop {
+			match expr {
+				Self::Field { object, name, .. } => {
+					steps.push(AccessStep::Field(*name));
+					expr = object;
+				}
+
+				Self::Access { object, field, .. } => {
+					steps.push(AccessStep::Index(field));
+					expr = object;
+				}
+
+				Self::Parenthesized { expr: inner, .. } => expr = inner,
+
+				_ => break,
+			}
+		}
+
+		steps.reverse();
+
+		(expr, steps)
+	}
+}
+
+
+/// A step in an access path. See Expr::access_path.
+#[derive(Debug, PartialEq)]
+pub enum AccessStep<'a> {
+	/// Dot access, as in `object.name`.
+	Field(Symbol),
+	/// Bracket access, as in `object[index]`.
+	Index(&'a Expr),
+}
+
+
 impl IllFormed for Expr {
 	fn ill_formed() -> Self {
 		Self::IllFormed
@@ -305,33 +785,75 @@ impl IllFormed for Expr {
 
 
 /// Statements of all kinds in the language.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Statement {
 	/// An ill-formed statement, produced by a parse error.
 	IllFormed,
 	/// Introduces an identifier.
 	Let {
 		identifier: Symbol,
+		/// The optional type annotation.
+		annotation: Option<TypeExpr>,
+		init: Expr,
+		/// Whether the binding is immutable: `const identifier = init`.
+		is_const: bool,
+		pos: SourcePos,
+		span: Span,
+	},
+	/// Dict destructuring: `let @[ field, field: identifier ] = init`.
+	/// Introduces an identifier for each field.
+	LetDict {
+		fields: Box<[FieldBinding]>,
 		init: Expr,
 		pos: SourcePos,
+		span: Span,
+	},
+	/// Array destructuring: `let [ identifier, identifier ] = init`.
+	/// Introduces an identifier for each item. Missing items are nil, and extra items are
+	/// ignored.
+	LetArray {
+		items: Box<[ItemBinding]>,
+		init: Expr,
+		pos: SourcePos,
+		span: Span,
 	},
 	Assign {
 		left: Expr,
 		right: Expr,
 		pos: SourcePos,
+		span: Span,
+	},
+	/// Multiple assignment: `left, left = right, right`. All values are evaluated before
+	/// any assignment, so that `a, b = b, a` swaps the variables.
+	MultiAssign {
+		left: Box<[Expr]>,
+		right: Box<[Expr]>,
+		pos: SourcePos,
+		span: Span,
 	},
 	Return {
 		expr: Expr,
 		pos: SourcePos,
+		span: Span,
 	},
+	/// Break from a loop, optionally supplying a value.
 	Break {
+		expr: Option<Expr>,
 		pos: SourcePos,
+		span: Span,
+	},
+	/// Skip to the next iteration of a loop.
+	Continue {
+		pos: SourcePos,
+		span: Span,
 	},
 	/// While loop.
 	While {
 		condition: Expr,
 		block: Block,
 		pos: SourcePos,
+		span: Span,
 	},
 	/// For loop. Also introduces an identifier.
 	For {
@@ -339,11 +861,70 @@ pub enum Statement {
 		expr: Expr,
 		block: Block,
 		pos: SourcePos,
+		span: Span,
+	},
+	/// Explicit scope: `begin ... end`.
+	Scope {
+		block: Block,
+		pos: SourcePos,
+		span: Span,
+	},
+	/// Assertion: `assert condition, message`. Panics if the condition is false.
+	Assert {
+		condition: Expr,
+		/// The optional message, shown when the assertion fails.
+		message: Option<Expr>,
+		pos: SourcePos,
+		span: Span,
 	},
 	Expr(Expr),
 }
 
 
+impl Statement {
+	/// The position of the statement, if it is well formed.
+	pub fn pos(&self) -> Option<SourcePos> {
+		match self {
+			Self::IllFormed => None,
+			Self::Let { pos, .. }
+			| Self::LetDict { pos, .. }
+			| Self::LetArray { pos, .. }
+			| Self::Assign { pos, .. }
+			| Self::MultiAssign { pos, .. }
+			| Self::Return { pos, .. }
+			| Self::Break { pos, .. }
+			| Self::Continue { pos, .. }
+			| Self::While { pos, .. }
+			| Self::For { pos, .. }
+			| Self::Scope { pos, .. }
+			| Self::Assert { pos, .. } => Some(*pos),
+			Self::Expr(expr) => expr.pos(),
+		}
+	}
+
+
+	/// The range covered by the statement, if it is well formed.
+	pub fn span(&self) -> Option<Span> {
+		match self {
+			Self::IllFormed => None,
+			Self::Let { span, .. }
+			| Self::LetDict { span, .. }
+			| Self::LetArray { span, .. }
+			| Self::Assign { span, .. }
+			| Self::MultiAssign { span, .. }
+			| Self::Return { span, .. }
+			| Self::Break { span, .. }
+			| Self::Continue { span, .. }
+			| Self::While { span, .. }
+			| Self::For { span, .. }
+			| Self::Scope { span, .. }
+			| Self::Assert { span, .. } => Some(*span),
+			Self::Expr(expr) => expr.span(),
+		}
+	}
+}
+
+
 impl IllFormed for Statement {
 	fn ill_formed() -> Self {
 		Self::IllFormed
@@ -356,7 +937,8 @@ impl IllFormed for Statement {
 
 
 /// The abstract syntax tree for a source file.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Ast {
 	/// The source path. May be something fictional, like "<stdin>".
 	pub source: Symbol,
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::symbol::Interner;
+use super::Ast;
+
+
+/// An AST bundled with the interner required to resolve its symbols.
+#[derive(Serialize)]
+struct StandaloneRef<'a> {
+	interner: &'a Interner,
+	ast: &'a Ast,
+}
+
+
+/// The owned counterpart of StandaloneRef.
+#[derive(Deserialize)]
+struct Standalone {
+	interner: Interner,
+	ast: Ast,
+}
+
+
+/// Serialize the AST embedding the symbol table, producing a self-contained artifact that
+/// can be deserialized elsewhere.
+pub fn serialize<S>(ast: &Ast, interner: &Interner, serializer: S) -> Result<S::Ok, S::Error>
+where
+	S: Serializer,
+{
+	StandaloneRef { interner, ast }.serialize(serializer)
+}
+
+
+/// Deserialize an AST produced by `serialize`, rebuilding the interner for its symbols.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<(Ast, Interner), D::Error>
+where
+	D: Deserializer<'de>,
+{
+	let Standalone { interner, ast } = Standalone::deserialize(deserializer)?;
+	Ok((ast, interner))
+}
+
+
+/// Serialize the AST to JSON, embedding the symbol table.
+pub fn to_json(ast: &Ast, interner: &Interner) -> serde_json::Result<Vec<u8>> {
+	let mut json = Vec::new();
+	serialize(ast, interner, &mut serde_json::Serializer::new(&mut json))?;
+	Ok(json)
+}
+
+
+/// Deserialize an AST from JSON produced by `to_json`.
+pub fn from_json(json: &[u8]) -> serde_json::Result<(Ast, Interner)> {
+	deserialize(&mut serde_json::Deserializer::from_slice(json))
+}
+
+
+/// The magic bytes that start a binary serialized AST.
+#[cfg(feature = "bincode")]
+const MAGIC: &[u8; 8] = b"HUSHAST\0";
+
+
+/// The version of the binary format. This must be bumped whenever the AST or its
+/// serialization changes, so that stale caches are detected.
+#[cfg(feature = "bincode")]
+pub const FORMAT_VERSION: u32 = 27;
+
+
+/// An error in the binary serialization of the AST.
+#[cfg(feature = "bincode")]
+#[derive(Debug)]
+pub enum BinaryError {
+	/// The input does not start with the expected magic bytes.
+	InvalidHeader,
+	/// The input was serialized with a different format version.
+	VersionMismatch { version: u32 },
+	/// The encoding or decoding of the AST failed.
+	Bincode(bincode::Error),
+}
+
+
+#[cfg(feature = "bincode")]
+impl std::fmt::Display for BinaryError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InvalidHeader => write!(f, "invalid header"),
+
+			Self::VersionMismatch { version } => write!(
+				f,
+				"format version mismatch: expected {}, found {}",
+				FORMAT_VERSION,
+				version
+			),
+
+			Self::Bincode(error) => error.fmt(f),
+		}
+	}
+}
+
+
+#[cfg(feature = "bincode")]
+impl std::error::Error for BinaryError { }
+
+
+#[cfg(feature = "bincode")]
+impl From<bincode::Error> for BinaryError {
+	fn from(error: bincode::Error) -> Self {
+		Self::Bincode(error)
+	}
+}
+
+
+/// Serialize the AST to a compact binary format, embedding the symbol table.
+/// The output starts with a versioned header.
+#[cfg(feature = "bincode")]
+pub fn serialize_ast(ast: &Ast, interner: &Interner) -> Result<Vec<u8>, BinaryError> {
+	let mut bytes = Vec::new();
+
+	bytes.extend_from_slice(MAGIC);
+	bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+
+	bincode::serialize_into(&mut bytes, &StandaloneRef { interner, ast })?;
+
+	Ok(bytes)
+}
+
+
+/// Deserialize an AST produced by `serialize_ast`.
+/// Inputs with a mismatched header or format version are rejected.
+#[cfg(feature = "bincode")]
+pub fn deserialize_ast(bytes: &[u8]) -> Result<(Ast, Interner), BinaryError> {
+	let bytes = bytes
+		.strip_prefix(MAGIC.as_ref())
+		.ok_or(BinaryError::InvalidHeader)?;
+
+	if bytes.len() < 4 {
+		return Err(BinaryError::InvalidHeader);
+	}
+
+	let (version, bytes) = bytes.split_at(4);
+	let version = u32::from_le_bytes([ version[0], version[1], version[2], version[3] ]);
+
+	if version != FORMAT_VERSION {
+		return Err(BinaryError::VersionMismatch { version });
+	}
+
+	let Standalone { interner, ast } = bincode::deserialize(bytes)?;
+
+	Ok((ast, interner))
+}
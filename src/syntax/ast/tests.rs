@@ -0,0 +1,490 @@
+#[cfg(feature = "serde")]
+use std::{io, os::unix::ffi::OsStrExt};
+
+use crate::{
+	fmt,
+	symbol,
+	syntax::{Analysis, Source},
+};
+#[cfg(feature = "serde")]
+use crate::tests;
+use super::{
+	build,
+	constants::{self, Constant},
+	diff::{self, Change},
+	hash,
+	fmt::Context,
+	visit::{self, Visitor},
+	AccessStep,
+	ArgExpansion,
+	ArgPart,
+	ArgUnit,
+	Ast,
+	BinaryOp,
+	Block,
+	Expr,
+	IllFormed,
+	Literal,
+	SourcePos,
+	Statement,
+};
+#[cfg(feature = "serde")]
+use super::serialize;
+
+
+const INPUT: &str = r#"
+		let dict = @[ key: [ 1, 2.5, "three", '4' ], other: nil ]
+
+		function fun(param)
+			if param.key == dict["other"] then
+				return { echo $param | cat >> file; ls ~/*.hsh }
+			end
+
+			for item in std.iter(param) do
+				break
+			end
+		end
+"#;
+
+
+fn parse(input: &str, interner: &mut symbol::Interner) -> Ast {
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let analysis = Analysis::analyze(&source, interner);
+
+	assert!(analysis.is_ok());
+
+	analysis.ast
+}
+
+
+#[cfg(feature = "serde")]
+fn assert_same(
+	(ast, interner): (&Ast, &symbol::Interner),
+	(expected, expected_interner): (&Ast, &symbol::Interner)
+) {
+	assert_eq!(ast, expected);
+	assert_eq!(
+		fmt::Show(ast, Context::from(interner)).to_string(),
+		fmt::Show(expected, Context::from(expected_interner)).to_string(),
+	);
+}
+
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_round_trip() {
+	let mut interner = symbol::Interner::new();
+	let expected = parse(INPUT, &mut interner);
+
+	let json = serialize::to_json(&expected, &interner).expect("failed to serialize");
+	let (ast, deserialized_interner) = serialize::from_json(&json).expect("failed to deserialize");
+
+	assert_same((&ast, &deserialized_interner), (&expected, &interner));
+}
+
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_round_trip_scripts() -> io::Result<()> {
+	tests::util::test_dir(
+		"src/runtime/tests/data/positive",
+		|path, file| {
+			let mut interner = symbol::Interner::new();
+			let path = interner.get_or_intern(path.as_os_str().as_bytes());
+			let source = Source::from_reader(path, file)?;
+			let analysis = Analysis::analyze(&source, &mut interner);
+			assert!(analysis.is_ok());
+
+			let json = serialize::to_json(&analysis.ast, &interner).expect("failed to serialize");
+			let (ast, deserialized_interner) = serialize::from_json(&json).expect("failed to deserialize");
+
+			assert_same((&ast, &deserialized_interner), (&analysis.ast, &interner));
+
+			Ok(())
+		}
+	)
+}
+
+
+#[cfg(feature = "bincode")]
+#[test]
+fn test_binary_round_trip() {
+	let mut interner = symbol::Interner::new();
+	let expected = parse(INPUT, &mut interner);
+
+	let bytes = serialize::serialize_ast(&expected, &interner).expect("failed to serialize");
+	let (ast, deserialized_interner) = serialize::deserialize_ast(&bytes).expect("failed to deserialize");
+
+	assert_same((&ast, &deserialized_interner), (&expected, &interner));
+}
+
+
+#[cfg(feature = "bincode")]
+#[test]
+fn test_binary_version_mismatch() {
+	use assert_matches::assert_matches;
+
+	let mut interner = symbol::Interner::new();
+	let ast = parse(INPUT, &mut interner);
+
+	let mut bytes = serialize::serialize_ast(&ast, &interner).expect("failed to serialize");
+	let version = serialize::FORMAT_VERSION + 1;
+	bytes[8 .. 12].copy_from_slice(&version.to_le_bytes());
+
+	assert_matches!(
+		serialize::deserialize_ast(&bytes),
+		Err(serialize::BinaryError::VersionMismatch { version: v }) if v == version
+	);
+
+	assert_matches!(
+		serialize::deserialize_ast(b"HUSH"),
+		Err(serialize::BinaryError::InvalidHeader)
+	);
+}
+
+
+#[test]
+fn test_walk_calls() {
+	let input = r#"
+		let f = function(x) return x end
+		let y = f(f(1), [ std.len("a") ])
+		f(function() return f(2) end)()
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let ast = parse(input, &mut interner);
+
+	let mut calls = Vec::new();
+	ast.walk_calls(
+		|callee, pos| {
+			let callee = match callee {
+				Expr::Identifier { identifier, .. } => interner
+					.resolve(*identifier)
+					.map(|name| String::from_utf8_lossy(name).into_owned()),
+				_ => None,
+			};
+
+			calls.push((callee, pos.line, pos.column));
+		}
+	);
+
+	let f = || Some("f".to_owned());
+
+	assert_eq!(
+		calls,
+		[
+			(f(), 3, 11),
+			(f(), 3, 13),
+			(None, 3, 27),
+			(None, 4, 31),
+			(f(), 4, 3),
+			(f(), 4, 23),
+		]
+	);
+}
+
+
+#[test]
+fn test_enclosing_function() {
+	let input = r#"
+		function foo(x)
+			let inner = function(y) return y * 2 end
+			return std.map(function(z) return z end, x + 1)
+		end
+		let bar = 3
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let ast = parse(input, &mut interner);
+
+	let at = |needle: &str| SourcePos {
+		offset: input.find(needle).expect("missing needle"),
+		..SourcePos::default()
+	};
+
+	let name = |pos| {
+		let function = ast.enclosing_function(pos).expect("no enclosing function");
+		assert!(matches!(function.function, Expr::Literal { literal: Literal::Function { .. }, .. }));
+		function.name.and_then(|name| interner.resolve(name))
+	};
+
+	assert_eq!(name(at("x + 1")), Some(b"foo".as_ref()));
+	assert_eq!(name(at("y * 2")), Some(b"inner".as_ref()));
+	assert_eq!(name(at("return z")), None);
+
+	assert!(ast.enclosing_function(at("bar")).is_none());
+}
+
+
+#[test]
+fn test_arg_part_accessors() {
+	let mut interner = symbol::Interner::new();
+	let symbol = interner.get_or_intern("var");
+
+	let literal = ArgPart::Unit(ArgUnit::Literal(b"file".as_ref().into()));
+	assert!(literal.is_literal());
+	assert!(!literal.is_expansion());
+	assert_eq!(literal.as_bytes(), Some(b"file".as_ref()));
+	assert!(literal.expansion().is_none());
+
+	let dollar = ArgPart::Unit(ArgUnit::Dollar { symbol, pos: SourcePos::ill_formed() });
+	assert!(!dollar.is_literal());
+	assert!(!dollar.is_expansion());
+	assert_eq!(dollar.as_bytes(), None);
+	assert!(matches!(dollar.unit(), Some(ArgUnit::Dollar { symbol: s, .. }) if *s == symbol));
+
+	let star = ArgPart::Expansion(ArgExpansion::Star);
+	assert!(!star.is_literal());
+	assert!(star.is_expansion());
+	assert_eq!(star.as_bytes(), None);
+	assert!(star.unit().is_none());
+	assert_eq!(star.expansion(), Some(&ArgExpansion::Star));
+}
+
+
+#[test]
+fn test_build() {
+	let mut interner = symbol::Interner::new();
+	let std = interner.get_or_intern("std");
+	let print = interner.get_or_intern("print");
+	let x = interner.get_or_intern("x");
+
+	let sum = build::binop(
+		build::int(1),
+		BinaryOp::Plus,
+		build::binop(build::ident(x), BinaryOp::Times, build::float(2.5)),
+	);
+	let call = build::call(build::field(build::ident(std), print), vec![sum, build::string("x")]);
+
+	assert_eq!(
+		fmt::Show(&call, Context::from(&interner)).to_string(),
+		r#"std.print((1 + (x * 2.5)), "x")"#,
+	);
+
+	let pos = SourcePos { line: 1, column: 2, offset: 2, path: std };
+	assert_eq!(build::at(build::nil(), pos).pos(), Some(pos));
+	assert_eq!(build::nil().pos(), Some(SourcePos::ill_formed()));
+}
+
+
+#[test]
+fn test_access_path() {
+	let mut interner = symbol::Interner::new();
+	let ast = parse("config.server.ports[0]", &mut interner);
+
+	let expr = match &ast.statements {
+		Block::Block(statements) => match statements.as_ref() {
+			[Statement::Expr(expr)] => expr,
+			statements => panic!("unexpected statements: {:?}", statements),
+		},
+		block => panic!("unexpected block: {:?}", block),
+	};
+
+	let symbol = |name| interner.get(name).expect("symbol not interned");
+
+	let (root, steps) = expr.access_path();
+	assert!(matches!(root, Expr::Identifier { identifier, .. } if *identifier == symbol("config")));
+	assert_eq!(steps.len(), 3);
+	assert_eq!(steps[0], AccessStep::Field(symbol("server")));
+	assert_eq!(steps[1], AccessStep::Field(symbol("ports")));
+	assert!(
+		matches!(steps[2], AccessStep::Index(Expr::Literal { literal: Literal::Int(0), .. }))
+	);
+
+	// Expressions which are not accesses have no steps.
+	let (root, steps) = root.access_path();
+	assert!(matches!(root, Expr::Identifier { .. }));
+	assert!(steps.is_empty());
+}
+
+
+#[test]
+fn test_diff() {
+	let old = r#"
+		let x = 1
+
+		function double(n)
+			return n * 2
+		end
+
+		std.print(x)
+	"#;
+
+	// Changes the function body, which shifts the following lines, and reorders the bindings.
+	let new = r#"
+		function double(n)
+			let result = n + n
+			return result
+		end
+
+		let x = 1
+
+		std.print(x)
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let old = parse(old, &mut interner);
+	let new = parse(new, &mut interner);
+
+	assert_eq!(diff::diff(&old, &new, &interner), [ Change::Modified(0) ]);
+	assert!(diff::diff(&old, &old, &interner).is_empty());
+}
+
+
+#[test]
+fn test_hash() {
+	let mut interner = symbol::Interner::new();
+	let ast = parse(INPUT, &mut interner);
+
+	// Symbols get different ids in the other interner.
+	let mut other_interner = symbol::Interner::new();
+	other_interner.get_or_intern("unrelated");
+	other_interner.get_or_intern("param");
+	let other_ast = parse(INPUT, &mut other_interner);
+
+	assert_ne!(ast, other_ast);
+	assert_eq!(hash::hash(&ast, &interner), hash::hash(&other_ast, &other_interner));
+
+	let changed = parse(&INPUT.replace("param.key", "param.other"), &mut interner);
+	assert_ne!(hash::hash(&ast, &interner), hash::hash(&changed, &interner));
+
+	// Encodings ignore positions, but tell apart any structural change.
+	let shifted = parse(&format!("\n\n{}", INPUT), &mut interner);
+	let encode = |ast: &Ast| match &ast.statements {
+		Block::Block(statements) => hash::encode_statement(&statements[1], &interner),
+		Block::IllFormed => panic!("ill-formed block"),
+	};
+	assert_eq!(encode(&ast), encode(&shifted));
+	assert_ne!(encode(&ast), encode(&changed));
+}
+
+
+#[test]
+fn test_command_source_round_trip() {
+	let input = r#"
+		let name = "world"
+		{
+			echo 'hello world' "it's $name" ~/src/*.rs 'a\'b' "\$x" %.txt then 42;
+			cat < "in file" >> out | grep -v ${name}x?
+		}
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let ast = parse(input, &mut interner);
+
+	let formatted = fmt::Show(&ast.statements, Context::from(&interner)).to_string();
+	let reparsed = parse(&formatted, &mut interner);
+
+	assert_eq!(
+		hash::hash(&ast, &interner),
+		hash::hash(&reparsed, &interner),
+		"formatted source:\n{}",
+		formatted
+	);
+	assert_eq!(
+		formatted,
+		fmt::Show(&reparsed.statements, Context::from(&interner)).to_string(),
+	);
+}
+
+
+#[test]
+fn test_constants() {
+	let input = r#"
+		let x = 42
+		let y = [ 42, "42", 4.2, 42 ]
+		if x == 42 then
+			std.print("42")
+		end
+		let z
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let ast = parse(input, &mut interner);
+
+	let table = constants::constants(&ast);
+
+	let int = table.occurrences(&Constant::Int(42));
+	assert_eq!(
+		int.iter().map(|pos| (pos.line, pos.column)).collect::<Vec<_>>(),
+		[(2, 10), (3, 12), (3, 27), (4, 10)]
+	);
+	assert_eq!(table.occurrences(&Constant::String("42".as_bytes().into())).len(), 2);
+	assert_eq!(table.occurrences(&Constant::Float(4.2f64.to_bits())).len(), 1);
+	assert!(table.occurrences(&Constant::Int(0)).is_empty());
+
+	// Unique constants, in order of first occurrence. Nil is not collected.
+	assert_eq!(
+		table.iter().map(|(constant, _)| constant.clone()).collect::<Vec<_>>(),
+		[
+			Constant::Int(42),
+			Constant::String("42".as_bytes().into()),
+			Constant::Float(4.2f64.to_bits()),
+		]
+	);
+}
+
+
+#[test]
+fn test_visitor() {
+	/// Counts the calls, except those in function bodies.
+	#[derive(Default)]
+	struct Calls(usize);
+
+	impl<'a> Visitor<'a> for Calls {
+		fn visit_expr(&mut self, expr: &'a Expr) {
+			match expr {
+				Expr::Call { .. } => self.0 += 1,
+				Expr::Literal { literal: Literal::Function { .. }, .. } => return,
+				_ => (),
+			}
+
+			visit::walk_expr(self, expr)
+		}
+	}
+
+	let input = r#"
+		let x = std.len(std.to_string(1))
+		for i in std.range(0, x, 1) do
+			std.print([ i, std.type(i) ])
+		end
+		let f = function()
+			std.print("ignored")
+		end
+		f()
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let ast = parse(input, &mut interner);
+
+	let mut calls = Calls::default();
+	calls.visit_block(&ast.statements);
+	assert_eq!(calls.0, 6);
+}
+
+
+#[test]
+fn test_iter_well_formed() {
+	use assert_matches::assert_matches;
+
+	let pos = SourcePos::ill_formed();
+
+	let block = Block::from(
+		vec![
+			Statement::IllFormed,
+			Statement::Continue { pos, span: pos.into() },
+			Statement::IllFormed,
+			Statement::Expr(build::nil()),
+			Statement::IllFormed,
+		]
+			.into_boxed_slice()
+	);
+
+	assert_matches!(
+		block.iter_well_formed().collect::<Vec<_>>().as_slice(),
+		[Statement::Continue { .. }, Statement::Expr(_)]
+	);
+
+	assert_eq!(Block::IllFormed.iter_well_formed().count(), 0);
+}
@@ -0,0 +1,276 @@
+use super::{Ast, Block, DictKey, Expr, Literal, SourcePos, Statement, Symbol};
+
+
+/// A read-only AST visitor.
+/// Each method defaults to recursing into the node's children through the corresponding
+/// walk function. Implementors may override a method to inspect a node, and call the walk
+/// function to keep recursing. Not calling it skips the node's children.
+pub trait Visitor<'a>: Sized {
+	fn visit_block(&mut self, block: &'a Block) {
+		walk_block(self, block)
+	}
+
+
+	fn visit_statement(&mut self, statement: &'a Statement) {
+		walk_statement(self, statement)
+	}
+
+
+	fn visit_expr(&mut self, expr: &'a Expr) {
+		walk_expr(self, expr)
+	}
+}
+
+
+/// Visit the statements of a block.
+pub fn walk_block<'a, V: Visitor<'a>>(visitor: &mut V, block: &'a Block) {
+	if let Block::Block(statements) = block {
+		for statement in statements.iter() {
+			visitor.visit_statement(statement);
+		}
+	}
+}
+
+
+/// Visit the children of a statement.
+pub fn walk_statement<'a, V: Visitor<'a>>(visitor: &mut V, statement: &'a Statement) {
+	match statement {
+		Statement::IllFormed => (),
+
+		Statement::Let { init, .. } => visitor.visit_expr(init),
+
+		Statement::LetDict { init, .. } => visitor.visit_expr(init),
+
+		Statement::LetArray { init, .. } => visitor.visit_expr(init),
+
+		Statement::Assign { left, right, .. } => {
+			visitor.visit_expr(left);
+			visitor.visit_expr(right);
+		}
+
+		Statement::MultiAssign { left, right, .. } => {
+			for expr in left.iter().chain(right.iter()) {
+				visitor.visit_expr(expr);
+			}
+		}
+
+		Statement::Return { expr, .. } => visitor.visit_expr(expr),
+
+		Statement::Break { expr, .. } => {
+			if let Some(expr) = expr {
+				visitor.visit_expr(expr);
+			}
+		}
+
+		Statement::Continue { .. } => (),
+
+		Statement::While { condition, block, .. } => {
+			visitor.visit_expr(condition);
+			visitor.visit_block(block);
+		}
+
+		Statement::For { expr, block, .. } => {
+			visitor.visit_expr(expr);
+			visitor.visit_block(block);
+		}
+
+		Statement::Scope { block, .. } => visitor.visit_block(block),
+
+		Statement::Assert { condition, message, .. } => {
+			visitor.visit_expr(condition);
+
+			if let Some(message) = message {
+				visitor.visit_expr(message);
+			}
+		}
+
+		Statement::Expr(expr) => visitor.visit_expr(expr),
+	}
+}
+
+
+/// Visit the children of an expression.
+pub fn walk_expr<'a, V: Visitor<'a>>(visitor: &mut V, expr: &'a Expr) {
+	match expr {
+		Expr::IllFormed
+		| Expr::Self_ { .. }
+		| Expr::Identifier { .. }
+		| Expr::CommandBlock { .. } => (),
+
+		Expr::Literal { literal, .. } => match literal {
+			Literal::Array(items) => {
+				for item in items.iter() {
+					visitor.visit_expr(item);
+				}
+			}
+
+			Literal::Dict(items) => {
+				for item in items.iter() {
+					if let DictKey::Expr(key) = &item.key {
+						visitor.visit_expr(key);
+					}
+
+					visitor.visit_expr(&item.value);
+				}
+			}
+
+			Literal::Function { params, body, .. } => {
+				for default in params.iter().filter_map(|param| param.default.as_ref()) {
+					visitor.visit_expr(default);
+				}
+
+				visitor.visit_block(body)
+			}
+
+			_ => (),
+		},
+
+		Expr::UnaryOp { operand, .. } => visitor.visit_expr(operand),
+
+		Expr::BinaryOp { left, right, .. } => {
+			visitor.visit_expr(left);
+			visitor.visit_expr(right);
+		}
+
+		Expr::If { condition, then, otherwise, .. } => {
+			visitor.visit_expr(condition);
+			visitor.visit_block(then);
+			visitor.visit_block(otherwise);
+		}
+
+		Expr::When { condition, value, .. } => {
+			visitor.visit_expr(condition);
+			visitor.visit_expr(value);
+		}
+
+		Expr::Match { scrutinee, arms, otherwise, .. } => {
+			visitor.visit_expr(scrutinee);
+
+			for (pattern, block) in arms.iter() {
+				visitor.visit_expr(pattern);
+				visitor.visit_block(block);
+			}
+
+			visitor.visit_block(otherwise);
+		}
+
+		Expr::Access { object, field, .. } => {
+			visitor.visit_expr(object);
+			visitor.visit_expr(field);
+		}
+
+		Expr::Field { object, .. } => visitor.visit_expr(object),
+
+		Expr::Call { function, args, .. } => {
+			visitor.visit_expr(function);
+
+			for arg in args.iter() {
+				visitor.visit_expr(arg);
+			}
+		}
+
+		Expr::Comprehension { output, source, filter, .. } => {
+			visitor.visit_expr(output);
+			visitor.visit_expr(source);
+
+			if let Some(filter) = filter {
+				visitor.visit_expr(filter);
+			}
+		}
+
+		Expr::Spread { operand, .. }
+		| Expr::Parenthesized { expr: operand, .. } => visitor.visit_expr(operand),
+
+		Expr::Range { start, end, .. } => {
+			visitor.visit_expr(start);
+			visitor.visit_expr(end);
+		}
+	}
+}
+
+
+impl Ast {
+	/// Call the given function for each function call in the AST, with the callee
+	/// expression and the position of the call. Calls are visited in source order, with
+	/// outer calls before the calls nested in them.
+	pub fn walk_calls<'a, F>(&'a self, visit: F)
+	where
+		F: FnMut(&'a Expr, SourcePos),
+	{
+		struct Calls<F>(F);
+
+		impl<'a, F> Visitor<'a> for Calls<F>
+		where
+			F: FnMut(&'a Expr, SourcePos),
+		{
+			fn visit_expr(&mut self, expr: &'a Expr) {
+				if let Expr::Call { function, pos, .. } = expr {
+					(self.0)(function, *pos);
+				}
+
+				walk_expr(self, expr)
+			}
+		}
+
+		Calls(visit).visit_block(&self.statements);
+	}
+
+
+	/// Find the innermost function literal enclosing the given position. The position of
+	/// any node in the function's body may be used to find the function enclosing it.
+	pub fn enclosing_function(&self, pos: SourcePos) -> Option<EnclosingFunction<'_>> {
+		struct Enclosing<'a> {
+			pos: SourcePos,
+			function: Option<EnclosingFunction<'a>>,
+		}
+
+		impl<'a> Enclosing<'a> {
+			fn encloses(&self, expr: &Expr) -> bool {
+				matches!(
+					expr,
+					Expr::Literal { literal: Literal::Function { .. }, span, .. } if span.contains(self.pos)
+				)
+			}
+		}
+
+		// Functions are visited before the functions nested in them, so the innermost one
+		// is the last to be found.
+		impl<'a> Visitor<'a> for Enclosing<'a> {
+			fn visit_statement(&mut self, statement: &'a Statement) {
+				match statement {
+					Statement::Let { identifier, init, .. } if self.encloses(init) => {
+						self.function = Some(EnclosingFunction { function: init, name: Some(*identifier) });
+						walk_expr(self, init)
+					}
+
+					_ => walk_statement(self, statement),
+				}
+			}
+
+
+			fn visit_expr(&mut self, expr: &'a Expr) {
+				if self.encloses(expr) {
+					self.function = Some(EnclosingFunction { function: expr, name: None });
+				}
+
+				walk_expr(self, expr)
+			}
+		}
+
+		let mut enclosing = Enclosing { pos, function: None };
+		enclosing.visit_block(&self.statements);
+
+		enclosing.function
+	}
+}
+
+
+/// A function literal enclosing some position in the AST.
+#[derive(Debug, Clone, Copy)]
+pub struct EnclosingFunction<'a> {
+	/// The function literal expression.
+	pub function: &'a Expr,
+	/// The name the function is bound to, as in `let function name` or `let name =
+	/// function`. Anonymous functions have no name.
+	pub name: Option<Symbol>,
+}
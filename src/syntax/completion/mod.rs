@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests;
+
+use crate::symbol;
+use super::{
+	lexer::{Cursor, Keyword, Lexer, Operator, Token, TokenKind},
+	Source,
+};
+
+
+/// The syntactic context at a position in the source code, used to drive completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionContext {
+	/// The start of a statement, where keywords and expressions are allowed.
+	Statement,
+	/// An expression operand, such as after an operator or an opening parenthesis.
+	Operand,
+	/// A new name being declared, as in `let` or `for`. Nothing should be completed.
+	Declaration,
+	/// A member access, after a dot.
+	Member,
+	/// A function parameter list.
+	Parameters,
+	/// Inside a command block, or right before one, as after the capture keyword.
+	Command,
+}
+
+
+/// Classify the syntactic context at the given byte offset of the source code.
+/// Only the source code up to the offset is considered. If the offset is right after a
+/// word, such word is assumed to be incomplete and is ignored.
+pub fn completion_context(
+	source: &Source,
+	offset: usize,
+	interner: &mut symbol::Interner,
+) -> CompletionContext {
+	let offset = offset.min(source.contents.len());
+	let prefix = Source {
+		path: source.path,
+		contents: source.contents[.. offset].into(),
+	};
+
+	// The start of the word being completed, if any.
+	let word_start = prefix.contents
+		.iter()
+		.rposition(|c| !(c.is_ascii_alphanumeric() || *c == b'_'))
+		.map(|ix| ix + 1)
+		.unwrap_or(0);
+
+	let cursor = Cursor::from(&prefix);
+	let tokens = Lexer::new(cursor, interner)
+		.filter_map(Result::ok)
+		.filter(|token| word_start == offset || token.pos.offset < word_start);
+
+	let mut state = State::default();
+
+	for token in tokens {
+		state.visit(token);
+	}
+
+	state.context()
+}
+
+
+/// The completion state, updated for each token.
+#[derive(Debug, Default)]
+struct State {
+	/// The last visited token.
+	last: Option<TokenKind>,
+	/// How many command blocks are open.
+	command_depth: usize,
+	/// Whether a function keyword was visited, and its parameter list wasn't closed yet.
+	function: Option<FunctionState>,
+}
+
+
+/// The progress in a function header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FunctionState {
+	/// After the function keyword.
+	Keyword,
+	/// Inside the parameter list.
+	Parameters,
+}
+
+
+impl State {
+	fn visit(&mut self, token: Token) {
+		match token.kind {
+			TokenKind::Command
+				| TokenKind::AsyncCommand
+				| TokenKind::CaptureCommand
+				| TokenKind::CombinedCaptureCommand => {
+				self.command_depth += 1;
+			}
+
+			TokenKind::CloseCommand => {
+				self.command_depth = self.command_depth.saturating_sub(1);
+			}
+
+			TokenKind::Keyword(Keyword::Function) => self.function = Some(FunctionState::Keyword),
+
+			TokenKind::OpenParens if self.function == Some(FunctionState::Keyword) => {
+				self.function = Some(FunctionState::Parameters);
+			}
+
+			TokenKind::CloseParens if self.function == Some(FunctionState::Parameters) => {
+				self.function = None;
+			}
+
+			// The function name, in a let function statement.
+			TokenKind::Identifier(_) if self.function == Some(FunctionState::Keyword) => (),
+
+			_ if self.function == Some(FunctionState::Keyword) => self.function = None,
+
+			_ => (),
+		}
+
+		self.last = Some(token.kind);
+	}
+
+
+	fn context(&self) -> CompletionContext {
+		if self.command_depth > 0 {
+			return CompletionContext::Command;
+		}
+
+		if self.function == Some(FunctionState::Parameters) {
+			return CompletionContext::Parameters;
+		}
+
+		match &self.last {
+			None => CompletionContext::Statement,
+
+			Some(TokenKind::Operator(Operator::Dot)) => CompletionContext::Member,
+
+			// Postfix operator, which completes an expression.
+			Some(TokenKind::Operator(Operator::Try)) => CompletionContext::Statement,
+
+			Some(TokenKind::Operator(_)) => CompletionContext::Operand,
+
+			Some(TokenKind::Keyword(keyword)) => match keyword {
+				Keyword::Let | Keyword::Const | Keyword::For | Keyword::Function => CompletionContext::Declaration,
+
+				// Only a command block may follow.
+				Keyword::Capture => CompletionContext::Command,
+
+				Keyword::If
+				| Keyword::ElseIf
+				| Keyword::In
+				| Keyword::While
+				| Keyword::Return
+				| Keyword::Break
+				| Keyword::Assert
+				| Keyword::When
+				| Keyword::Match
+				| Keyword::With => CompletionContext::Operand,
+
+				Keyword::Then
+				| Keyword::Else
+				| Keyword::Continue
+				| Keyword::Do
+				| Keyword::End
+				| Keyword::Begin
+				| Keyword::Self_ => CompletionContext::Statement,
+			},
+
+			Some(
+				TokenKind::Colon
+				| TokenKind::Comma
+				| TokenKind::OpenParens
+				| TokenKind::OpenBracket
+				| TokenKind::OpenDict
+			) => CompletionContext::Operand,
+
+			// After a complete expression, a new statement may start.
+			Some(_) => CompletionContext::Statement,
+		}
+	}
+}
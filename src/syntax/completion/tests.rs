@@ -0,0 +1,44 @@
+use crate::symbol;
+use super::{completion_context, CompletionContext, Source};
+
+
+/// Get the completion context at the position marked with `|` in the input.
+fn context_at(input: &str) -> CompletionContext {
+	let offset = input.find('|').expect("missing cursor marker");
+	let input = input.replacen('|', "", 1);
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	completion_context(&source, offset, &mut interner)
+}
+
+
+#[test]
+fn test_completion_context() {
+	assert_eq!(context_at("|"), CompletionContext::Statement);
+	assert_eq!(context_at("let x = 1\nre|"), CompletionContext::Statement);
+	assert_eq!(context_at("if x then\n\t|\nend"), CompletionContext::Statement);
+
+	assert_eq!(context_at("let x = |"), CompletionContext::Operand);
+	assert_eq!(context_at("let x = y + fo|"), CompletionContext::Operand);
+	assert_eq!(context_at("f(1, |)"), CompletionContext::Operand);
+	assert_eq!(context_at("if |"), CompletionContext::Operand);
+
+	assert_eq!(context_at("let |"), CompletionContext::Declaration);
+	assert_eq!(context_at("for i| in"), CompletionContext::Declaration);
+
+	assert_eq!(context_at("std.|"), CompletionContext::Member);
+	assert_eq!(context_at("std.pr|int(1)"), CompletionContext::Member);
+
+	assert_eq!(context_at("function f(a, |)"), CompletionContext::Parameters);
+	assert_eq!(context_at("let f = function(|"), CompletionContext::Parameters);
+	assert_eq!(context_at("function f(a) |"), CompletionContext::Statement);
+
+	assert_eq!(context_at("let x = ${ ec|"), CompletionContext::Command);
+	assert_eq!(context_at("{ ls -l | gr| }"), CompletionContext::Command);
+	assert_eq!(context_at("{ ls }\n|"), CompletionContext::Statement);
+	assert_eq!(context_at("let x = capture |"), CompletionContext::Command);
+	assert_eq!(context_at("let x = capture { ec|"), CompletionContext::Command);
+}
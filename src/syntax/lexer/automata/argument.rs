@@ -1,9 +1,11 @@
 use super::{
 	word::{self, IsWord},
 	expansion::{self, Expansion, ExpansionContext},
+	ArgExpansion,
 	ArgPart,
 	ArgUnit,
 	Command,
+	Config,
 	Cursor,
 	Checkpoint,
 	Error,
@@ -224,7 +226,7 @@ where
 	}
 
 
-	pub fn visit(mut self, cursor: &Cursor, interner: &mut SymbolInterner) -> Transition {
+	pub fn visit(mut self, cursor: &Cursor, interner: &mut SymbolInterner, config: &Config) -> Transition {
 		macro_rules! produce {
 			($consume:expr) => {{
 				// If no characters have been read, the identifier is empty, which is an error.
@@ -236,7 +238,7 @@ where
 						.error(Error::invalid_identifier(identifier, self.pos))
 				}
 
-				match word::to_token(identifier, interner) {
+				match word::to_token(identifier, interner, config) {
 					TokenKind::Identifier(symbol) => {
 						if $consume {
 							self.context.produce(symbol, self.pos)
@@ -325,6 +327,100 @@ impl From<Dollar<DoubleQuoted>> for State {
 }
 
 
+/// The state for lexing array splats: `@args`. A splat must span the whole argument, and
+/// otherwise the argument is lexed as a literal word, as in `user@host`.
+#[derive(Debug)]
+pub(super) struct Splat {
+	/// The position of the at sign, where to rollback to.
+	start: Checkpoint,
+	pos: SourcePos,
+	/// The start offset of the identifier.
+	start_offset: Option<usize>,
+	/// The parent state.
+	parent: Argument,
+}
+
+
+impl Splat {
+	pub fn at(cursor: &Cursor, parent: Argument) -> Self {
+		Self {
+			start: cursor.checkpoint(),
+			pos: cursor.pos(),
+			start_offset: None,
+			parent,
+		}
+	}
+
+
+	pub fn visit(mut self, cursor: &Cursor, interner: &mut SymbolInterner, config: &Config) -> Transition {
+		match (self.start_offset, cursor.peek()) {
+			// Head character.
+			(None, Some(c)) if c.is_word_start() => {
+				self.start_offset = Some(cursor.offset());
+				Transition::step(self)
+			}
+
+			// Tail character.
+			(Some(_), Some(c)) if c.is_word() => Transition::step(self),
+
+			// End of argument.
+			(Some(offset), None) => self.produce(offset, cursor, interner, config),
+			(Some(offset), Some(c)) if Self::ends_argument(c) => {
+				self.produce(offset, cursor, interner, config)
+			}
+
+			// Not a splat.
+			_ => self.rollback(),
+		}
+	}
+
+
+	/// Produce the splat for the identifier starting at the given offset, without consuming
+	/// the current character.
+	fn produce(
+		mut self,
+		offset: usize,
+		cursor: &Cursor,
+		interner: &mut SymbolInterner,
+		config: &Config,
+	) -> Transition {
+		let identifier = &cursor.slice()[offset .. cursor.offset()];
+
+		match word::to_token(identifier, interner, config) {
+			TokenKind::Identifier(symbol) => {
+				self.parent.parts.push(
+					ArgPart::Expansion(ArgExpansion::Splat { symbol, pos: self.pos })
+				);
+
+				Transition::resume(self.parent)
+			}
+
+			// Keywords are not valid variables.
+			_ => self.rollback(),
+		}
+	}
+
+
+	/// Lex the argument as a literal word instead.
+	fn rollback(self) -> Transition {
+		Transition::rollback(self.start, Word::from(self.parent))
+	}
+
+
+	/// Whether a character ends the argument, such that it may not be part of a literal.
+	fn ends_argument(value: u8) -> bool {
+		!Argument::is_word(value) && !matches!(value, b'$' | b'\'' | b'"' | b'=')
+	}
+}
+
+
+impl From<Splat> for State {
+	fn from(state: Splat) -> State {
+		Self::Splat(state)
+	}
+}
+
+
 /// The state for lexing argument literals enclosed in single quotes.
 #[derive(Debug)]
 pub(super) struct SingleQuoted {
@@ -392,6 +488,7 @@ impl WordContext for SingleQuoted {
 			// Additional escape sequences:
 			b'n' => Some(b'\n'),
 			b't' => Some(b'\t'),
+			b'r' => Some(b'\r'),
 			b'0' => Some(b'\0'),
 			b'\\' => Some(b'\\'),
 
@@ -481,6 +578,7 @@ impl WordContext for DoubleQuoted {
 			// Additional escape sequences:
 			b'n' => Some(b'\n'),
 			b't' => Some(b'\t'),
+			b'r' => Some(b'\r'),
 			b'0' => Some(b'\0'),
 			b'\\' => Some(b'\\'),
 
@@ -530,6 +628,9 @@ impl Argument {
 			// Dollar.
 			Some(b'$') => Transition::step(Dollar::at(cursor, self)),
 
+			// Array splat, only in argument start.
+			Some(b'@') if self.parts.is_empty() => Transition::step(Splat::at(cursor, self)),
+
 			// Single quotes.
 			Some(b'\'') => Transition::step(SingleQuoted::from(self)),
 
@@ -553,10 +654,7 @@ impl Argument {
 			// End of argument.
 			_ => Transition::resume_produce(
 				Command,
-				Token {
-					kind: TokenKind::Argument(self.parts.into_boxed_slice()),
-					pos: self.pos,
-				},
+				Token::new(TokenKind::Argument(self.parts.into_boxed_slice()), self.pos),
 			),
 		}
 	}
@@ -580,6 +678,7 @@ impl WordContext for Argument {
 			b'$' => false,                         // Dollar.
 			b'=' => false,                         // Env assign.
 			b'}' => false,                         // Close command.
+			b'\0' => false,                        // Null bytes are never valid.
 			c if c.is_ascii_whitespace() => false, // Whitespace.
 			_ => true,
 		}
@@ -611,6 +710,7 @@ impl WordContext for Argument {
 			// Additional escape sequences:
 			b'n' => Some(b'\n'),
 			b't' => Some(b'\t'),
+			b'r' => Some(b'\r'),
 			b'0' => Some(b'\0'),
 			b'\\' => Some(b'\\'),
 
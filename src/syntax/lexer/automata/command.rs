@@ -1,6 +1,7 @@
 use super::{
 	symbol::CommandSymbolChar,
 	Argument,
+	BlockComment,
 	CommandSymbol,
 	Comment,
 	Cursor,
@@ -25,14 +26,20 @@ impl Command {
 			Some(c) if c.is_ascii_whitespace() => Transition::step(self),
 
 			// Comment.
-			Some(b'#') => Transition::step(Comment::from(self)),
+			Some(b'#') if BlockComment::<Self>::starts(cursor) => {
+				Transition::step(BlockComment::at(cursor, self))
+			}
+			Some(b'#') => Transition::step(Comment::at(cursor, self)),
 
 			// Close command block.
 			Some(b'}') => Transition::produce(
 				Root,
-				Token { kind: TokenKind::CloseCommand, pos: cursor.pos() },
+				Token::new(TokenKind::CloseCommand, cursor.pos()),
 			),
 
+			// Null bytes can't be part of arguments.
+			Some(b'\0') => Transition::error(self, Error::unexpected(b'\0', cursor.pos())),
+
 			// Argument or operator.
 			Some(c) => match CommandSymbolChar::from_first(c) {
 				// Argument.
@@ -40,7 +47,7 @@ impl Command {
 
 				// Semicolon, pipe or try.
 				CommandSymbolChar::Single(token) => {
-					Transition::produce(self, Token { kind: token, pos: cursor.pos() })
+					Transition::produce(self, Token::new(token, cursor.pos()))
 				}
 
 				// >, >>, <, <<.
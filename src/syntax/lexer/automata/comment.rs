@@ -1,10 +1,17 @@
-use super::{Command, Cursor, Root, State, Transition};
+use super::{Command, Cursor, Error, Root, SourcePos, State, Token, TokenKind, Transition};
+
 
 /// The state for lexing comments.
 /// This state is generic in the sense that it returns to the previous state once the
-/// entire comment is consumed.
+/// entire comment is consumed. The comment is produced as a token, which the automata
+/// discards unless comments are preserved.
 #[derive(Debug)]
-pub(super) struct Comment<S>(S);
+pub(super) struct Comment<S> {
+	state: S,
+	/// The offset of the hash.
+	start: usize,
+	pos: SourcePos,
+}
 
 
 impl<S> Comment<S>
@@ -12,21 +19,118 @@ where
 	S: Into<State>,
 	State: From<Self>,
 {
+	/// Create the state at the hash, returning to the given state afterwards.
+	pub fn at(cursor: &Cursor, state: S) -> Self {
+		Self { state, start: cursor.offset(), pos: cursor.pos() }
+	}
+
+
 	pub fn visit(self, cursor: &Cursor) -> Transition {
 		match cursor.peek() {
 			// Newline marks the end of the comment.
-			Some(b'\n') => Transition::resume(self.0),
+			Some(b'\n') => {
+				let token = self.token(&cursor.slice()[self.start .. cursor.offset()]);
+				Transition::resume_produce(self.state, token)
+			}
+
+			// Comments may end at EOF as well.
+			None => {
+				let token = self.token(&cursor.slice()[self.start ..]);
+				Transition::produce(self.state, token)
+			}
 
 			// Otherwise, eat everything.
 			_ => Transition::step(self),
 		}
 	}
+
+
+	/// The token for the comment, given its source including the hash.
+	fn token(&self, comment: &[u8]) -> Token {
+		let text = String::from_utf8_lossy(&comment[1 ..]);
+
+		Token::new(TokenKind::Comment(text.trim().as_bytes().into()), self.pos)
+	}
+}
+
+
+/// The state for lexing block comments: `#{ ... }#`. Block comments may be nested, in
+/// which case the entire outermost comment is consumed. Like line comments, this state
+/// returns to the previous state once the comment is consumed.
+#[derive(Debug)]
+pub(super) struct BlockComment<S> {
+	state: S,
+	/// The nesting depth.
+	depth: usize,
+	/// The previous character, which may start a delimiter.
+	previous: Option<u8>,
+	/// The position of the outermost opener.
+	pos: SourcePos,
+}
+
+
+impl<S> BlockComment<S>
+where
+	S: Into<State>,
+	State: From<Self>,
+{
+	/// Check if a block comment starts at the cursor.
+	pub fn starts(cursor: &Cursor) -> bool {
+		cursor.slice()[cursor.offset() ..].starts_with(b"#{")
+	}
+
+
+	/// Create the state at the opening hash, returning to the given state afterwards.
+	pub fn at(cursor: &Cursor, state: S) -> Self {
+		Self { state, depth: 0, previous: Some(b'#'), pos: cursor.pos() }
+	}
+
+
+	pub fn visit(mut self, cursor: &Cursor) -> Transition {
+		match (self.previous, cursor.peek()) {
+			// EOF before the outermost comment is closed.
+			(_, None) => {
+				let pos = self.pos;
+				Transition::error(self.state, Error::unterminated_block_comment(pos))
+			}
+
+			// Opener, possibly nested.
+			(Some(b'#'), Some(b'{')) => {
+				self.depth += 1;
+				self.previous = None;
+				Transition::step(self)
+			}
+
+			// Closer of the outermost comment.
+			(Some(b'}'), Some(b'#')) if self.depth == 1 => Transition::step(self.state),
+
+			// Closer of a nested comment.
+			(Some(b'}'), Some(b'#')) => {
+				self.depth -= 1;
+				self.previous = None;
+				Transition::step(self)
+			}
+
+			// Otherwise, eat everything.
+			(_, c) => {
+				self.previous = c;
+				Transition::step(self)
+			}
+		}
+	}
+}
+
+
+impl From<BlockComment<Root>> for State {
+	fn from(state: BlockComment<Root>) -> State {
+		Self::BlockComment(state)
+	}
 }
 
 
-impl<S> From<S> for Comment<S> {
-	fn from(state: S) -> Self {
-		Self(state)
+impl From<BlockComment<Command>> for State {
+	fn from(state: BlockComment<Command>) -> State {
+		Self::CommandBlockComment(state)
 	}
 }
 
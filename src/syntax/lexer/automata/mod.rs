@@ -12,20 +12,22 @@ use self::{
 	argument::{Argument, DoubleQuoted, SingleQuoted},
 	expansion::Expansion,
 	command::Command,
-	comment::Comment,
+	comment::{BlockComment, Comment},
 	number::NumberLiteral,
 	root::Root,
-	string::{ByteLiteral, StringLiteral},
-	symbol::{CommandSymbol, Symbol},
-	word::Word,
+	string::{ByteLiteral, RawStringLiteral, StringLiteral},
+	symbol::{CommandSymbol, Concat, Range, Symbol},
+	word::{IsWord, Word},
 };
 use super::{
 	ArgPart,
 	ArgExpansion,
 	ArgUnit,
+	CommandBlockKind,
 	CommandOperator,
 	Cursor,
 	Checkpoint,
+	Config,
 	Error,
 	ErrorKind,
 	Keyword,
@@ -34,6 +36,7 @@ use super::{
 	SourcePos,
 	Token,
 	TokenKind,
+	Warning,
 };
 use crate::symbol::Interner as SymbolInterner;
 
@@ -73,6 +76,8 @@ struct Transition {
 	step: Step,
 	/// The produced output, if any.
 	output: Option<Output>,
+	/// The produced warning, if any.
+	warning: Option<Warning>,
 }
 
 
@@ -80,7 +85,7 @@ impl Transition {
 	/// Consume the character while updating the machine state, but not producing a token
 	/// yet.
 	pub fn step<S: Into<State>>(state: S) -> Self {
-		Self { state: state.into(), step: Step::Forward, output: None }
+		Self { state: state.into(), step: Step::Forward, output: None, warning: None }
 	}
 
 	/// Consume the input character and produce a token.
@@ -89,6 +94,7 @@ impl Transition {
 			state: state.into(),
 			step: Step::Forward,
 			output: Some(Ok(token)),
+			warning: None,
 		}
 	}
 
@@ -98,12 +104,13 @@ impl Transition {
 			state: state.into(),
 			step: Step::Forward,
 			output: Some(Err(error)),
+			warning: None,
 		}
 	}
 
 	/// Don't consume the input character, updating the machine state instead.
 	pub fn resume<S: Into<State>>(state: S) -> Self {
-		Self { state: state.into(), step: Step::Resume, output: None }
+		Self { state: state.into(), step: Step::Resume, output: None, warning: None }
 	}
 
 	/// Don't consume the input character, but produce a token.
@@ -112,6 +119,7 @@ impl Transition {
 			state: state.into(),
 			step: Step::Resume,
 			output: Some(Ok(output)),
+			warning: None,
 		}
 	}
 
@@ -121,6 +129,7 @@ impl Transition {
 			state: state.into(),
 			step: Step::Resume,
 			output: Some(Err(error)),
+			warning: None,
 		}
 	}
 
@@ -130,8 +139,24 @@ impl Transition {
 			state: state.into(),
 			step: Step::Rollback(checkpoint),
 			output: None,
+			warning: None,
 		}
 	}
+
+	/// Rollback to a checkpoint with the given state, and produce a token.
+	pub fn rollback_produce<S: Into<State>>(checkpoint: Checkpoint, state: S, token: Token) -> Self {
+		Self {
+			state: state.into(),
+			step: Step::Rollback(checkpoint),
+			output: Some(Ok(token)),
+			warning: None,
+		}
+	}
+
+	/// Produce a warning along with the transition.
+	pub fn with_warning(self, warning: Warning) -> Self {
+		Self { warning: Some(warning), ..self }
+	}
 }
 
 
@@ -141,15 +166,20 @@ enum State {
 	// Top level lexer states:
 	Root(Root),
 	Comment(Comment<Root>),
+	BlockComment(BlockComment<Root>),
 	NumberLiteral(NumberLiteral),
 	ByteLiteral(ByteLiteral),
 	StringLiteral(StringLiteral),
+	RawStringLiteral(RawStringLiteral),
 	Word(Word),
 	Symbol(Symbol),
+	Concat(Concat),
+	Range(Range),
 
 	// Command block lexer states:
 	Command(Command),
 	CommandComment(Comment<Command>),
+	CommandBlockComment(BlockComment<Command>),
 	Argument(Argument),
 	Expansion(Expansion<Argument>),
 	ExpansionWord(Expansion<argument::Word<Argument>>),
@@ -160,6 +190,7 @@ enum State {
 	DoubleQuotedWord(argument::Word<DoubleQuoted>),
 	Dollar(argument::Dollar<Argument>),
 	QuotedDollar(argument::Dollar<DoubleQuoted>),
+	Splat(argument::Splat),
 	CommandSymbol(CommandSymbol),
 }
 
@@ -172,18 +203,23 @@ impl Default for State {
 
 
 impl State {
-	pub fn visit(self, cursor: &Cursor, interner: &mut SymbolInterner) -> Transition {
+	pub fn visit(self, cursor: &Cursor, interner: &mut SymbolInterner, config: &Config) -> Transition {
 		match self {
 			Self::Root(state) => state.visit(cursor),
 			Self::Comment(state) => state.visit(cursor),
+			Self::BlockComment(state) => state.visit(cursor),
 			Self::NumberLiteral(state) => state.visit(cursor),
-			Self::ByteLiteral(state) => state.visit(cursor),
-			Self::StringLiteral(state) => state.visit(cursor),
-			Self::Word(state) => state.visit(cursor, interner),
+			Self::ByteLiteral(state) => state.visit(cursor, config),
+			Self::StringLiteral(state) => state.visit(cursor, config),
+			Self::RawStringLiteral(state) => state.visit(cursor),
+			Self::Word(state) => state.visit(cursor, interner, config),
 			Self::Symbol(state) => state.visit(cursor),
+			Self::Concat(state) => state.visit(cursor),
+			Self::Range(state) => state.visit(cursor),
 
 			Self::Command(state) => state.visit(cursor),
 			Self::CommandComment(state) => state.visit(cursor),
+			Self::CommandBlockComment(state) => state.visit(cursor),
 			Self::Argument(state) => state.visit(cursor),
 			Self::Expansion(state) => state.visit(cursor),
 			Self::ExpansionWord(state) => state.visit(cursor),
@@ -192,8 +228,9 @@ impl State {
 			Self::UnquotedWord(state) => state.visit(cursor),
 			Self::SingleQuotedWord(state) => state.visit(cursor),
 			Self::DoubleQuotedWord(state) => state.visit(cursor),
-			Self::Dollar(state) => state.visit(cursor, interner),
-			Self::QuotedDollar(state) => state.visit(cursor, interner),
+			Self::Dollar(state) => state.visit(cursor, interner, config),
+			Self::QuotedDollar(state) => state.visit(cursor, interner, config),
+			Self::Splat(state) => state.visit(cursor, interner, config),
 			Self::CommandSymbol(state) => state.visit(cursor),
 		}
 	}
@@ -206,12 +243,193 @@ pub(super) struct Automata<'a, 'b> {
 	state: State,
 	cursor: Cursor<'a>,
 	interner: &'b mut SymbolInterner,
+	config: Config,
+	/// The enabled warnings reported so far.
+	warnings: Vec<Warning>,
+	/// An output to be produced before resuming, as when splitting a disabled opener.
+	pending: Option<Output>,
+	/// The string literals with an open embedded expression, innermost last. Each entry
+	/// indicates whether the literal is triple quoted.
+	interpolations: Vec<bool>,
+	/// Whether the input starts with a shebang line.
+	shebang: bool,
+	/// Whether the next token starts a command in a command block, which may be labeled.
+	command_start: bool,
+	/// The number of transitions taken so far.
+	transitions: usize,
 }
 
 
 impl<'a, 'b> Automata<'a, 'b> {
-	pub fn new(cursor: Cursor<'a>, interner: &'b mut SymbolInterner) -> Self {
-		Self { state: State::default(), cursor, interner }
+	pub fn new(mut cursor: Cursor<'a>, interner: &'b mut SymbolInterner, config: Config) -> Self {
+		let shebang = Self::skip_shebang(&mut cursor);
+
+		Self {
+			state: State::default(),
+			cursor,
+			interner,
+			config,
+			warnings: Vec::new(),
+			pending: None,
+			interpolations: Vec::new(),
+			shebang,
+			command_start: false,
+			transitions: 0,
+		}
+	}
+
+
+	pub fn warnings(&self) -> &[Warning] {
+		&self.warnings
+	}
+
+
+	pub fn shebang(&self) -> bool {
+		self.shebang
+	}
+
+
+	pub fn transitions(&self) -> usize {
+		self.transitions
+	}
+
+
+	/// Skip a `#!` line at the very start of the input, up to the newline. Unlike regular
+	/// comments, it is never produced as a token.
+	fn skip_shebang(cursor: &mut Cursor) -> bool {
+		if cursor.offset() != 0 || !cursor.slice().starts_with(b"#!") {
+			return false;
+		}
+
+		while !matches!(cursor.peek(), None | Some(b'\n')) {
+			cursor.step();
+		}
+
+		true
+	}
+
+
+	/// Split a disabled command block opener into an unexpected prefix, and a synchronous
+	/// command block opener, which is left pending.
+	fn split_disabled_opener(&mut self, output: Output) -> Output {
+		match output {
+			Ok(Token { kind, pos, end })
+				if kind != TokenKind::Command
+					&& CommandBlockKind::from_token(&kind)
+						.is_some_and(|block| self.config.disabled_command_blocks.contains(&block)) =>
+			{
+				let prefix = self.cursor.slice()[pos.offset];
+				let brace_pos = SourcePos { column: pos.column + 1, offset: pos.offset + 1, ..pos };
+
+				self.pending = Some(Ok(Token { kind: TokenKind::Command, pos: brace_pos, end }));
+
+				Err(Error::unexpected(prefix, pos))
+			}
+
+			output => output,
+		}
+	}
+
+
+	/// Resume the enclosing string literal when the closing brace of an embedded expression
+	/// is reached. Braces are not otherwise valid in the root state, as command blocks are
+	/// lexed by their own states.
+	fn resume_interpolation(&self, state: State) -> State {
+		match (state, self.cursor.peek(), self.interpolations.last()) {
+			(State::Root(_), Some(b'}'), Some(&triple)) => {
+				StringLiteral::resume_at(&self.cursor, triple).into()
+			}
+
+			(state, _, _) => state,
+		}
+	}
+
+
+	/// Track the string literals with open embedded expressions.
+	fn track_interpolation(&mut self, output: &Output) {
+		match output {
+			Ok(Token { kind: TokenKind::StringHead(_), pos, .. }) => {
+				// The head token is positioned at the opening quotes.
+				let triple = self.cursor.slice()[pos.offset ..].starts_with(b"\"\"\"");
+				self.interpolations.push(triple);
+			}
+
+			Ok(Token { kind: TokenKind::StringTail(_), .. }) => {
+				self.interpolations.pop();
+			}
+
+			_ => (),
+		}
+	}
+
+
+	/// Convert the first argument of a command into a label if it is an unquoted identifier
+	/// followed by a colon, as in `{ build: make all }`. Labels are only recognized at the
+	/// start of a command block, after a semicolon or a pipe, and after the keywords of
+	/// conditional commands.
+	fn label(&mut self, output: &mut Output) {
+		let kind = match output {
+			Ok(Token { kind: TokenKind::Comment(_), .. }) => return,
+			Ok(Token { kind, .. }) => kind,
+			Err(_) => {
+				self.command_start = false;
+				return;
+			}
+		};
+
+		let command_start = std::mem::replace(
+			&mut self.command_start,
+			kind.is_command_block_starter()
+				|| matches!(kind, TokenKind::Semicolon | TokenKind::Pipe),
+		);
+
+		if !command_start {
+			return;
+		}
+
+		let word = match &*kind {
+			TokenKind::Argument(parts) => match parts.as_ref() {
+				[ArgPart::Unquoted(ArgUnit::Literal(word))] => word,
+				_ => return,
+			},
+			_ => return,
+		};
+
+		let is_keyword = [Keyword::If, Keyword::Then, Keyword::Else]
+			.iter()
+			.any(|keyword| word.as_ref() == keyword.as_str().as_bytes());
+
+		if is_keyword {
+			self.command_start = true;
+			return;
+		}
+
+		let is_identifier = |name: &[u8]| match name.split_first() {
+			Some((first, rest)) => first.is_word_start() && rest.iter().all(IsWord::is_word),
+			None => false,
+		};
+
+		if let Some(label) = word.strip_suffix(b":").filter(|name| is_identifier(name)) {
+			*kind = TokenKind::Label(self.interner.get_or_intern(label));
+		}
+	}
+
+
+	/// Warn if the output is an identifier which is a reserved word.
+	fn check_reserved(&mut self, output: &Output) {
+		if self.config.reserved_words.is_empty() {
+			return;
+		}
+
+		if let Ok(Token { kind: TokenKind::Identifier(symbol), pos, .. }) = output {
+			let word = self.interner
+				.resolve(*symbol)
+				.filter(|word| self.config.reserved_words.contains(*word));
+
+			if let Some(word) = word {
+				self.warnings.push(Warning::reserved_word(word, *pos));
+			}
+		}
 	}
 }
 
@@ -220,11 +438,17 @@ impl<'a, 'b> Iterator for Automata<'a, 'b> {
 	type Item = Output;
 
 	fn next(&mut self) -> Option<Output> {
+		if let Some(output) = self.pending.take() {
+			return Some(output);
+		}
+
 		loop {
 			// We must temporarily take the state so that we can consume it.
 			let state = std::mem::take(&mut self.state);
+			let state = self.resume_interpolation(state);
 
-			let transition = state.visit(&self.cursor, self.interner);
+			let transition = state.visit(&self.cursor, self.interner, &self.config);
+			self.transitions += 1;
 
 			self.state = transition.state;
 
@@ -233,8 +457,28 @@ impl<'a, 'b> Iterator for Automata<'a, 'b> {
 
 			transition.step.apply(&mut self.cursor);
 
-			if let Some(output) = transition.output {
-				return Some(output);
+			if let Some(warning) = transition.warning {
+				if self.config.is_enabled(&warning.warning) {
+					self.warnings.push(warning);
+				}
+			}
+
+			let output = transition.output.filter(
+				|output| self.config.preserve_comments
+					|| !matches!(output, Ok(Token { kind: TokenKind::Comment(_), .. }))
+			);
+
+			if let Some(mut output) = output {
+				// Tokens end where the cursor stops after producing them, as their characters
+				// have been consumed, and the following ones haven't.
+				if let Ok(token) = &mut output {
+					token.end = self.cursor.pos();
+				}
+
+				self.label(&mut output);
+				self.check_reserved(&output);
+				self.track_interpolation(&output);
+				return Some(self.split_disabled_opener(output));
 			}
 
 			if eof {
@@ -1,4 +1,6 @@
 use super::{
+	word::IsWord,
+	Checkpoint,
 	Cursor,
 	Error,
 	ErrorKind,
@@ -18,6 +20,12 @@ pub(super) struct NumberLiteral {
 	start_offset: usize,
 	consumed_decimal: Option<bool>,
 	consumed_exponent: Option<bool>,
+	/// The position of the decimal dot, to rollback in case it's not part of the number.
+	dot: Option<Checkpoint>,
+	/// The radix of an integer literal, which is changed by a prefix such as `0x`.
+	radix: u32,
+	/// The position of the last consumed character, if it was a digit separator.
+	underscore: Option<SourcePos>,
 	pos: SourcePos,
 }
 
@@ -28,15 +36,79 @@ impl NumberLiteral {
 			start_offset: cursor.offset(),
 			consumed_decimal: None,
 			consumed_exponent: None,
+			dot: None,
+			radix: 10,
+			underscore: None,
 			pos: cursor.pos(),
 		}
 	}
 
 
+	/// Start a number literal after a leading dot, as in `.5`, given the dot's position.
+	/// The dot must be followed by a digit.
+	pub fn after_dot(pos: SourcePos) -> Self {
+		Self {
+			start_offset: pos.offset,
+			consumed_decimal: Some(false),
+			consumed_exponent: None,
+			dot: None,
+			radix: 10,
+			underscore: None,
+			pos,
+		}
+	}
+
+
 	pub fn visit(mut self, cursor: &Cursor) -> Transition {
 		let error = |error| Transition::error(Root, Error { error, pos: self.pos });
 
 		match (&self, cursor.peek()) {
+			// A leading zero may be followed by a radix prefix, as in `0xFF`, `0o755` or `0b10`.
+			(&Self { radix: 10, consumed_decimal: None, .. }, Some(prefix @ (b'x' | b'o' | b'b')))
+				if self.is_leading_zero(cursor) => {
+					self.radix = match prefix {
+						b'x' => 16,
+						b'o' => 8,
+						_ => 2,
+					};
+
+					Transition::step(self)
+				}
+
+			// Digit separators follow the same rules in all radices. A separator must be
+			// followed by a digit, as in `1_000` or `0xFF_FF`.
+			(&Self { underscore: Some(pos), .. }, value)
+				if !matches!(value, Some(c) if self.is_digit(c)) => {
+					// For consecutive separators, report the second one.
+					let pos = if value == Some(b'_') { cursor.pos() } else { pos };
+					Transition::error(Root, Error::unexpected(b'_', pos))
+				}
+
+			// A digit separator must also be preceded by a digit, and not by a radix prefix,
+			// a dot or an exponent, as in `0x_FF`, `1._5` or `1e_5`.
+			(_, Some(b'_')) => {
+				if self.follows_digit(cursor) {
+					self.underscore = Some(cursor.pos());
+					Transition::step(self)
+				} else {
+					Transition::error(Root, Error::unexpected(b'_', cursor.pos()))
+				}
+			}
+
+			// Consume digits of the radix.
+			(&Self { radix, .. }, Some(value)) if radix != 10 && self.is_digit(value) => self.digit(),
+
+			// Other word characters are not allowed right after the digits, as in `0b12`.
+			(&Self { radix, .. }, Some(value)) if radix != 10 && value.is_word() => {
+				error(ErrorKind::Unexpected(value))
+			}
+
+			// Stop and produce. Prefixed literals are always integers.
+			(&Self { radix, .. }, _) if radix != 10 => match self.parse(cursor, cursor.offset()) {
+				Ok(token) => Transition::resume_produce(Root, token),
+				Err(error) => Transition::error(Root, error),
+			},
+
 			// There must be up to one dot, and it must precede the exponent.
 			(
 				&Self {
@@ -45,40 +117,50 @@ impl NumberLiteral {
 				Some(b'.'),
 			) => {
 				self.consumed_decimal = Some(false);
+				self.dot = Some(cursor.checkpoint());
 				Transition::step(self)
 			}
 
-			// Exponent may be present regardless of dot.
-			(&Self { consumed_exponent: None, .. }, Some(c)) if c == b'e' || c == b'E' => {
+			// A trailing dot followed by another dot or a word is not a decimal dot, as in
+			// `5..10` or `5.foo`. In such case, produce an integer and lex the dot again.
+			(&Self { consumed_decimal: Some(false), dot: Some(dot), .. }, Some(value))
+				if value == b'.' || value.is_word_start() => {
+					self.consumed_decimal = None;
+
+					// The dot is the last consumed character.
+					match self.parse(cursor, cursor.offset() - 1) {
+						Ok(token) => Transition::rollback_produce(dot, Root, token),
+						Err(error) => Transition::error(Root, error),
+					}
+				}
+
+			// Exponent may be present regardless of dot, but not right after it.
+			(&Self { consumed_exponent: None, consumed_decimal, .. }, Some(c))
+				if (c == b'e' || c == b'E') && consumed_decimal != Some(false) => {
 				self.consumed_exponent = Some(false);
 				Transition::step(self)
 			}
 
+			// The exponent may be signed, as in `2.5e-3`.
+			(&Self { consumed_exponent: Some(false), .. }, Some(b'+' | b'-'))
+				if matches!(self.last_consumed(cursor), Some(b'e' | b'E')) => Transition::step(self),
+
 			// Consume digits.
-			(_, Some(value)) if value.is_ascii_digit() => {
-				// If a dot or an exponent preceded, then set the according flag.
-				if self.consumed_decimal == Some(false) {
-					self.consumed_decimal = Some(true);
-				}
-				if self.consumed_exponent == Some(false) {
-					self.consumed_exponent = Some(true);
-				}
+			(_, Some(value)) if self.is_digit(value) => self.digit(),
 
-				Transition::step(self)
-			}
+			// An exponent must be followed by a digit.
+			(&Self { consumed_exponent: Some(false), .. }, value) => {
+				let error = match value {
+					Some(value) => Error::unexpected(value, cursor.pos()),
+					None => Error::unexpected_eof(cursor.pos()),
+				};
 
-			// A dot or an exponent must be followed by a digit.
-			(&Self { consumed_decimal: Some(false), .. }, value)
-			| (&Self { consumed_exponent: Some(false), .. }, value) => {
-				if let Some(value) = value {
-					error(ErrorKind::Unexpected(value))
-				} else {
-					error(ErrorKind::UnexpectedEof)
-				}
+				Transition::error(Root, error)
 			}
 
-			// Stop and produce if a non-digit is found, including EOF.
-			(_, _) => match self.parse(cursor) {
+			// Stop and produce if a non-digit is found, including EOF. A trailing dot is
+			// allowed, as in `5.`.
+			(_, _) => match self.parse(cursor, cursor.offset()) {
 				Ok(token) => Transition::resume_produce(Root, token),
 				Err(error) => Transition::error(Root, error),
 			},
@@ -86,17 +168,26 @@ impl NumberLiteral {
 	}
 
 
-	/// Parse the consumed characters.
-	fn parse(&self, cursor: &Cursor) -> Result<Token, Error> {
-		let number = &cursor.slice()[self.start_offset .. cursor.offset()];
+	/// Parse the consumed characters, up to the given offset.
+	fn parse(&self, cursor: &Cursor, end_offset: usize) -> Result<Token, Error> {
+		let number = &cursor.slice()[self.start_offset .. end_offset];
 
-		let literal = |literal| Ok(Token { kind: TokenKind::Literal(literal), pos: self.pos });
+		let literal = |literal| Ok(Token::new(TokenKind::Literal(literal), self.pos));
 
 		// There is no method in std to parse a number from a byte array.
 		let number_str = std::str::from_utf8(number)
 			.expect("number literals should be valid ascii, which should be valid utf8");
 
-		if self.is_float() {
+		// Digit separators are only meant for readability.
+		let number_str: String = number_str.chars().filter(|&c| c != '_').collect();
+
+		if self.radix != 10 {
+			// Skip the prefix. An empty digit run or an overflow are invalid.
+			match i64::from_str_radix(&number_str[2..], self.radix) {
+				Ok(int) => literal(Literal::Int(int)),
+				Err(_) => Err(Error::invalid_number(number, self.pos)),
+			}
+		} else if self.is_float() {
 			match number_str.parse() {
 				Ok(float) => literal(Literal::Float(float)),
 				Err(_) => Err(Error::invalid_number(number, self.pos)),
@@ -110,6 +201,48 @@ impl NumberLiteral {
 	}
 
 
+	/// Check if the only consumed character is a zero, which may precede a radix prefix.
+	fn is_leading_zero(&self, cursor: &Cursor) -> bool {
+		cursor.offset() == self.start_offset + 1 && cursor.slice()[self.start_offset] == b'0'
+	}
+
+
+	/// The last consumed character of the literal, if any.
+	fn last_consumed(&self, cursor: &Cursor) -> Option<u8> {
+		if cursor.offset() > self.start_offset {
+			Some(cursor.slice()[cursor.offset() - 1])
+		} else {
+			None
+		}
+	}
+
+
+	/// Check if the given character is a digit in the radix of the literal.
+	fn is_digit(&self, value: u8) -> bool {
+		(value as char).is_digit(self.radix)
+	}
+
+
+	/// Consume a digit, which completes a preceding dot, exponent or digit separator.
+	fn digit(mut self) -> Transition {
+		if self.consumed_decimal == Some(false) {
+			self.consumed_decimal = Some(true);
+		}
+		if self.consumed_exponent == Some(false) {
+			self.consumed_exponent = Some(true);
+		}
+		self.underscore = None;
+
+		Transition::step(self)
+	}
+
+
+	/// Check if the last consumed character is a digit in the radix of the literal.
+	fn follows_digit(&self, cursor: &Cursor) -> bool {
+		matches!(self.last_consumed(cursor), Some(c) if self.is_digit(c))
+	}
+
+
 	/// Check if the consumed characters constitue a float.
 	fn is_float(&self) -> bool {
 		self.consumed_decimal.is_some() || self.consumed_exponent.is_some()
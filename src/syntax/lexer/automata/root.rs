@@ -1,6 +1,7 @@
 use super::{
 	symbol::SymbolChar,
 	word::IsWord,
+	BlockComment,
 	ByteLiteral,
 	Command,
 	Comment,
@@ -29,7 +30,10 @@ impl Root {
 			Some(c) if c.is_ascii_whitespace() => Transition::step(self),
 
 			// Comments.
-			Some(b'#') => Transition::step(Comment::from(self)),
+			Some(b'#') if BlockComment::<Self>::starts(cursor) => {
+				Transition::step(BlockComment::at(cursor, self))
+			}
+			Some(b'#') => Transition::step(Comment::at(cursor, self)),
 
 			// String literals.
 			Some(b'"') => Transition::step(StringLiteral::at(cursor)),
@@ -49,11 +53,11 @@ impl Root {
 
 				SymbolChar::Single(TokenKind::Command) => Transition::produce(
 					Command,
-					Token { kind: TokenKind::Command, pos: cursor.pos() },
+					Token::new(TokenKind::Command, cursor.pos()),
 				),
 
 				SymbolChar::Single(token) => {
-					Transition::produce(self, Token { kind: token, pos: cursor.pos() })
+					Transition::produce(self, Token::new(token, cursor.pos()))
 				}
 
 				SymbolChar::Double { first } => Transition::step(Symbol::from_first(first, cursor)),
@@ -1,4 +1,16 @@
-use super::{Cursor, Error, Literal, Root, SourcePos, State, Token, TokenKind, Transition};
+use super::{
+	Config,
+	Cursor,
+	Error,
+	Literal,
+	Root,
+	SourcePos,
+	State,
+	Token,
+	TokenKind,
+	Transition,
+	Warning,
+};
 
 
 /// The state for lexing byte literals.
@@ -19,7 +31,7 @@ impl ByteLiteral {
 	}
 
 
-	pub fn visit(mut self, cursor: &Cursor) -> Transition {
+	pub fn visit(mut self, cursor: &Cursor, config: &Config) -> Transition {
 		match (&self, cursor.peek()) {
 			// EOF while scanning a literal is always an error.
 			(_, None) => Transition::error(Root, Error::unexpected_eof(cursor.pos())),
@@ -27,10 +39,7 @@ impl ByteLiteral {
 			// Closing quote.
 			(&Self { value: Some(c), .. }, Some(b'\'')) => Transition::produce(
 				Root,
-				Token {
-					kind: TokenKind::Literal(Literal::Byte(c)),
-					pos: self.pos,
-				},
+				Token::new(TokenKind::Literal(Literal::Byte(c)), self.pos),
 			),
 
 			// If a value has already been scanned (including incorrect escape sequences). There
@@ -46,6 +55,9 @@ impl ByteLiteral {
 				if let Some(c) = validate_escape(value) {
 					self.value = Some(c);
 					Transition::step(self)
+				} else if config.lenient_escapes {
+					self.value = Some(value);
+					Transition::step(self)
 				} else {
 					// Use a placeholder to produce a valid literal after reporting the error. This
 					// won't get to be actually used, because the program won't be interpreted after
@@ -84,13 +96,41 @@ impl From<ByteLiteral> for State {
 }
 
 
-/// The state for lexing string literals.
+/// A multi character escape sequence in a string literal.
+#[derive(Debug)]
+enum Sequence {
+	/// `\xHH`: a byte given by two hex digits.
+	Hex { value: u8, digits: u8 },
+	/// `\u{H...}`: a unicode scalar value given by up to six hex digits, which is UTF-8
+	/// encoded. The digits are scanned once the brace is opened.
+	Unicode { value: u32, digits: u8, opened: bool },
+}
+
+
+/// The state for lexing string literals. Literals delimited by triple quotes (`"""`) are
+/// closed only by another triple quote, which allows embedding quotes. Both forms may span
+/// multiple lines, and process escape sequences.
+/// Expressions may be embedded with `${ expr }`, in which case the literal is split in
+/// parts around the expressions. The automata lexes the expressions from the root state,
+/// and resumes the literal on the closing brace.
 #[derive(Debug)]
 pub(super) struct StringLiteral {
 	/// The parsed bytes, if any.
 	value: Vec<u8>,
 	/// The position of the current escape sequence, if any.
 	escaping: Option<(usize, SourcePos)>,
+	/// The multi character escape sequence being scanned, if any.
+	sequence: Option<Sequence>,
+	/// Whether the literal is delimited by triple quotes.
+	triple: bool,
+	/// Whether the literal is resumed after an embedded expression.
+	resumed: bool,
+	/// Whether an embedded expression is being opened.
+	interpolating: bool,
+	/// The number of opening delimiter characters yet to be consumed.
+	opening: u8,
+	/// The number of consecutive quotes consumed, which may close a triple quoted literal.
+	closing: u8,
 	/// The position of the literal.
 	pos: SourcePos,
 }
@@ -98,19 +138,158 @@ pub(super) struct StringLiteral {
 
 impl StringLiteral {
 	pub fn at(cursor: &Cursor) -> Self {
+		let triple = cursor.slice()[cursor.offset() ..].starts_with(b"\"\"\"");
+
 		Self {
 			value: Vec::with_capacity(8), // We expect most literals to not be empty.
 			escaping: None,
+			sequence: None,
+			triple,
+			resumed: false,
+			interpolating: false,
+			opening: if triple { 2 } else { 0 },
+			closing: 0,
 			pos: cursor.pos(),
 		}
 	}
 
 
-	pub fn visit(mut self, cursor: &Cursor) -> Transition {
+	/// Resume the literal at the closing brace of an embedded expression.
+	pub fn resume_at(cursor: &Cursor, triple: bool) -> Self {
+		Self {
+			value: Vec::with_capacity(8),
+			escaping: None,
+			sequence: None,
+			triple,
+			resumed: true,
+			interpolating: false,
+			opening: 1,
+			closing: 0,
+			pos: cursor.pos(),
+		}
+	}
+
+
+	/// The token for the scanned part of the literal, given whether it is followed by an
+	/// embedded expression.
+	fn token(self, interpolated: bool) -> Token {
+		let value = self.value.into_boxed_slice();
+
+		let kind = match (self.resumed, interpolated) {
+			(false, false) => TokenKind::Literal(Literal::String(value)),
+			(false, true) => TokenKind::StringHead(value),
+			(true, true) => TokenKind::StringMiddle(value),
+			(true, false) => TokenKind::StringTail(value),
+		};
+
+		Token::new(kind, self.pos)
+	}
+
+
+	/// Scan a character of a multi character escape sequence.
+	fn visit_sequence(mut self, sequence: Sequence, cursor: &Cursor) -> Transition {
+		let (offset, pos) = self.escaping.expect("sequence outside escape");
+		let digit = cursor.peek().and_then(|c| (c as char).to_digit(16));
+
+		let sequence = match (sequence, cursor.peek(), digit) {
+			(Sequence::Hex { value, digits: 1 }, _, Some(digit)) => {
+				self.value.push(value << 4 | digit as u8);
+				None
+			}
+
+			(Sequence::Hex { value, digits }, _, Some(digit)) => {
+				Some(Sequence::Hex { value: value << 4 | digit as u8, digits: digits + 1 })
+			}
+
+			(Sequence::Unicode { value, digits, opened: false }, Some(b'{'), _) => {
+				Some(Sequence::Unicode { value, digits, opened: true })
+			}
+
+			(Sequence::Unicode { value, digits, opened: true }, _, Some(digit)) if digits < 6 => {
+				Some(Sequence::Unicode { value: value << 4 | digit, digits: digits + 1, opened: true })
+			}
+
+			(Sequence::Unicode { value, digits, opened: true }, Some(b'}'), _) if digits > 0 => {
+				match char::from_u32(value) {
+					Some(c) => {
+						let mut buffer = [0; 4];
+						self.value.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+						None
+					}
+
+					// Out of range, or a surrogate.
+					None => {
+						self.escaping = None;
+						let escape_sequence = &cursor.slice()[offset ..= cursor.offset()];
+						return Transition::error(self, Error::invalid_escape_sequence(escape_sequence, pos));
+					}
+				}
+			}
+
+			// Invalid character in the sequence, which is then scanned as part of the literal.
+			_ => {
+				self.escaping = None;
+				let escape_sequence = &cursor.slice()[offset ..= cursor.offset()];
+				return Transition::resume_error(self, Error::invalid_escape_sequence(escape_sequence, pos));
+			}
+		};
+
+		if sequence.is_none() {
+			self.escaping = None;
+		}
+
+		self.sequence = sequence;
+		Transition::step(self)
+	}
+
+
+	pub fn visit(mut self, cursor: &Cursor, config: &Config) -> Transition {
+		if let Some(sequence) = self.sequence.take() {
+			if !cursor.is_eof() {
+				return self.visit_sequence(sequence, cursor);
+			}
+		}
+
 		match (&self, cursor.peek()) {
+			// EOF in an escape sequence.
+			(&Self { escaping: Some((offset, pos)), .. }, None) => {
+				let escape_sequence = &cursor.slice()[offset ..];
+				Transition::error(Root, Error::invalid_escape_sequence(escape_sequence, pos))
+			}
+
 			// EOF while scanning a literal is always an error.
 			(_, None) => Transition::error(Root, Error::unexpected_eof(cursor.pos())),
 
+			// Remaining opening delimiter.
+			(&Self { opening: 1 ..= 2, .. }, Some(_)) => {
+				self.opening -= 1;
+				Transition::step(self)
+			}
+
+			// Quotes that don't make up a closing triple quote are part of the literal.
+			(&Self { closing: 1 ..= 2, .. }, Some(c)) if c != b'\"' => {
+				self.value.resize(self.value.len() + self.closing as usize, b'"');
+				self.closing = 0;
+				Transition::resume(self)
+			}
+
+			// Escaped newline, which continues the string without inserting anything.
+			(&Self { escaping: Some(_), .. }, Some(b'\n')) => {
+				self.escaping = None;
+				Transition::step(self)
+			}
+
+			// Begin of a multi character escape sequence.
+			(&Self { escaping: Some(_), .. }, Some(b'x')) => {
+				self.sequence = Some(Sequence::Hex { value: 0, digits: 0 });
+				Transition::step(self)
+			}
+
+			(&Self { escaping: Some(_), .. }, Some(b'u')) => {
+				self.sequence = Some(Sequence::Unicode { value: 0, digits: 0, opened: false });
+				Transition::step(self)
+			}
+
 			// Escaped character.
 			(&Self { escaping: Some((offset, pos)), .. }, Some(value)) => {
 				self.escaping = None;
@@ -118,6 +297,9 @@ impl StringLiteral {
 				if let Some(c) = validate_escape(value) {
 					self.value.push(c);
 					Transition::step(self)
+				} else if config.lenient_escapes {
+					self.value.push(value);
+					Transition::step(self)
 				} else {
 					let escape_sequence = &cursor.slice()[offset ..= cursor.offset()];
 					Transition::error(self, Error::invalid_escape_sequence(escape_sequence, pos))
@@ -130,14 +312,30 @@ impl StringLiteral {
 				Transition::step(self)
 			}
 
+			// Brace of an embedded expression, whose tokens are produced from the root state.
+			(&Self { interpolating: true, .. }, Some(_)) => Transition::produce(Root, self.token(true)),
+
+			// Begin of an embedded expression.
+			(_, Some(b'$')) if cursor.slice()[cursor.offset() ..].starts_with(b"${") => {
+				self.interpolating = true;
+				Transition::step(self)
+			}
+
+			// Quote in a triple quoted literal, which may be part of the closing delimiter.
+			(&Self { triple: true, closing, .. }, Some(b'"')) if closing < 2 => {
+				self.closing += 1;
+				Transition::step(self)
+			}
+
 			// Closing quote.
-			(_, Some(b'\"')) => Transition::produce(
-				Root,
-				Token {
-					kind: TokenKind::Literal(Literal::String(self.value.into_boxed_slice())),
-					pos: self.pos,
-				},
-			),
+			(_, Some(b'\"')) => Transition::produce(Root, self.token(false)),
+
+			// Raw tab, which may be accidental.
+			(_, Some(b'\t')) => {
+				self.value.push(b'\t');
+				Transition::step(self)
+					.with_warning(Warning::raw_tab(cursor.pos()))
+			}
 
 			// Ordinary character.
 			(_, Some(value)) => {
@@ -156,6 +354,110 @@ impl From<StringLiteral> for State {
 }
 
 
+/// The state for lexing raw string literals: `r"..."` or `r#"..."#`. Backslashes have no
+/// special meaning, and bytes are copied verbatim. The closing quote must be followed by
+/// as many hashes as the opening one is preceded by, which allows embedding quotes.
+#[derive(Debug)]
+pub(super) struct RawStringLiteral {
+	/// The parsed bytes, if any.
+	value: Vec<u8>,
+	/// The number of hashes in the delimiter.
+	hashes: usize,
+	/// Whether the opening quote has been consumed.
+	opened: bool,
+	/// The number of hashes following a candidate closing quote, if any.
+	closing: Option<usize>,
+	/// The position of the literal.
+	pos: SourcePos,
+}
+
+
+impl RawStringLiteral {
+	/// Start a raw string literal after the `r` prefix at the given position.
+	pub fn at(pos: SourcePos) -> Self {
+		Self {
+			value: Vec::with_capacity(8), // We expect most literals to not be empty.
+			hashes: 0,
+			opened: false,
+			closing: None,
+			pos,
+		}
+	}
+
+
+	/// Check if the input following the `r` prefix starts a raw string literal.
+	pub fn starts(cursor: &Cursor) -> bool {
+		cursor.slice()[cursor.offset() ..]
+			.iter()
+			.find(|&&c| c != b'#')
+			== Some(&b'"')
+	}
+
+
+	pub fn visit(mut self, cursor: &Cursor) -> Transition {
+		let produce = |value: Vec<u8>, pos| Transition::produce(
+			Root,
+			Token::new(TokenKind::Literal(Literal::String(value.into_boxed_slice())), pos),
+		);
+
+		match (&self, cursor.peek()) {
+			// EOF while scanning a literal is always an error.
+			(_, None) => Transition::error(Root, Error::unexpected_eof(cursor.pos())),
+
+			// Opening delimiter.
+			(&Self { opened: false, .. }, Some(b'#')) => {
+				self.hashes += 1;
+				Transition::step(self)
+			}
+
+			(&Self { opened: false, .. }, Some(_)) => {
+				self.opened = true;
+				Transition::step(self)
+			}
+
+			// Closing quote.
+			(&Self { closing: None, hashes: 0, .. }, Some(b'"')) => produce(self.value, self.pos),
+
+			(&Self { closing: None, .. }, Some(b'"')) => {
+				self.closing = Some(0);
+				Transition::step(self)
+			}
+
+			// Closing hashes.
+			(&Self { closing: Some(count), hashes, .. }, Some(b'#')) => {
+				if count + 1 == hashes {
+					produce(self.value, self.pos)
+				} else {
+					self.closing = Some(count + 1);
+					Transition::step(self)
+				}
+			}
+
+			// Not enough hashes, so the candidate closing delimiter is part of the literal.
+			(&Self { closing: Some(count), .. }, Some(_)) => {
+				self.value.push(b'"');
+				self.value.resize(self.value.len() + count, b'#');
+				self.closing = None;
+				Transition::resume(self)
+			}
+
+			// Ordinary character, including backslashes.
+			(_, Some(value)) => {
+				self.value.push(value);
+				Transition::step(self)
+			}
+		}
+	}
+}
+
+
+impl From<RawStringLiteral> for State {
+	fn from(state: RawStringLiteral) -> State {
+		Self::RawStringLiteral(state)
+	}
+}
+
+
 /// Check if a escape sequence is valid, producing the correspondent byte if so.
 fn validate_escape(sequence: u8) -> Option<u8> {
 	match sequence {
@@ -163,8 +465,10 @@ fn validate_escape(sequence: u8) -> Option<u8> {
 		b'\'' => Some(b'\''),
 		b'n' => Some(b'\n'),
 		b't' => Some(b'\t'),
+		b'r' => Some(b'\r'),
 		b'0' => Some(b'\0'),
 		b'\\' => Some(b'\\'),
+		b'$' => Some(b'$'),
 		_ => None,
 	}
 }
@@ -3,6 +3,7 @@ use super::{
 	CommandOperator,
 	Cursor,
 	Error,
+	NumberLiteral,
 	Operator,
 	Root,
 	SourcePos,
@@ -29,7 +30,7 @@ impl Symbol {
 
 	pub fn visit(self, cursor: &Cursor) -> Transition {
 		let unexpected = |input| Transition::resume_error(Root, Error::unexpected(input, self.pos));
-		let token = |token| Token { kind: token, pos: self.pos };
+		let token = |token| Token::new(token, self.pos);
 		let operator = |op| token(TokenKind::Operator(op));
 
 		let skip_produce = |output| Transition::resume_produce(Root, output);
@@ -41,16 +42,39 @@ impl Symbol {
 			(b'<', Some(b'=')) => Transition::produce(Root, operator(Operator::LowerEquals)),
 			(b'<', _) => skip_produce(operator(Operator::Lower)),
 
-			(b'+', Some(b'+')) => Transition::produce(Root, operator(Operator::Concat)),
+			(b'+', Some(b'+')) => Transition::step(Concat { pos: self.pos }),
+			(b'+', Some(b'=')) => Transition::produce(Root, operator(Operator::PlusAssign)),
 			(b'+', _) => skip_produce(operator(Operator::Plus)),
 
+			(b'-', Some(b'=')) => Transition::produce(Root, operator(Operator::MinusAssign)),
+			(b'-', _) => skip_produce(operator(Operator::Minus)),
+
+			(b'*', Some(b'=')) => Transition::produce(Root, operator(Operator::TimesAssign)),
+			(b'*', _) => skip_produce(operator(Operator::Times)),
+
+			(b'/', Some(b'=')) => Transition::produce(Root, operator(Operator::DivAssign)),
+			(b'/', _) => skip_produce(operator(Operator::Div)),
+
+			(b'%', Some(b'=')) => Transition::produce(Root, operator(Operator::ModAssign)),
+			(b'%', _) => skip_produce(operator(Operator::Mod)),
+
 			(b'=', Some(b'=')) => Transition::produce(Root, operator(Operator::Equals)),
 			(b'=', _) => skip_produce(operator(Operator::Assign)),
 
 			(b'!', Some(b'=')) => Transition::produce(Root, operator(Operator::NotEquals)),
 			(b'!', _) => unexpected(self.first),
 
+			// A leading dot followed by a digit is a number literal, as in `.5`.
+			(b'.', Some(c)) if c.is_ascii_digit() => {
+				Transition::resume(NumberLiteral::after_dot(self.pos))
+			}
+			(b'.', Some(b'.')) => Transition::step(Range { pos: self.pos }),
+			(b'.', _) => skip_produce(operator(Operator::Dot)),
+
 			(b'@', Some(b'[')) => Transition::produce(Root, token(TokenKind::OpenDict)),
+			(b'@', Some(b'{')) => {
+				Transition::produce(Command, token(TokenKind::CombinedCaptureCommand))
+			}
 			(b'@', _) => unexpected(self.first),
 
 			(b'$', Some(b'{')) => Transition::produce(Command, token(TokenKind::CaptureCommand)),
@@ -59,6 +83,9 @@ impl Symbol {
 			(b'&', Some(b'{')) => Transition::produce(Command, token(TokenKind::AsyncCommand)),
 			(b'&', _) => unexpected(self.first),
 
+			(b'|', Some(b'>')) => Transition::produce(Root, operator(Operator::Pipeline)),
+			(b'|', _) => unexpected(self.first),
+
 			// We must have covered all possibilites for the first character. The peeked
 			// character is wildcarded, which will cover everthing including EOF (None).
 			_ => unreachable!("invalid first character in symbol state"),
@@ -74,6 +101,60 @@ impl From<Symbol> for State {
 }
 
 
+/// The state after the concat operator, which may be followed by an equals sign: `++=`.
+#[derive(Debug)]
+pub(super) struct Concat {
+	pos: SourcePos,
+}
+
+
+impl Concat {
+	pub fn visit(self, cursor: &Cursor) -> Transition {
+		let token = |op| Token::new(TokenKind::Operator(op), self.pos);
+
+		match cursor.peek() {
+			Some(b'=') => Transition::produce(Root, token(Operator::ConcatAssign)),
+			_ => Transition::resume_produce(Root, token(Operator::Concat)),
+		}
+	}
+}
+
+
+impl From<Concat> for State {
+	fn from(state: Concat) -> State {
+		Self::Concat(state)
+	}
+}
+
+
+/// The state after the range operator, which may be followed by an equals sign, as in
+/// `..=`, or by a third dot, as in the spread operator `...`.
+#[derive(Debug)]
+pub(super) struct Range {
+	pos: SourcePos,
+}
+
+
+impl Range {
+	pub fn visit(self, cursor: &Cursor) -> Transition {
+		let token = |op| Token::new(TokenKind::Operator(op), self.pos);
+
+		match cursor.peek() {
+			Some(b'=') => Transition::produce(Root, token(Operator::RangeInclusive)),
+			Some(b'.') => Transition::produce(Root, token(Operator::Spread)),
+			_ => Transition::resume_produce(Root, token(Operator::Range)),
+		}
+	}
+}
+
+
+impl From<Range> for State {
+	fn from(state: Range) -> State {
+		Self::Range(state)
+	}
+}
+
+
 /// The state for lexing two-character symbols in command blocks.
 #[derive(Debug)]
 pub(super) struct CommandSymbol {
@@ -89,7 +170,7 @@ impl CommandSymbol {
 
 
 	pub fn visit(self, cursor: &Cursor) -> Transition {
-		let token = |token| Token { kind: token, pos: self.pos };
+		let token = |token| Token::new(token, self.pos);
 		let operator = |op| token(TokenKind::CmdOperator(op));
 
 		let produce = |token| Transition::produce(Command, token);
@@ -144,11 +225,6 @@ impl SymbolChar {
 
 		match first {
 			// Single character.
-			b'-' => operator(Operator::Minus),
-			b'*' => operator(Operator::Times),
-			b'/' => operator(Operator::Div),
-			b'%' => operator(Operator::Mod),
-			b'.' => operator(Operator::Dot),
 			b'?' => operator(Operator::Try),
 			b':' => token(TokenKind::Colon),
 			b',' => token(TokenKind::Comma),
@@ -159,14 +235,20 @@ impl SymbolChar {
 			b'{' => token(TokenKind::Command),
 
 			// Double character.
+			b'.' => double(first),
 			b'>' => double(first),
 			b'<' => double(first),
 			b'+' => double(first),
+			b'-' => double(first),
+			b'*' => double(first),
+			b'/' => double(first),
+			b'%' => double(first),
 			b'=' => double(first),
 			b'!' => double(first),
 			b'@' => double(first),
 			b'$' => double(first),
 			b'&' => double(first),
+			b'|' => double(first),
 
 			// Not a symbol character:
 			_ => SymbolChar::None,
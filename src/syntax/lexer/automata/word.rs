@@ -1,5 +1,7 @@
 use super::{
+	Config,
 	Cursor,
+	RawStringLiteral,
 	Keyword,
 	Literal,
 	Operator,
@@ -11,6 +13,7 @@ use super::{
 	TokenKind,
 	Transition,
 };
+use crate::symbol::Symbol;
 
 
 /// The state for lexing identifiers, keywords and word operators.
@@ -27,7 +30,15 @@ impl Word {
 	}
 
 
-	pub fn visit(self, cursor: &Cursor, interner: &mut SymbolInterner) -> Transition {
+	/// Whether the word is preceded by a dot, which makes it a field name. The range
+	/// operator is not a dot, as in `0..self.len`.
+	fn follows_dot(&self, cursor: &Cursor) -> bool {
+		let preceding = &cursor.slice()[.. self.start_offset];
+		preceding.ends_with(b".") && !preceding.ends_with(b"..")
+	}
+
+
+	pub fn visit(self, cursor: &Cursor, interner: &mut SymbolInterner, config: &Config) -> Transition {
 		// We don't need to check if the first character is a number here, because the Root
 		// state will only transition to this state if that is the case.
 		match cursor.peek() {
@@ -37,9 +48,20 @@ impl Word {
 			// If we visit EOF or a non-identifier character, we should just produce.
 			_ => {
 				let word = &cursor.slice()[self.start_offset .. cursor.offset()];
-				let token = to_token(word, interner);
 
-				Transition::resume_produce(Root, Token { kind: token, pos: self.pos })
+				// The raw string prefix.
+				if word == b"r" && !self.follows_dot(cursor) && RawStringLiteral::starts(cursor) {
+					return Transition::resume(RawStringLiteral::at(self.pos));
+				}
+
+				let token = if self.follows_dot(cursor) {
+					// Field names may be keywords, as in `std.assert`.
+					TokenKind::Identifier(intern_identifier(word, interner, config))
+				} else {
+					to_token(word, interner, config)
+				};
+
+				Transition::resume_produce(Root, Token::new(token, self.pos))
 			}
 		}
 	}
@@ -53,24 +75,13 @@ impl From<Word> for State {
 }
 
 
-pub fn to_token(word: &[u8], interner: &mut SymbolInterner) -> TokenKind {
-	match word {
-		// Keywords:
-		b"let" => TokenKind::Keyword(Keyword::Let),
-		b"if" => TokenKind::Keyword(Keyword::If),
-		b"then" => TokenKind::Keyword(Keyword::Then),
-		b"else" => TokenKind::Keyword(Keyword::Else),
-		b"elseif" => TokenKind::Keyword(Keyword::ElseIf),
-		b"end" => TokenKind::Keyword(Keyword::End),
-		b"for" => TokenKind::Keyword(Keyword::For),
-		b"in" => TokenKind::Keyword(Keyword::In),
-		b"do" => TokenKind::Keyword(Keyword::Do),
-		b"while" => TokenKind::Keyword(Keyword::While),
-		b"function" => TokenKind::Keyword(Keyword::Function),
-		b"return" => TokenKind::Keyword(Keyword::Return),
-		b"break" => TokenKind::Keyword(Keyword::Break),
-		b"self" => TokenKind::Keyword(Keyword::Self_),
+/// Convert a word to a keyword, literal, word operator, or identifier.
+pub fn to_token(word: &[u8], interner: &mut SymbolInterner, config: &Config) -> TokenKind {
+	if let Some(keyword) = Keyword::from_bytes(word) {
+		return TokenKind::Keyword(keyword);
+	}
 
+	match word {
 		// Literals:
 		b"nil" => TokenKind::Literal(Literal::Nil),
 		b"true" => TokenKind::Literal(Literal::True),
@@ -83,13 +94,23 @@ pub fn to_token(word: &[u8], interner: &mut SymbolInterner) -> TokenKind {
 
 		// Identifier:
 		ident => {
-			let symbol = interner.get_or_intern(ident);
+			let symbol = intern_identifier(ident, interner, config);
 			TokenKind::Identifier(symbol)
 		}
 	}
 }
 
 
+/// Intern an identifier, applying the configured normalization beforehand, so that the
+/// original spelling is never interned.
+pub fn intern_identifier(ident: &[u8], interner: &mut SymbolInterner, config: &Config) -> Symbol {
+	match config.normalize_identifier {
+		Some(normalize) => interner.get_or_intern(normalize(ident)),
+		None => interner.get_or_intern(ident),
+	}
+}
+
+
 /// Helper trait for checking if a character is a valid word constituent.
 pub trait IsWord {
 	fn is_word_start(&self) -> bool;
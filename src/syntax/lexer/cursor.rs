@@ -5,7 +5,6 @@ use super::{Source, SourcePos};
 #[derive(Debug, Clone)]
 pub struct Cursor<'a> {
 	input: &'a [u8],
-	offset: usize,
 	pos: SourcePos,
 }
 
@@ -17,17 +16,17 @@ impl<'a> Cursor<'a> {
 
 
 	pub fn offset(&self) -> usize {
-		self.offset
+		self.pos.offset
 	}
 
 
 	pub fn is_eof(&self) -> bool {
-		self.offset == self.input.len()
+		self.pos.offset == self.input.len()
 	}
 
 
 	pub fn peek(&self) -> Option<u8> {
-		self.input.get(self.offset).copied()
+		self.input.get(self.pos.offset).copied()
 	}
 
 
@@ -41,29 +40,25 @@ impl<'a> Cursor<'a> {
 			return;
 		}
 
-		if self.input[self.offset] == b'\n' {
+		if self.input[self.pos.offset] == b'\n' {
 			self.pos.line += 1;
 			self.pos.column = 0;
 		} else {
 			self.pos.column += 1;
 		}
 
-		self.offset += 1;
+		self.pos.offset += 1;
 	}
 
 
 	/// Save a checkpoint in the current position.
 	pub fn checkpoint(&self) -> Checkpoint {
-		Checkpoint {
-			offset: self.offset,
-			pos: self.pos,
-		}
+		Checkpoint { pos: self.pos }
 	}
 
 
 	/// Rollback to the given checkpoint.
 	pub fn rollback(&mut self, checkpoint: Checkpoint) {
-		self.offset = checkpoint.offset;
 		self.pos = checkpoint.pos;
 	}
 }
@@ -73,8 +68,7 @@ impl<'a> From<&'a Source> for Cursor<'a> {
 	fn from(source: &'a Source) -> Self {
 		Self {
 			input: &source.contents,
-			offset: 0,
-			pos: SourcePos { line: 1, column: 0, path: source.path }
+			pos: SourcePos { line: 1, column: 0, offset: 0, path: source.path }
 		}
 	}
 }
@@ -84,6 +78,5 @@ impl<'a> From<&'a Source> for Cursor<'a> {
 /// This can be used to save and restore a position.
 #[derive(Debug, Copy, Clone)]
 pub struct Checkpoint {
-	offset: usize,
 	pos: SourcePos,
 }
@@ -29,6 +29,8 @@ impl std::fmt::Display for ErrorKind {
 			Self::InvalidIdentifier(ident) => {
 				write!(f, "invalid identifier '{}'", String::from_utf8_lossy(ident))?;
 			}
+
+			Self::UnterminatedBlockComment => "unterminated block comment".fmt(f)?,
 		};
 
 		Ok(())
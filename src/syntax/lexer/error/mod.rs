@@ -18,6 +18,9 @@ pub enum ErrorKind {
 	InvalidNumber(Box<[u8]>),
 	/// Invalid identifier, only possible in dollar braces (${}).
 	InvalidIdentifier(Box<[u8]>),
+	/// Block comment not closed before the end of file. The error is positioned at the
+	/// opener.
+	UnterminatedBlockComment,
 }
 
 
@@ -65,4 +68,8 @@ impl Error {
 			pos,
 		}
 	}
+
+	pub fn unterminated_block_comment(pos: SourcePos) -> Self {
+		Self { error: ErrorKind::UnterminatedBlockComment, pos }
+	}
 }
@@ -4,10 +4,13 @@ mod error;
 #[cfg(test)]
 mod tests;
 mod token;
+mod warning;
+
+use std::collections::HashSet;
 
 use crate::symbol;
 use automata::Automata;
-use super::{Source, SourcePos};
+use super::{ast::CommandBlockKind, Source, SourcePos, Span};
 pub use cursor::{Cursor, Checkpoint};
 pub use error::{Error, ErrorKind};
 pub use token::{
@@ -21,6 +24,48 @@ pub use token::{
 	Token,
 	TokenKind
 };
+pub use warning::{Warning, WarningKind};
+
+
+/// A normalization function for identifiers.
+pub type Normalizer = fn(&[u8]) -> Box<[u8]>;
+
+
+/// Lexer configuration. All warnings are opt-in.
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+	/// Warn about raw tab characters in string literals.
+	pub raw_tab_warning: bool,
+	/// Words reserved for future use. Using one of those as an identifier is warned.
+	pub reserved_words: HashSet<Box<[u8]>>,
+	/// Normalization applied to identifiers before interning, such as case folding, so
+	/// that different spellings resolve to the same symbol. Identifiers are kept as is if
+	/// none is supplied.
+	pub normalize_identifier: Option<Normalizer>,
+	/// Kinds of command blocks whose prefixed openers are disabled, such as `&{` for
+	/// asynchronous blocks. Disabled openers are lexed as their constituent characters: an
+	/// unexpected prefix, followed by a synchronous command block opener. Synchronous
+	/// blocks can't be disabled, and are ignored here.
+	pub disabled_command_blocks: Vec<CommandBlockKind>,
+	/// Produce line comments as tokens, for tooling such as documentation generators.
+	/// The parser skips them, attaching trailing comments to parameters and dict entries.
+	pub preserve_comments: bool,
+	/// Interpret unknown escape sequences in string and byte literals as the escaped
+	/// character, such as `\q` as `q`. By default, they are reported as errors. Malformed
+	/// hex and unicode escape sequences are always errors.
+	pub lenient_escapes: bool,
+}
+
+
+impl Config {
+	/// Check whether the given kind of warning is enabled.
+	fn is_enabled(&self, warning: &WarningKind) -> bool {
+		match warning {
+			WarningKind::RawTab => self.raw_tab_warning,
+			WarningKind::ReservedWord(_) => !self.reserved_words.is_empty(),
+		}
+	}
+}
 
 
 /// The lexer for Hush source code.
@@ -30,8 +75,48 @@ pub struct Lexer<'a, 'b>(Automata<'a, 'b>);
 
 impl<'a, 'b> Lexer<'a, 'b> {
 	pub fn new(cursor: Cursor<'a>, interner: &'b mut symbol::Interner) -> Self {
-		Self(Automata::new(cursor, interner))
+		Self::with_config(cursor, interner, Config::default())
+	}
+
+
+	/// Create a new lexer, using the given configuration.
+	pub fn with_config(
+		cursor: Cursor<'a>,
+		interner: &'b mut symbol::Interner,
+		config: Config,
+	) -> Self {
+		Self(Automata::new(cursor, interner, config))
 	}
+
+
+	/// The enabled warnings reported so far.
+	pub fn warnings(&self) -> &[Warning] {
+		self.0.warnings()
+	}
+
+
+	/// Whether the source starts with a shebang line, such as `#!/usr/bin/env hush`.
+	/// The shebang is skipped, and never produced as a comment token.
+	pub fn shebang(&self) -> bool {
+		self.0.shebang()
+	}
+
+
+	/// The number of automaton transitions taken so far, each of which consumes at most one
+	/// byte of input. This measures the work done, independently of the running time.
+	pub fn transitions(&self) -> usize {
+		self.0.transitions()
+	}
+}
+
+
+/// Lex the given source, invoking the callback for each token or error as it is produced.
+/// Unlike collecting the lexer, this doesn't hold all tokens in memory.
+pub fn lex_with<F>(source: &Source, interner: &mut symbol::Interner, callback: F)
+where
+	F: FnMut(Result<Token, Error>),
+{
+	Lexer::new(Cursor::from(source), interner).for_each(callback)
 }
 
 
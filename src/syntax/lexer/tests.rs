@@ -29,6 +29,74 @@ fn test_token_kind_size() {
 }
 
 
+#[test]
+fn test_keyword_lookup() {
+	let mut interner = symbol::Interner::new();
+
+	for keyword in Keyword::ALL {
+		assert_eq!(Keyword::from_str(keyword.as_str()), Some(keyword));
+
+		// The lexer agrees.
+		let path = interner.get_or_intern("<test>");
+		let source = Source { path, contents: keyword.as_str().as_bytes().into() };
+		let tokens: Vec<Result<Token, Error>> = Lexer::new(Cursor::from(&source), &mut interner).collect();
+		assert_matches!(&tokens[..], [token!(TokenKind::Keyword(kw))] if *kw == keyword);
+	}
+
+	assert_eq!(Keyword::from_str("and"), None);
+	assert_eq!(Keyword::from_str("Let"), None);
+	assert_eq!(Keyword::from_str("letter"), None);
+}
+
+
+#[test]
+fn test_keywords_not_interned() {
+	let line = "if x then let y = self else while true do break end return nil end\n";
+	let input = line.repeat(16 * 1024);
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let mut lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.by_ref().collect();
+	assert!(lexer.transitions() <= 2 * input.len());
+
+	// Only the path and the two identifiers are interned.
+	assert_eq!(interner.len(), 3);
+	for keyword in Keyword::ALL {
+		assert_eq!(interner.get(keyword.as_str()), None);
+	}
+
+	assert_eq!(tokens.len(), 16 * 16 * 1024);
+	assert_matches!(
+		&tokens[.. 16],
+		[
+			token!(TokenKind::Keyword(Keyword::If)),
+			token!(TokenKind::Identifier(x)),
+			token!(TokenKind::Keyword(Keyword::Then)),
+			token!(TokenKind::Keyword(Keyword::Let)),
+			token!(TokenKind::Identifier(y)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Keyword(Keyword::Self_)),
+			token!(TokenKind::Keyword(Keyword::Else)),
+			token!(TokenKind::Keyword(Keyword::While)),
+			token!(TokenKind::Literal(Literal::True)),
+			token!(TokenKind::Keyword(Keyword::Do)),
+			token!(TokenKind::Keyword(Keyword::Break)),
+			token!(TokenKind::Keyword(Keyword::End)),
+			token!(TokenKind::Keyword(Keyword::Return)),
+			token!(TokenKind::Literal(Literal::Nil)),
+			token!(TokenKind::Keyword(Keyword::End)),
+		] => {
+			assert_symbol!(interner, x, "x");
+			assert_symbol!(interner, y, "y");
+		}
+	);
+}
+
+
 #[test]
 fn test_simple_function() {
 	let input = r#"
@@ -156,6 +224,83 @@ fn test_invalid_tokens() {
 }
 
 
+#[test]
+fn test_null_bytes() {
+	let input = "let x = 1\0\nlet y = x { echo a\0b }";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	let literal = |lit: &str| ArgPart::Unquoted(ArgUnit::Literal(lit.as_bytes().into()));
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Keyword(Keyword::Let)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Literal(Literal::Int(1))),
+			Err(Error { error: ErrorKind::Unexpected(b'\0'), pos: pos1 }),
+			token!(TokenKind::Keyword(Keyword::Let)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Command),
+			token!(TokenKind::Argument(echo)),
+			token!(TokenKind::Argument(a)),
+			Err(Error { error: ErrorKind::Unexpected(b'\0'), pos: pos2 }),
+			token!(TokenKind::Argument(b)),
+			token!(TokenKind::CloseCommand),
+		]
+			=> {
+				assert_eq!((pos1.line, pos1.column), (1, 9));
+				assert_eq!((pos2.line, pos2.column), (2, 18));
+
+				assert_eq!(echo.as_ref(), &[literal("echo")]);
+				assert_eq!(a.as_ref(), &[literal("a")]);
+				assert_eq!(b.as_ref(), &[literal("b")]);
+			}
+	);
+}
+
+
+#[test]
+fn test_long_line() {
+	// A single line of about 1MB, mixing a long literal with many short tokens.
+	let input = format!(
+		"let s = \"{}\" + {}1",
+		"a".repeat(512 * 1024),
+		"x + ".repeat(128 * 1024),
+	);
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let mut lexer = Lexer::new(cursor, &mut interner);
+
+	// Column tracking must be incremental, otherwise rescanning the line would take a
+	// quadratic number of transitions.
+	let tokens: Vec<Result<Token, Error>> = lexer.by_ref().collect();
+	assert!(lexer.transitions() <= 2 * input.len());
+
+	assert_eq!(tokens.len(), 5 + 2 * 128 * 1024 + 1);
+	assert_matches!(
+		tokens.last(),
+		Some(Ok(Token { kind: TokenKind::Literal(Literal::Int(1)), pos, .. }))
+			=> {
+				assert_eq!(pos.line, 1);
+				assert_eq!(pos.column as usize, input.len() - 1);
+			}
+	);
+}
+
+
 #[test]
 fn test_byte_literals() {
 	let input = r#"
@@ -256,10 +401,8 @@ fn test_string_literals() {
 
 
 #[test]
-fn test_number_literals() {
-	let input = r#"
-		let var = 123 + 456.7 + 89e10 + 1.2e3
-	"#;
+fn test_triple_quoted_strings() {
+	let input = "let var = \"\"\"first \"quoted\" line\n\\tsecond \"\" line\"\"\"\nlet x = \"\" ++ \"\"";
 
 	let mut interner = symbol::Interner::new();
 	let path = interner.get_or_intern("<test>");
@@ -273,36 +416,32 @@ fn test_number_literals() {
 		&tokens[..],
 		[
 			token!(TokenKind::Keyword(Keyword::Let)),
-			token!(TokenKind::Identifier(var)),
+			token!(TokenKind::Identifier(_)),
 			token!(TokenKind::Operator(Operator::Assign)),
-			token!(TokenKind::Literal(Literal::Int(i1))),
-			token!(TokenKind::Operator(Operator::Plus)),
-			token!(TokenKind::Literal(Literal::Float(f1))),
-			token!(TokenKind::Operator(Operator::Plus)),
-			token!(TokenKind::Literal(Literal::Float(f2))),
-			token!(TokenKind::Operator(Operator::Plus)),
-			token!(TokenKind::Literal(Literal::Float(f3))),
+			token!(TokenKind::Literal(Literal::String(lit))),
+			Ok(Token { kind: TokenKind::Keyword(Keyword::Let), pos, .. }),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Literal(Literal::String(empty1))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::String(empty2))),
 		]
 			=> {
-				assert_symbol!(interner, var, "var");
-				assert_eq!(*i1, 123);
-				assert_eq!(*f1, 456.7);
-				assert_eq!(*f2, 89e10);
-				assert_eq!(*f3, 1.2e3);
+				assert_eq!(lit.as_ref(), b"first \"quoted\" line\n\tsecond \"\" line");
+				// The token after the string is on the third line.
+				assert_eq!((pos.line, pos.column), (3, 0));
+				assert!(empty1.is_empty());
+				assert!(empty2.is_empty());
 			}
 	);
 }
 
 
 #[test]
-fn test_command_block() {
-	let input = r#"
-		let result = {
-			here-is-some 1arg "2arg" '3arg' 4'arg' >> "5"arg?;
-			$dollars ${are} "$fun";
-			so\ are escape \> \< \; sequences \?
-		}
-	"#;
+fn test_raw_string_literals() {
+	let input = r###"
+		r"C:\temp\new" ++ r#"say "hi" \n"# ++ r##"a "# b"## ++ r"" ++ r ++ std.r
+	"###;
 
 	let mut interner = symbol::Interner::new();
 	let path = interner.get_or_intern("<test>");
@@ -312,90 +451,46 @@ fn test_command_block() {
 
 	let tokens: Vec<Result<Token, Error>> = lexer.collect();
 
-	let unquoted = ArgPart::Unquoted;
-	let single_quoted = |arg: &str| ArgPart::SingleQuoted(arg.as_bytes().into());
-	let double_quoted = |parts: &[ArgUnit]| ArgPart::DoubleQuoted(parts.to_vec().into());
-
-	let literal = |lit: &str| ArgUnit::Literal(lit.as_bytes().into());
-	let sym = |ident: &str| interner.get(ident).expect("symbol not found");
-
 	assert_matches!(
 		&tokens[..],
 		[
-			token!(TokenKind::Keyword(Keyword::Let)),
-			token!(TokenKind::Identifier(var)),
-			token!(TokenKind::Operator(Operator::Assign)),
-			token!(TokenKind::Command),
-			token!(TokenKind::Argument(args0)),
-			token!(TokenKind::Argument(args1)),
-			token!(TokenKind::Argument(args2)),
-			token!(TokenKind::Argument(args3)),
-			token!(TokenKind::Argument(args4)),
-			token!(TokenKind::CmdOperator(CommandOperator::Output { append: true })),
-			token!(TokenKind::Argument(args5)),
-			token!(TokenKind::CmdOperator(CommandOperator::Try)),
-			token!(TokenKind::Semicolon),
-			token!(TokenKind::Argument(dollar1)),
-			token!(TokenKind::Argument(dollar2)),
-			token!(TokenKind::Argument(dollar3)),
-			token!(TokenKind::Semicolon),
-			token!(TokenKind::Argument(args6)),
-			token!(TokenKind::Argument(args7)),
-			token!(TokenKind::Argument(gt)),
-			token!(TokenKind::Argument(lt)),
-			token!(TokenKind::Argument(semicolon)),
-			token!(TokenKind::Argument(args8)),
-			token!(TokenKind::Argument(question)),
-			token!(TokenKind::CloseCommand),
+			Ok(Token { kind: TokenKind::Literal(Literal::String(lit1)), pos, .. }),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::String(lit2))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::String(lit3))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::String(lit4))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Identifier(r)),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Dot)),
+			token!(TokenKind::Identifier(field)),
 		]
 			=> {
-				assert_symbol!(interner, var, "result");
-
-				assert_eq!(args0.as_ref(), &[unquoted(literal("here-is-some"))]);
-				assert_eq!(args1.as_ref(), &[unquoted(literal("1arg"))]);
-				assert_eq!(args2.as_ref(), &[double_quoted(&[literal("2arg")])]);
-				assert_eq!(args3.as_ref(), &[single_quoted("3arg")]);
-				assert_eq!(args4.as_ref(), &[unquoted(literal("4")), single_quoted("arg")]);
-				assert_eq!(args5.as_ref(), &[double_quoted(&[literal("5")]), unquoted(literal("arg"))]);
-
-				assert_matches!(
-					dollar1.as_ref(),
-					&[ArgPart::Unquoted(ArgUnit::Dollar { symbol, .. })]
-						if symbol == sym("dollars")
-				);
-				assert_matches!(
-					dollar2.as_ref(),
-					&[ArgPart::Unquoted(ArgUnit::Dollar { symbol, .. })]
-						if symbol == sym("are")
-				);
-				assert_matches!(
-					dollar3.as_ref(),
-					&[ArgPart::DoubleQuoted(ref units)]
-						if matches!(units.as_ref(), &[ArgUnit::Dollar { ref symbol, .. }] if *symbol == sym("fun"))
-				);
-
-				assert_eq!(args6.as_ref(), &[unquoted(literal("so are"))]);
-				assert_eq!(args7.as_ref(), &[unquoted(literal("escape"))]);
-				assert_eq!(gt.as_ref(), &[unquoted(literal(">"))]);
-				assert_eq!(lt.as_ref(), &[unquoted(literal("<"))]);
-				assert_eq!(semicolon.as_ref(), &[unquoted(literal(";"))]);
-				assert_eq!(args8.as_ref(), &[unquoted(literal("sequences"))]);
-				assert_eq!(question.as_ref(), &[unquoted(literal("?"))]);
+				assert_eq!(lit1.as_ref(), br"C:\temp\new");
+				assert_eq!(lit2.as_ref(), br#"say "hi" \n"#);
+				assert_eq!(lit3.as_ref(), br##"a "# b"##);
+				assert!(lit4.is_empty());
+				assert_symbol!(interner, r, "r");
+				assert_symbol!(interner, field, "r");
+				// The literal starts at the prefix.
+				assert_eq!((pos.line, pos.column), (2, 2));
 			}
 	);
+
+	// Unterminated, as the closing quote lacks the hash.
+	let source = Source { path, contents: br##"r#"abc""##.as_ref().into() };
+	let tokens: Vec<_> = Lexer::new(Cursor::from(&source), &mut interner).collect();
+	assert_matches!(&tokens[..], [ error!(ErrorKind::UnexpectedEof) ]);
 }
 
 
 #[test]
-fn test_expansions() {
+fn test_string_escapes() {
 	let input = r#"
-		{
-			ls ~/;
-			ls *~/;
-			ls file-%.*;
-			ls ~/*%file%*?;
-			ls "*~/";
-		}
+		"tab\t cr\r backslash\\ quote\' end" ++ "unknown \q escape" ++ '\r'
 	"#;
 
 	let mut interner = symbol::Interner::new();
@@ -406,51 +501,791 @@ fn test_expansions() {
 
 	let tokens: Vec<Result<Token, Error>> = lexer.collect();
 
-	let unquoted = ArgPart::Unquoted;
-	let expansion = ArgPart::Expansion;
-	let double_quoted = |parts: &[ArgUnit]| ArgPart::DoubleQuoted(parts.to_vec().into());
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Literal(Literal::String(lit))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			error!(ErrorKind::InvalidEscapeSequence(sequence)),
+			token!(TokenKind::Literal(Literal::String(_))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::Byte(b'\r'))),
+		]
+			=> {
+				assert_eq!(lit.as_ref(), b"tab\t cr\r backslash\\ quote' end");
+				assert_eq!(sequence.as_ref(), b"\\q");
+			}
+	);
 
-	let literal = |lit: &str| ArgUnit::Literal(lit.as_bytes().into());
+	// The error points to the escape sequence.
+	assert_matches!(&tokens[2], Err(Error { pos, .. }) if (pos.line, pos.column) == (2, 51));
+}
+
+
+#[test]
+fn test_string_line_continuation() {
+	let input = "let var = \"foo\\\nbar\" ++ \"foo\\nbar\"\nvar";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
 
 	assert_matches!(
 		&tokens[..],
 		[
-			token!(TokenKind::Command),
-			token!(TokenKind::Argument(args0)),
-			token!(TokenKind::Argument(args1)),
-			token!(TokenKind::Semicolon),
-			token!(TokenKind::Argument(args2)),
-			token!(TokenKind::Argument(args3)),
-			token!(TokenKind::Semicolon),
-			token!(TokenKind::Argument(args4)),
-			token!(TokenKind::Argument(args5)),
-			token!(TokenKind::Semicolon),
-			token!(TokenKind::Argument(args6)),
-			token!(TokenKind::Argument(args7)),
-			token!(TokenKind::CmdOperator(CommandOperator::Try)),
-			token!(TokenKind::Semicolon),
-			token!(TokenKind::Argument(args8)),
-			token!(TokenKind::Argument(args9)),
-			token!(TokenKind::Semicolon),
-			token!(TokenKind::CloseCommand),
+			token!(TokenKind::Keyword(Keyword::Let)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Literal(Literal::String(lit1))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::String(lit2))),
+			Ok(Token { kind: TokenKind::Identifier(_), pos, .. }),
 		]
 			=> {
-				assert_eq!(args0.as_ref(), &[unquoted(literal("ls"))]);
-				assert_eq!(args1.as_ref(), &[expansion(ArgExpansion::Home)]);
+				assert_eq!(lit1.as_ref(), b"foobar");
+				assert_eq!(lit2.as_ref(), b"foo\nbar");
+				// The escaped newline still counts as a line break.
+				assert_eq!(pos.line, 3);
+			}
+	);
+}
 
-				assert_eq!(args2.as_ref(), &[unquoted(literal("ls"))]);
-				assert_eq!(args3.as_ref(), &[expansion(ArgExpansion::Star), unquoted(literal("~/"))]);
 
-				assert_eq!(args4.as_ref(), &[unquoted(literal("ls"))]);
-				assert_eq!(
-					args5.as_ref(),
-					&[
-						unquoted(literal("file-")),
-						expansion(ArgExpansion::Percent),
-						unquoted(literal(".")),
-						expansion(ArgExpansion::Star)
-					]
-				);
+#[test]
+fn test_raw_tab_warning() {
+	let input = "let x = \"a\tb\" ++ \"a\\tb\"";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	let config = Config { raw_tab_warning: true, ..Config::default() };
+	let mut lexer = Lexer::with_config(Cursor::from(&source), &mut interner, config);
+	let tokens: Vec<Result<Token, Error>> = lexer.by_ref().collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Keyword(Keyword::Let)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Literal(Literal::String(lit1))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::String(lit2))),
+		]
+			=> {
+				assert_eq!(lit1.as_ref(), b"a\tb");
+				assert_eq!(lit2.as_ref(), b"a\tb");
+			}
+	);
+
+	// Only the raw tab is reported.
+	assert_matches!(
+		lexer.warnings(),
+		[Warning { warning: WarningKind::RawTab, pos }] if pos.column == 10
+	);
+
+	// Disabled by default.
+	let mut lexer = Lexer::new(Cursor::from(&source), &mut interner);
+	assert_eq!(lexer.by_ref().count(), 6);
+	assert!(lexer.warnings().is_empty());
+}
+
+
+#[test]
+fn test_reserved_words() {
+	let input = "let yield = 1\nlet x = yield + yields\n{ echo yield $yield }";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	let config = Config {
+		reserved_words: std::iter::once(b"yield".as_ref().into()).collect(),
+		..Config::default()
+	};
+	let mut lexer = Lexer::with_config(Cursor::from(&source), &mut interner, config);
+	assert!(lexer.by_ref().all(|result| result.is_ok()));
+
+	// Arguments and dollars in command blocks are not identifiers.
+	let warnings: Vec<_> = lexer
+		.warnings()
+		.iter()
+		.map(
+			|warning| match &warning.warning {
+				WarningKind::ReservedWord(word) => (word.clone(), warning.pos.line, warning.pos.column),
+				kind => panic!("unexpected warning: {:?}", kind),
+			}
+		)
+		.collect();
+
+	let reserved = |line, column| (b"yield".as_ref().into(), line, column);
+	assert_eq!(warnings, [reserved(1, 4), reserved(2, 8)]);
+
+	// Nothing is reserved by default.
+	let mut lexer = Lexer::new(Cursor::from(&source), &mut interner);
+	lexer.by_ref().for_each(drop);
+	assert!(lexer.warnings().is_empty());
+}
+
+
+#[test]
+fn test_identifier_normalization() {
+	let input = "let Foo = foo\n{ echo $FOO }";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	let symbols = |lexer: Lexer| -> Vec<symbol::Symbol> {
+		lexer
+			.filter_map(
+				|token| match token.expect("unexpected error").kind {
+					TokenKind::Identifier(symbol) => Some(symbol),
+					TokenKind::Argument(parts) => match parts.as_ref() {
+						[ArgPart::Unquoted(ArgUnit::Dollar { symbol, .. })] => Some(*symbol),
+						_ => None,
+					},
+					_ => None,
+				}
+			)
+			.collect()
+	};
+
+	let config = Config {
+		normalize_identifier: Some(|word| word.to_ascii_lowercase().into()),
+		..Config::default()
+	};
+	let normalized = symbols(Lexer::with_config(Cursor::from(&source), &mut interner, config));
+	let foo = interner.get_or_intern("foo");
+	assert_eq!(normalized, [foo, foo, foo]);
+
+	// The original spellings are never interned.
+	assert_eq!(interner.get("Foo"), None);
+	assert_eq!(interner.get("FOO"), None);
+
+	// Identifiers are kept as is by default.
+	let identity = symbols(Lexer::new(Cursor::from(&source), &mut interner));
+	assert_eq!(identity.len(), 3);
+	assert_ne!(identity[0], identity[1]);
+	assert_ne!(identity[1], identity[2]);
+}
+
+
+#[test]
+fn test_number_literals() {
+	let input = r#"
+		let var = 123 + 456.7 + 89e10 + 1.2e3
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Keyword(Keyword::Let)),
+			token!(TokenKind::Identifier(var)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Literal(Literal::Int(i1))),
+			token!(TokenKind::Operator(Operator::Plus)),
+			token!(TokenKind::Literal(Literal::Float(f1))),
+			token!(TokenKind::Operator(Operator::Plus)),
+			token!(TokenKind::Literal(Literal::Float(f2))),
+			token!(TokenKind::Operator(Operator::Plus)),
+			token!(TokenKind::Literal(Literal::Float(f3))),
+		]
+			=> {
+				assert_symbol!(interner, var, "var");
+				assert_eq!(*i1, 123);
+				assert_eq!(*f1, 456.7);
+				assert_eq!(*f2, 89e10);
+				assert_eq!(*f3, 1.2e3);
+			}
+	);
+}
+
+
+#[test]
+fn test_radix_literals() {
+	let input = r#"
+		let var = 0xFF + 0o755 + 0b1010 + 0x7fffffffffffffff + 0
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Keyword(Keyword::Let)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Literal(Literal::Int(i1))),
+			token!(TokenKind::Operator(Operator::Plus)),
+			token!(TokenKind::Literal(Literal::Int(i2))),
+			token!(TokenKind::Operator(Operator::Plus)),
+			token!(TokenKind::Literal(Literal::Int(i3))),
+			token!(TokenKind::Operator(Operator::Plus)),
+			token!(TokenKind::Literal(Literal::Int(i4))),
+			token!(TokenKind::Operator(Operator::Plus)),
+			token!(TokenKind::Literal(Literal::Int(i5))),
+		]
+			=> {
+				assert_eq!(*i1, 0xFF);
+				assert_eq!(*i2, 0o755);
+				assert_eq!(*i3, 0b1010);
+				assert_eq!(*i4, i64::MAX);
+				assert_eq!(*i5, 0);
+			}
+	);
+
+	let input = "0x 0x10000000000000000 0b12 0o8";
+
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			error!(ErrorKind::InvalidNumber(empty)),
+			error!(ErrorKind::InvalidNumber(overflow)),
+			error!(ErrorKind::Unexpected(b'2')),
+			error!(ErrorKind::Unexpected(b'8')),
+		]
+			=> {
+				assert_eq!(empty.as_ref(), b"0x");
+				assert_eq!(overflow.as_ref(), b"0x10000000000000000");
+			}
+	);
+}
+
+
+#[test]
+fn test_digit_separators() {
+	let input = "1_000_000 1.234_567 1_0.5e1_0 0_1";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Literal(Literal::Int(1_000_000))),
+			token!(TokenKind::Literal(Literal::Float(f1))),
+			token!(TokenKind::Literal(Literal::Float(f2))),
+			token!(TokenKind::Literal(Literal::Int(1))),
+		]
+			=> {
+				assert_eq!(*f1, 1.234_567);
+				assert_eq!(*f2, 10.5e10);
+			}
+	);
+
+	let input = "100_ 1__0 1_.5";
+
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			// Trailing separator.
+			Err(Error { error: ErrorKind::Unexpected(b'_'), pos: p1 }),
+			// Consecutive separators.
+			Err(Error { error: ErrorKind::Unexpected(b'_'), pos: p2 }),
+			token!(TokenKind::Literal(Literal::Int(0))),
+			// Separator before the decimal dot.
+			Err(Error { error: ErrorKind::Unexpected(b'_'), pos: p3 }),
+			token!(TokenKind::Literal(Literal::Int(5))),
+		]
+			=> {
+				assert_eq!(p1.column, 3);
+				assert_eq!(p2.column, 7);
+				assert_eq!(p3.column, 11);
+			}
+	);
+}
+
+
+#[test]
+fn test_digit_separators_radix() {
+	let input = "0xFF_FF 0o7_55 0b1010_1010 1.5e1_0";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Literal(Literal::Int(0xFF_FF))),
+			token!(TokenKind::Literal(Literal::Int(0o7_55))),
+			token!(TokenKind::Literal(Literal::Int(0b1010_1010))),
+			token!(TokenKind::Literal(Literal::Float(f1))),
+		]
+			=> assert_eq!(*f1, 1.5e10)
+	);
+
+	let input = "0x_FF 0o7__5 0b1_ 1._5 1e_5";
+
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			// Separator after the radix prefix.
+			Err(Error { error: ErrorKind::Unexpected(b'_'), pos: p1 }),
+			token!(TokenKind::Identifier(_)),
+			// Consecutive separators.
+			Err(Error { error: ErrorKind::Unexpected(b'_'), pos: p2 }),
+			token!(TokenKind::Literal(Literal::Int(5))),
+			// Trailing separator.
+			Err(Error { error: ErrorKind::Unexpected(b'_'), pos: p3 }),
+			// Separator after the decimal dot.
+			Err(Error { error: ErrorKind::Unexpected(b'_'), pos: p4 }),
+			token!(TokenKind::Literal(Literal::Int(5))),
+			// Separator after the exponent.
+			Err(Error { error: ErrorKind::Unexpected(b'_'), pos: p5 }),
+			token!(TokenKind::Literal(Literal::Int(5))),
+		]
+			=> {
+				assert_eq!(p1.column, 2);
+				assert_eq!(p2.column, 10);
+				assert_eq!(p3.column, 16);
+				assert_eq!(p4.column, 20);
+				assert_eq!(p5.column, 25);
+			}
+	);
+}
+
+
+#[test]
+fn test_scientific_notation() {
+	let input = "1e10 2.5e-3 6.022E23 1e5 1.5e+2";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	let assert_close = |value: f64, expected: f64| {
+		assert!((value - expected).abs() <= f64::EPSILON * expected.abs());
+	};
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Literal(Literal::Float(f1))),
+			token!(TokenKind::Literal(Literal::Float(f2))),
+			token!(TokenKind::Literal(Literal::Float(f3))),
+			token!(TokenKind::Literal(Literal::Float(f4))),
+			token!(TokenKind::Literal(Literal::Float(f5))),
+		]
+			=> {
+				assert_close(*f1, 1e10);
+				assert_close(*f2, 0.0025);
+				assert_close(*f3, 6.022e23);
+				assert_close(*f4, 100000.0);
+				assert_close(*f5, 150.0);
+			}
+	);
+
+	let input = "1e 2e-x";
+
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			// Missing exponent digits.
+			Err(Error { error: ErrorKind::Unexpected(b' '), pos: p1 }),
+			// Missing digits after the exponent sign.
+			Err(Error { error: ErrorKind::Unexpected(b'x'), pos: p2 }),
+		]
+			=> {
+				assert_eq!(p1.column, 2);
+				assert_eq!(p2.column, 6);
+			}
+	);
+}
+
+
+#[test]
+fn test_compound_assign_operators() {
+	let input = "+= -= *= /= %= ++= ++ -1";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Operator(Operator::PlusAssign)),
+			token!(TokenKind::Operator(Operator::MinusAssign)),
+			token!(TokenKind::Operator(Operator::TimesAssign)),
+			token!(TokenKind::Operator(Operator::DivAssign)),
+			token!(TokenKind::Operator(Operator::ModAssign)),
+			token!(TokenKind::Operator(Operator::ConcatAssign)),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Operator(Operator::Minus)),
+			token!(TokenKind::Literal(Literal::Int(1))),
+		]
+	);
+}
+
+
+#[test]
+fn test_number_literals_dots() {
+	let input = ".5 5. (5.) 5.foo 5..10 1.5.x";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			// Leading dot.
+			token!(TokenKind::Literal(Literal::Float(f1))),
+			// Trailing dot.
+			token!(TokenKind::Literal(Literal::Float(f2))),
+			token!(TokenKind::OpenParens),
+			token!(TokenKind::Literal(Literal::Float(f3))),
+			token!(TokenKind::CloseParens),
+			// Member access on an integer.
+			token!(TokenKind::Literal(Literal::Int(5))),
+			token!(TokenKind::Operator(Operator::Dot)),
+			token!(TokenKind::Identifier(foo)),
+			// Range after an integer.
+			token!(TokenKind::Literal(Literal::Int(5))),
+			token!(TokenKind::Operator(Operator::Range)),
+			token!(TokenKind::Literal(Literal::Int(10))),
+			// Member access on a float.
+			token!(TokenKind::Literal(Literal::Float(f4))),
+			token!(TokenKind::Operator(Operator::Dot)),
+			token!(TokenKind::Identifier(x)),
+		]
+			=> {
+				assert_eq!(*f1, 0.5);
+				assert_eq!(*f2, 5.0);
+				assert_eq!(*f3, 5.0);
+				assert_eq!(*f4, 1.5);
+				assert_symbol!(interner, foo, "foo");
+				assert_symbol!(interner, x, "x");
+			}
+	);
+
+	let positions: Vec<u32> = tokens
+		.iter()
+		.map(|token| token.as_ref().expect("unexpected error").pos.column)
+		.collect();
+
+	assert_eq!(positions, [0, 3, 6, 7, 9, 11, 12, 13, 17, 18, 20, 23, 26, 27]);
+
+	// Dots are not symbols in command blocks.
+	let source = Source { path, contents: b"{ ./run .5 }".as_ref().into() };
+	let tokens: Vec<_> = Lexer::new(Cursor::from(&source), &mut interner).collect();
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Command),
+			token!(TokenKind::Argument(_)),
+			token!(TokenKind::Argument(_)),
+			token!(TokenKind::CloseCommand),
+		]
+	);
+}
+
+
+#[test]
+fn test_keyword_field_names() {
+	let input = "std.assert x.end x. if";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	// Words right after a dot are field names, even if they are keywords.
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Dot)),
+			token!(TokenKind::Identifier(assert)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Dot)),
+			token!(TokenKind::Identifier(end)),
+			// Separated by whitespace, the keyword is kept.
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Dot)),
+			token!(TokenKind::Keyword(Keyword::If)),
+		]
+			=> {
+				assert_symbol!(interner, assert, "assert");
+				assert_symbol!(interner, end, "end");
+			}
+	);
+}
+
+
+#[test]
+fn test_multi_char_operator_positions() {
+	let input = "a == b >= c <= d != e ++ f\n  @[ ${ x >> y } &{ z << w }";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let positions: Vec<(TokenKind, u32, u32)> = lexer
+		.map(|token| token.expect("unexpected error"))
+		.filter(
+			|token| matches!(
+				token.kind,
+				TokenKind::Operator(_)
+					| TokenKind::CmdOperator(_)
+					| TokenKind::OpenDict
+					| TokenKind::CaptureCommand
+					| TokenKind::AsyncCommand
+			)
+		)
+		.map(|token| (token.kind, token.pos.line, token.pos.column))
+		.collect();
+
+	assert_eq!(
+		positions,
+		[
+			(TokenKind::Operator(Operator::Equals), 1, 2),
+			(TokenKind::Operator(Operator::GreaterEquals), 1, 7),
+			(TokenKind::Operator(Operator::LowerEquals), 1, 12),
+			(TokenKind::Operator(Operator::NotEquals), 1, 17),
+			(TokenKind::Operator(Operator::Concat), 1, 22),
+			(TokenKind::OpenDict, 2, 2),
+			(TokenKind::CaptureCommand, 2, 5),
+			(TokenKind::CmdOperator(CommandOperator::Output { append: true }), 2, 10),
+			(TokenKind::AsyncCommand, 2, 17),
+			(TokenKind::CmdOperator(CommandOperator::Input { literal: true }), 2, 22),
+		]
+	);
+}
+
+
+#[test]
+fn test_command_block() {
+	let input = r#"
+		let result = {
+			here-is-some 1arg "2arg" '3arg' 4'arg' >> "5"arg?;
+			$dollars ${are} "$fun";
+			so\ are escape \> \< \; sequences \?
+		}
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	let unquoted = ArgPart::Unquoted;
+	let single_quoted = |arg: &str| ArgPart::SingleQuoted(arg.as_bytes().into());
+	let double_quoted = |parts: &[ArgUnit]| ArgPart::DoubleQuoted(parts.to_vec().into());
+
+	let literal = |lit: &str| ArgUnit::Literal(lit.as_bytes().into());
+	let sym = |ident: &str| interner.get(ident).expect("symbol not found");
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Keyword(Keyword::Let)),
+			token!(TokenKind::Identifier(var)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Command),
+			token!(TokenKind::Argument(args0)),
+			token!(TokenKind::Argument(args1)),
+			token!(TokenKind::Argument(args2)),
+			token!(TokenKind::Argument(args3)),
+			token!(TokenKind::Argument(args4)),
+			token!(TokenKind::CmdOperator(CommandOperator::Output { append: true })),
+			token!(TokenKind::Argument(args5)),
+			token!(TokenKind::CmdOperator(CommandOperator::Try)),
+			token!(TokenKind::Semicolon),
+			token!(TokenKind::Argument(dollar1)),
+			token!(TokenKind::Argument(dollar2)),
+			token!(TokenKind::Argument(dollar3)),
+			token!(TokenKind::Semicolon),
+			token!(TokenKind::Argument(args6)),
+			token!(TokenKind::Argument(args7)),
+			token!(TokenKind::Argument(gt)),
+			token!(TokenKind::Argument(lt)),
+			token!(TokenKind::Argument(semicolon)),
+			token!(TokenKind::Argument(args8)),
+			token!(TokenKind::Argument(question)),
+			token!(TokenKind::CloseCommand),
+		]
+			=> {
+				assert_symbol!(interner, var, "result");
+
+				assert_eq!(args0.as_ref(), &[unquoted(literal("here-is-some"))]);
+				assert_eq!(args1.as_ref(), &[unquoted(literal("1arg"))]);
+				assert_eq!(args2.as_ref(), &[double_quoted(&[literal("2arg")])]);
+				assert_eq!(args3.as_ref(), &[single_quoted("3arg")]);
+				assert_eq!(args4.as_ref(), &[unquoted(literal("4")), single_quoted("arg")]);
+				assert_eq!(args5.as_ref(), &[double_quoted(&[literal("5")]), unquoted(literal("arg"))]);
+
+				assert_matches!(
+					dollar1.as_ref(),
+					&[ArgPart::Unquoted(ArgUnit::Dollar { symbol, .. })]
+						if symbol == sym("dollars")
+				);
+				assert_matches!(
+					dollar2.as_ref(),
+					&[ArgPart::Unquoted(ArgUnit::Dollar { symbol, .. })]
+						if symbol == sym("are")
+				);
+				assert_matches!(
+					dollar3.as_ref(),
+					&[ArgPart::DoubleQuoted(ref units)]
+						if matches!(units.as_ref(), &[ArgUnit::Dollar { ref symbol, .. }] if *symbol == sym("fun"))
+				);
+
+				assert_eq!(args6.as_ref(), &[unquoted(literal("so are"))]);
+				assert_eq!(args7.as_ref(), &[unquoted(literal("escape"))]);
+				assert_eq!(gt.as_ref(), &[unquoted(literal(">"))]);
+				assert_eq!(lt.as_ref(), &[unquoted(literal("<"))]);
+				assert_eq!(semicolon.as_ref(), &[unquoted(literal(";"))]);
+				assert_eq!(args8.as_ref(), &[unquoted(literal("sequences"))]);
+				assert_eq!(question.as_ref(), &[unquoted(literal("?"))]);
+			}
+	);
+}
+
+
+#[test]
+fn test_expansions() {
+	let input = r#"
+		{
+			ls ~/;
+			ls *~/;
+			ls file-%.*;
+			ls ~/*%file%*?;
+			ls "*~/";
+		}
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	let unquoted = ArgPart::Unquoted;
+	let expansion = ArgPart::Expansion;
+	let double_quoted = |parts: &[ArgUnit]| ArgPart::DoubleQuoted(parts.to_vec().into());
+
+	let literal = |lit: &str| ArgUnit::Literal(lit.as_bytes().into());
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Command),
+			token!(TokenKind::Argument(args0)),
+			token!(TokenKind::Argument(args1)),
+			token!(TokenKind::Semicolon),
+			token!(TokenKind::Argument(args2)),
+			token!(TokenKind::Argument(args3)),
+			token!(TokenKind::Semicolon),
+			token!(TokenKind::Argument(args4)),
+			token!(TokenKind::Argument(args5)),
+			token!(TokenKind::Semicolon),
+			token!(TokenKind::Argument(args6)),
+			token!(TokenKind::Argument(args7)),
+			token!(TokenKind::CmdOperator(CommandOperator::Try)),
+			token!(TokenKind::Semicolon),
+			token!(TokenKind::Argument(args8)),
+			token!(TokenKind::Argument(args9)),
+			token!(TokenKind::Semicolon),
+			token!(TokenKind::CloseCommand),
+		]
+			=> {
+				assert_eq!(args0.as_ref(), &[unquoted(literal("ls"))]);
+				assert_eq!(args1.as_ref(), &[expansion(ArgExpansion::Home)]);
+
+				assert_eq!(args2.as_ref(), &[unquoted(literal("ls"))]);
+				assert_eq!(args3.as_ref(), &[expansion(ArgExpansion::Star), unquoted(literal("~/"))]);
+
+				assert_eq!(args4.as_ref(), &[unquoted(literal("ls"))]);
+				assert_eq!(
+					args5.as_ref(),
+					&[
+						unquoted(literal("file-")),
+						expansion(ArgExpansion::Percent),
+						unquoted(literal(".")),
+						expansion(ArgExpansion::Star)
+					]
+				);
 
 				assert_eq!(args6.as_ref(), &[unquoted(literal("ls"))]);
 				assert_eq!(
@@ -470,3 +1305,433 @@ fn test_expansions() {
 			}
 	);
 }
+
+
+#[test]
+fn test_line_starts() {
+	let input = "let x = 1\nlet yy = \"foo\"\n\n\tx";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let line_starts = source.line_starts();
+
+	assert_eq!(line_starts, [0, 10, 25, 26]);
+
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Token> = lexer
+		.collect::<Result<_, _>>()
+		.expect("unexpected lexer error");
+
+	let yy = &tokens[5];
+	assert_matches!(yy.kind, TokenKind::Identifier(_));
+	assert_eq!((yy.pos.line, yy.pos.column, yy.pos.offset), (2, 4, 14));
+	assert_eq!(&input.as_bytes()[yy.pos.offset..][..2], b"yy");
+
+	for token in tokens {
+		assert_eq!(token.pos.offset_in(&line_starts), Some(token.pos.offset));
+		assert_eq!(SourcePos::from_offset(token.pos.offset, &line_starts, path), token.pos);
+	}
+}
+
+
+#[test]
+fn test_lex_with() {
+	let input = r#"
+		let x = [ 1, 2.5, "three" ] # comment
+		{ echo $x | cat > file }
+		let y = 'ab'
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	let collected: Vec<String> = Lexer::new(Cursor::from(&source), &mut interner)
+		.map(|result| format!("{:?}", result))
+		.collect();
+
+	let mut streamed = Vec::new();
+	lex_with(&source, &mut interner, |result| streamed.push(format!("{:?}", result)));
+
+	assert!(collected.iter().any(|result| result.starts_with("Err")));
+	assert_eq!(streamed, collected);
+}
+
+
+#[test]
+fn test_disabled_command_blocks() {
+	let input = "&{ ls }\n${ ls }";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+
+	// Synchronous blocks can't be disabled, so the `{` left by the disabled opener is kept.
+	let config = Config {
+		disabled_command_blocks: vec![CommandBlockKind::Asynchronous, CommandBlockKind::Synchronous],
+		..Config::default()
+	};
+	let tokens: Vec<_> = Lexer::with_config(Cursor::from(&source), &mut interner, config).collect();
+
+	assert_matches!(
+		tokens.as_slice(),
+		[
+			error!(ErrorKind::Unexpected(b'&')),
+			token!(TokenKind::Command),
+			token!(TokenKind::Argument(_)),
+			token!(TokenKind::CloseCommand),
+			token!(TokenKind::CaptureCommand),
+			token!(TokenKind::Argument(_)),
+			token!(TokenKind::CloseCommand),
+		]
+	);
+
+	assert_matches!(&tokens[1], Ok(Token { pos, .. }) if pos.column == 1 && pos.offset == 1);
+
+	// All openers are enabled by default.
+	let tokens: Vec<_> = Lexer::new(Cursor::from(&source), &mut interner).collect();
+	assert_matches!(tokens.as_slice(), [token!(TokenKind::AsyncCommand), ..]);
+}
+
+
+#[test]
+fn test_string_interpolation() {
+	let input = r#""a ${x} b ${ "c${ { echo } }" }!" ++ "\${y}""#;
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::StringHead(head)),
+			token!(TokenKind::Identifier(x)),
+			token!(TokenKind::StringMiddle(middle)),
+			token!(TokenKind::StringHead(nested_head)),
+			token!(TokenKind::Command),
+			token!(TokenKind::Argument(_)),
+			token!(TokenKind::CloseCommand),
+			token!(TokenKind::StringTail(nested_tail)),
+			token!(TokenKind::StringTail(tail)),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::String(escaped))),
+		]
+			=> {
+				assert_eq!(head.as_ref(), b"a ");
+				assert_symbol!(interner, x, "x");
+				assert_eq!(middle.as_ref(), b" b ");
+				assert_eq!(nested_head.as_ref(), b"c");
+				assert!(nested_tail.is_empty());
+				assert_eq!(tail.as_ref(), b"!");
+				assert_eq!(escaped.as_ref(), b"${y}");
+			}
+	);
+}
+
+
+#[test]
+fn test_hex_and_unicode_escapes() {
+	let input = r#"
+		"\x41\x7f\xff" ++ "\u{48}\u{e9}\u{1F600}" ++ "a\u{10FFFF}b"
+		"\xg1" ++ "\x4" ++ "\u{110000}" ++ "\u41" ++ "\u{}" ++ "\u{1234567}"
+		"\u{41"
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Literal(Literal::String(hex))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::String(unicode))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			token!(TokenKind::Literal(Literal::String(max))),
+
+			Err(Error { error: ErrorKind::InvalidEscapeSequence(e1), pos: p1 }),
+			token!(TokenKind::Literal(Literal::String(_))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			error!(ErrorKind::InvalidEscapeSequence(e2)),
+			token!(TokenKind::Literal(Literal::String(s2))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			error!(ErrorKind::InvalidEscapeSequence(e3)),
+			token!(TokenKind::Literal(Literal::String(_))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			error!(ErrorKind::InvalidEscapeSequence(e4)),
+			token!(TokenKind::Literal(Literal::String(_))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			error!(ErrorKind::InvalidEscapeSequence(e5)),
+			token!(TokenKind::Literal(Literal::String(_))),
+			token!(TokenKind::Operator(Operator::Concat)),
+			error!(ErrorKind::InvalidEscapeSequence(e6)),
+			token!(TokenKind::Literal(Literal::String(_))),
+
+			// The invalid character is still scanned as part of the literal.
+			Err(Error { error: ErrorKind::InvalidEscapeSequence(e7), pos: p7 }),
+			token!(TokenKind::Literal(Literal::String(s7))),
+		]
+			=> {
+				assert_eq!(hex.as_ref(), b"A\x7f\xff");
+				assert_eq!(unicode.as_ref(), "H\u{e9}\u{1F600}".as_bytes());
+				assert_eq!(max.as_ref(), "a\u{10FFFF}b".as_bytes());
+
+				assert_eq!(e1.as_ref(), b"\\xg");
+				assert_eq!((p1.line, p1.column), (3, 3));
+				assert_eq!(e2.as_ref(), b"\\x4\"");
+				assert_eq!(s2.as_ref(), b"");
+				assert_eq!(e3.as_ref(), b"\\u{110000}");
+				assert_eq!(e4.as_ref(), b"\\u4");
+				assert_eq!(e5.as_ref(), b"\\u{}");
+				assert_eq!(e6.as_ref(), b"\\u{1234567");
+				assert_eq!(e7.as_ref(), b"\\u{41\"");
+				assert_eq!((p7.line, p7.column), (4, 3));
+				assert_eq!(s7.as_ref(), b"");
+			}
+	);
+
+	// Unterminated sequence at EOF, reported at the escape position.
+	let source = Source { path, contents: br#""ab\u{41"#.as_ref().into() };
+	let tokens: Vec<_> = Lexer::new(Cursor::from(&source), &mut interner).collect();
+	assert_matches!(
+		&tokens[..],
+		[ Err(Error { error: ErrorKind::InvalidEscapeSequence(e), pos }) ]
+			if e.as_ref() == b"\\u{41" && pos.column == 3
+	);
+}
+
+
+#[test]
+fn test_block_comments() {
+	let input = "let #{ outer #{ inner\n}# still # outer }# x = 1 #{}#\n{ echo #{ } }# a }\n#{ a }#b";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Keyword(Keyword::Let)),
+			Ok(Token { kind: TokenKind::Identifier(x), pos, .. }),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Literal(Literal::Int(1))),
+			token!(TokenKind::Command),
+			token!(TokenKind::Argument(_)),
+			token!(TokenKind::Argument(_)),
+			token!(TokenKind::CloseCommand),
+			token!(TokenKind::Identifier(b)),
+		]
+			=> {
+				assert_symbol!(interner, x, "x");
+				assert_symbol!(interner, b, "b");
+				// Newlines in the comment are accounted for.
+				assert_eq!((pos.line, pos.column), (2, 20));
+			}
+	);
+
+	// Unterminated, reported at the outermost opener.
+	let source = Source { path, contents: b"let x = 1\n  #{ a #{ b }# c".as_ref().into() };
+	let tokens: Vec<_> = Lexer::new(Cursor::from(&source), &mut interner).collect();
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Keyword(Keyword::Let)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Assign)),
+			token!(TokenKind::Literal(Literal::Int(1))),
+			Err(Error { error: ErrorKind::UnterminatedBlockComment, pos }),
+		] if (pos.line, pos.column) == (2, 2)
+	);
+}
+
+
+#[test]
+fn test_shebang() {
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let config = Config { preserve_comments: true, ..Config::default() };
+
+	let source = Source { path, contents: b"#!/usr/bin/env hush\nlet x".as_ref().into() };
+	let mut lexer = Lexer::with_config(Cursor::from(&source), &mut interner, config.clone());
+	assert!(lexer.shebang());
+
+	// The shebang is not a comment token.
+	let tokens: Vec<_> = lexer.by_ref().collect();
+	assert_matches!(
+		&tokens[..],
+		[
+			Ok(Token { kind: TokenKind::Keyword(Keyword::Let), pos, .. }),
+			token!(TokenKind::Identifier(_)),
+		] if (pos.line, pos.column) == (2, 0)
+	);
+
+	// Only at the start of the input.
+	let source = Source { path, contents: b" #!/usr/bin/env hush\nlet x".as_ref().into() };
+	let lexer = Lexer::with_config(Cursor::from(&source), &mut interner, config);
+	assert!(!lexer.shebang());
+	let tokens: Vec<_> = lexer.collect();
+	assert_matches!(tokens[0], token!(TokenKind::Comment(_)));
+}
+
+
+#[test]
+fn test_lenient_escapes() {
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: br#""a\qb" '\q' "\x4""#.as_ref().into() };
+
+	// Strict by default.
+	let tokens: Vec<_> = Lexer::new(Cursor::from(&source), &mut interner).collect();
+	assert_matches!(
+		&tokens[..],
+		[
+			Err(Error { error: ErrorKind::InvalidEscapeSequence(e1), pos }),
+			token!(TokenKind::Literal(Literal::String(_))),
+			error!(ErrorKind::InvalidEscapeSequence(e2)),
+			token!(TokenKind::Literal(Literal::Byte(_))),
+			error!(ErrorKind::InvalidEscapeSequence(_)),
+			token!(TokenKind::Literal(Literal::String(_))),
+		] => {
+			assert_eq!(e1.as_ref(), br"\q");
+			assert_eq!(e2.as_ref(), br"\q");
+			assert_eq!((pos.line, pos.column), (1, 2));
+		}
+	);
+
+	// Lenient escapes are interpreted as the escaped character, but malformed sequences
+	// are still errors.
+	let config = Config { lenient_escapes: true, ..Config::default() };
+	let tokens: Vec<_> = Lexer::with_config(Cursor::from(&source), &mut interner, config).collect();
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Literal(Literal::String(value))),
+			token!(TokenKind::Literal(Literal::Byte(b'q'))),
+			error!(ErrorKind::InvalidEscapeSequence(_)),
+			token!(TokenKind::Literal(Literal::String(_))),
+		] if value.as_ref() == b"aqb"
+	);
+}
+
+
+#[test]
+fn test_range_operators() {
+	let input = "0..self.len 1..=true .5..-.5 ...";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Literal(Literal::Int(0))),
+			token!(TokenKind::Operator(Operator::Range)),
+			// Keywords after a range are not field names.
+			token!(TokenKind::Keyword(Keyword::Self_)),
+			token!(TokenKind::Operator(Operator::Dot)),
+			token!(TokenKind::Identifier(len)),
+			token!(TokenKind::Literal(Literal::Int(1))),
+			token!(TokenKind::Operator(Operator::RangeInclusive)),
+			token!(TokenKind::Literal(Literal::True)),
+			token!(TokenKind::Literal(Literal::Float(f1))),
+			token!(TokenKind::Operator(Operator::Range)),
+			token!(TokenKind::Operator(Operator::Minus)),
+			token!(TokenKind::Literal(Literal::Float(f2))),
+			token!(TokenKind::Operator(Operator::Spread)),
+		]
+			=> {
+				assert_symbol!(interner, len, "len");
+				assert_eq!(*f1, 0.5);
+				assert_eq!(*f2, 0.5);
+			}
+	);
+}
+
+
+#[test]
+fn test_pipeline_operator() {
+	let input = "x |> f(1)|>g { a | b }";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let tokens: Vec<Result<Token, Error>> = lexer.collect();
+
+	assert_matches!(
+		&tokens[..],
+		[
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::Operator(Operator::Pipeline)),
+			token!(TokenKind::Identifier(_)),
+			token!(TokenKind::OpenParens),
+			token!(TokenKind::Literal(Literal::Int(1))),
+			token!(TokenKind::CloseParens),
+			token!(TokenKind::Operator(Operator::Pipeline)),
+			token!(TokenKind::Identifier(_)),
+			// Command pipes are unaffected.
+			token!(TokenKind::Command),
+			token!(TokenKind::Argument(_)),
+			token!(TokenKind::Pipe),
+			token!(TokenKind::Argument(_)),
+			token!(TokenKind::CloseCommand),
+		]
+	);
+}
+
+
+#[test]
+fn test_token_end() {
+	let input = "let x = \"a\\\"b\" == 10\n{ echo }";
+
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let lexer = Lexer::new(cursor, &mut interner);
+
+	let ranges: Vec<_> = lexer
+		.map(|token| token.expect("unexpected error").span())
+		.map(|span| ((span.start.line, span.start.column), (span.end.line, span.end.column)))
+		.collect();
+
+	assert_eq!(
+		ranges,
+		[
+			((1, 0), (1, 3)),
+			((1, 4), (1, 5)),
+			((1, 6), (1, 7)),
+			((1, 8), (1, 14)),
+			((1, 15), (1, 17)),
+			((1, 18), (1, 20)),
+			((2, 0), (2, 1)),
+			((2, 2), (2, 6)),
+			((2, 7), (2, 8)),
+		]
+	);
+}
@@ -20,27 +20,7 @@ use crate::{
 
 impl std::fmt::Display for Keyword {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-		color
-			::Fg(
-				color::Blue,
-				match self {
-					Self::Let => "let",
-					Self::If => "if",
-					Self::Then => "then",
-					Self::Else => "else",
-					Self::ElseIf => "elseif",
-					Self::End => "end",
-					Self::For => "for",
-					Self::In => "in",
-					Self::Do => "do",
-					Self::While => "while",
-					Self::Function => "function",
-					Self::Return => "return",
-					Self::Break => "break",
-					Self::Self_ => "self",
-				}
-			)
-			.fmt(f)
+		color::Fg(color::Blue, self.as_str()).fmt(f)
 	}
 }
 
@@ -82,8 +62,18 @@ impl std::fmt::Display for Operator {
 			Self::And => color::Fg(color::Blue, "and").fmt(f),
 			Self::Or => color::Fg(color::Blue, "or").fmt(f),
 			Self::Concat => color::Fg(color::Yellow, "++").fmt(f),
+			Self::Pipeline => color::Fg(color::Yellow, "|>").fmt(f),
 			Self::Dot => color::Fg(color::Yellow, ".").fmt(f),
+			Self::Range => color::Fg(color::Yellow, "..").fmt(f),
+			Self::RangeInclusive => color::Fg(color::Yellow, "..=").fmt(f),
+			Self::Spread => color::Fg(color::Yellow, "...").fmt(f),
 			Self::Assign => "=".fmt(f),
+			Self::PlusAssign => "+=".fmt(f),
+			Self::MinusAssign => "-=".fmt(f),
+			Self::TimesAssign => "*=".fmt(f),
+			Self::DivAssign => "/=".fmt(f),
+			Self::ModAssign => "%=".fmt(f),
+			Self::ConcatAssign => "++=".fmt(f),
 			Self::Try => color::Fg(color::Yellow, "?").fmt(f),
 		}
 	}
@@ -146,6 +136,10 @@ impl<'a> Display<'a> for ArgExpansion {
 
 				color::Fg(color::Yellow, "]").fmt(f)
 			},
+			Self::Splat { symbol, .. } => {
+				color::Fg(color::Yellow, "@").fmt(f)?;
+				symbol.fmt(f, context)
+			},
 		}
 	}
 }
@@ -201,6 +195,26 @@ impl<'a> Display<'a> for TokenKind {
 			Self::Keyword(kw) => kw.fmt(f),
 			Self::Operator(op) => op.fmt(f),
 			Self::Literal(lit) => lit.fmt(f),
+			Self::StringHead(s) => write!(
+				f,
+				"\"{}${{",
+				color::Bold(String::from_utf8_lossy(s).escape_debug())
+			),
+			Self::StringMiddle(s) => write!(
+				f,
+				"}}{}${{",
+				color::Bold(String::from_utf8_lossy(s).escape_debug())
+			),
+			Self::StringTail(s) => write!(
+				f,
+				"}}{}\"",
+				color::Bold(String::from_utf8_lossy(s).escape_debug())
+			),
+			Self::Comment(text) => write!(f, "# {}", String::from_utf8_lossy(text)),
+			Self::Label(s) => {
+				s.fmt(f, context)?;
+				":".fmt(f)
+			}
 			Self::Colon => ":".fmt(f),
 			Self::Comma => ",".fmt(f),
 			Self::OpenParens => "(".fmt(f),
@@ -211,6 +225,7 @@ impl<'a> Display<'a> for TokenKind {
 			Self::Command => "{".fmt(f),
 			Self::CaptureCommand => "${".fmt(f),
 			Self::AsyncCommand => "&{".fmt(f),
+			Self::CombinedCaptureCommand => "@{".fmt(f),
 			Self::CloseCommand => "}".fmt(f),
 			Self::Argument(parts) => {
 				for part in parts.iter() {
@@ -1,6 +1,6 @@
 mod fmt;
 
-use super::SourcePos;
+use super::{SourcePos, Span};
 use crate::symbol::Symbol;
 
 
@@ -8,6 +8,7 @@ use crate::symbol::Symbol;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Keyword {
 	Let,
+	Const,
 	If,
 	Then,
 	Else,
@@ -20,10 +21,129 @@ pub enum Keyword {
 	Function,
 	Return,
 	Break,
+	Continue,
+	Begin,
+	Assert,
+	Capture,
+	When,
+	Match,
+	With,
 	Self_,
 }
 
 
+impl Keyword {
+	/// All keywords, in declaration order.
+	pub const ALL: [Keyword; 22] = [
+		Self::Let,
+		Self::Const,
+		Self::If,
+		Self::Then,
+		Self::Else,
+		Self::ElseIf,
+		Self::End,
+		Self::For,
+		Self::In,
+		Self::Do,
+		Self::While,
+		Self::Function,
+		Self::Return,
+		Self::Break,
+		Self::Continue,
+		Self::Begin,
+		Self::Assert,
+		Self::Capture,
+		Self::When,
+		Self::Match,
+		Self::With,
+		Self::Self_,
+	];
+
+
+	/// The source text of the keyword.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Let => "let",
+			Self::Const => "const",
+			Self::If => "if",
+			Self::Then => "then",
+			Self::Else => "else",
+			Self::ElseIf => "elseif",
+			Self::End => "end",
+			Self::For => "for",
+			Self::In => "in",
+			Self::Do => "do",
+			Self::While => "while",
+			Self::Function => "function",
+			Self::Return => "return",
+			Self::Break => "break",
+			Self::Continue => "continue",
+			Self::Begin => "begin",
+			Self::Assert => "assert",
+			Self::Capture => "capture",
+			Self::When => "when",
+			Self::Match => "match",
+			Self::With => "with",
+			Self::Self_ => "self",
+		}
+	}
+
+
+	/// Check if the keyword starts a statement which is not an expression, such as `let` or
+	/// `return`.
+	pub fn is_statement_starter(&self) -> bool {
+		matches!(
+			self,
+			Self::Let
+				| Self::Const
+				| Self::For
+				| Self::While
+				| Self::Return
+				| Self::Break
+				| Self::Continue
+				| Self::Begin
+				| Self::Assert
+		)
+	}
+
+
+	/// Get the keyword for the given word, if any. This is the table used by the lexer.
+	pub fn from_bytes(word: &[u8]) -> Option<Self> {
+		match word {
+			b"let" => Some(Self::Let),
+			b"const" => Some(Self::Const),
+			b"if" => Some(Self::If),
+			b"then" => Some(Self::Then),
+			b"else" => Some(Self::Else),
+			b"elseif" => Some(Self::ElseIf),
+			b"end" => Some(Self::End),
+			b"for" => Some(Self::For),
+			b"in" => Some(Self::In),
+			b"do" => Some(Self::Do),
+			b"while" => Some(Self::While),
+			b"function" => Some(Self::Function),
+			b"return" => Some(Self::Return),
+			b"break" => Some(Self::Break),
+			b"continue" => Some(Self::Continue),
+			b"begin" => Some(Self::Begin),
+			b"assert" => Some(Self::Assert),
+			b"capture" => Some(Self::Capture),
+			b"when" => Some(Self::When),
+			b"match" => Some(Self::Match),
+			b"with" => Some(Self::With),
+			b"self" => Some(Self::Self_),
+			_ => None,
+		}
+	}
+
+
+	/// Get the keyword for the given word, if any.
+	pub fn from_str(word: &str) -> Option<Self> {
+		Self::from_bytes(word.as_bytes())
+	}
+}
+
+
 /// Literals for non-composite types.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
@@ -58,10 +178,21 @@ pub enum Operator {
 	And, // and
 	Or,  // or
 
-	Concat, // ++
-	Dot,    // .
+	Concat,   // ++
+	Pipeline, // |>
+	Dot,      // .
 
-	Assign, // =
+	Range,          // ..
+	RangeInclusive, // ..=
+	Spread,         // ...
+
+	Assign,       // =
+	PlusAssign,   // +=
+	MinusAssign,  // -=
+	TimesAssign,  // *=
+	DivAssign,    // /=
+	ModAssign,    // %=
+	ConcatAssign, // ++=
 
 	Try, // ?
 }
@@ -99,6 +230,20 @@ impl Operator {
 	pub fn is_prefix(&self) -> bool {
 		matches!(self, Self::Not | Self::Minus)
 	}
+
+
+	/// The binary operator of a compound assignment operator, as `+` in `+=`.
+	pub fn compound_assign(&self) -> Option<Self> {
+		match self {
+			Self::PlusAssign => Some(Self::Plus),
+			Self::MinusAssign => Some(Self::Minus),
+			Self::TimesAssign => Some(Self::Times),
+			Self::DivAssign => Some(Self::Div),
+			Self::ModAssign => Some(Self::Mod),
+			Self::ConcatAssign => Some(Self::Concat),
+			_ => None,
+		}
+	}
 }
 
 
@@ -133,6 +278,9 @@ pub enum ArgExpansion {
 	Star, // *
 	Percent, // %
 	CharClass(Box<[u8]>), // [...]
+
+	/// Each item of an array variable as a separate argument: `@args`.
+	Splat { symbol: Symbol, pos: SourcePos },
 }
 
 /// Argument parts may be single, double ou unquoted.
@@ -185,6 +333,16 @@ pub enum TokenKind {
 	Operator(Operator),
 	Literal(Literal),
 
+	// Interpolated string literals are split in parts around the embedded expressions,
+	// whose tokens are produced in between. Strings without interpolation are plain
+	// string literals.
+	StringHead(Box<[u8]>),   // "...${
+	StringMiddle(Box<[u8]>), // }...${
+	StringTail(Box<[u8]>),   // }..."
+
+	// Line comment text, only produced if comments are preserved.
+	Comment(Box<[u8]>), // # ...
+
 	Colon, // :
 	Comma, // ,
 
@@ -196,10 +354,15 @@ pub enum TokenKind {
 	CloseBracket, // ]
 
 	// Command block tokens
-	Command,        // {
-	AsyncCommand,   // &{
-	CaptureCommand, // ${
-	CloseCommand,   // }
+	Command,                // {
+	AsyncCommand,           // &{
+	CaptureCommand,         // ${
+	CombinedCaptureCommand, // @{
+	CloseCommand,           // }
+
+	// The label of a command, which is the first word of the command if it is an
+	// identifier followed by a colon.
+	Label(Symbol), // label:
 
 	// A single argument may be composed of many parts.
 	Argument(Box<[ArgPart]>),
@@ -213,20 +376,94 @@ pub enum TokenKind {
 
 impl TokenKind {
 	/// Check if the token terminates a statement block.
-	/// Currently, only the END, ELSE and ELSEIF keywords do that.
+	/// Currently, only the END, ELSE, ELSEIF and WITH keywords do that.
 	pub fn is_block_terminator(&self) -> bool {
 		matches!(
 			self,
-			TokenKind::Keyword(Keyword::End) | TokenKind::Keyword(Keyword::Else) | TokenKind::Keyword(Keyword::ElseIf)
+			TokenKind::Keyword(Keyword::End)
+				| TokenKind::Keyword(Keyword::Else)
+				| TokenKind::Keyword(Keyword::ElseIf)
+				| TokenKind::Keyword(Keyword::With)
 		)
 	}
 
 
-	/// Check if the token starts a command block.
+	/// Check if the token starts a command block. This includes the capture keyword, as
+	/// in `capture { ... }`.
 	pub fn is_command_block_starter(&self) -> bool {
 		matches!(
 			self,
-			TokenKind::Command | TokenKind::AsyncCommand | TokenKind::CaptureCommand
+			TokenKind::Command
+				| TokenKind::AsyncCommand
+				| TokenKind::CaptureCommand
+				| TokenKind::CombinedCaptureCommand
+				| TokenKind::Keyword(Keyword::Capture)
+		)
+	}
+
+
+	/// Check if the token may start an expression.
+	pub fn is_expr_starter(&self) -> bool {
+		match self {
+			TokenKind::Identifier(_) | TokenKind::Literal(_) | TokenKind::StringHead(_) => true,
+			TokenKind::OpenParens | TokenKind::OpenBracket | TokenKind::OpenDict => true,
+			TokenKind::Keyword(keyword) => matches!(
+				keyword,
+				Keyword::Self_ | Keyword::Function | Keyword::If | Keyword::When | Keyword::Match
+			),
+			TokenKind::Operator(op) => op.is_prefix(),
+			token => token.is_command_block_starter(),
+		}
+	}
+
+
+	/// Check if the token opens a nested construct, which is closed by a matching
+	/// closing token. See TokenKind::is_nesting_closer.
+	pub fn is_nesting_opener(&self) -> bool {
+		matches!(
+			self,
+			TokenKind::OpenParens
+				| TokenKind::OpenBracket
+				| TokenKind::OpenDict
+				| TokenKind::StringHead(_)
+				| TokenKind::Command
+				| TokenKind::AsyncCommand
+				| TokenKind::CaptureCommand
+				| TokenKind::CombinedCaptureCommand
+				| TokenKind::Keyword(Keyword::If)
+				| TokenKind::Keyword(Keyword::Match)
+				| TokenKind::Keyword(Keyword::While)
+				| TokenKind::Keyword(Keyword::For)
+				| TokenKind::Keyword(Keyword::Function)
+				| TokenKind::Keyword(Keyword::Begin)
+		)
+	}
+
+
+	/// Check if the token closes a nested construct.
+	pub fn is_nesting_closer(&self) -> bool {
+		matches!(
+			self,
+			TokenKind::CloseParens
+				| TokenKind::CloseBracket
+				| TokenKind::StringTail(_)
+				| TokenKind::CloseCommand
+				| TokenKind::Keyword(Keyword::End)
+		)
+	}
+
+
+	/// Check if the token is the given keyword in a command block, where keywords are not
+	/// lexed. Such keywords are single unquoted words.
+	pub fn is_command_keyword(&self, keyword: Keyword) -> bool {
+		matches!(
+			self,
+			TokenKind::Argument(parts)
+				if matches!(
+					parts.as_ref(),
+					[ArgPart::Unquoted(ArgUnit::Literal(word))]
+						if word.as_ref() == keyword.as_str().as_bytes()
+				)
 		)
 	}
 
@@ -243,8 +480,24 @@ impl TokenKind {
 
 
 /// A lexical token.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Token {
 	pub kind: TokenKind,
 	pub pos: SourcePos,
+	/// The position just after the token.
+	pub end: SourcePos,
+}
+
+
+impl Token {
+	/// A token at the given position. Its end is set by the lexer once it is produced.
+	pub fn new(kind: TokenKind, pos: SourcePos) -> Self {
+		Self { kind, pos, end: pos }
+	}
+
+
+	/// The range covered by the token.
+	pub fn span(&self) -> Span {
+		Span { start: self.pos, end: self.end }
+	}
 }
@@ -0,0 +1,57 @@
+use crate::{
+	fmt::{self, Display},
+	symbol,
+};
+use super::SourcePos;
+
+
+/// The kind of lexical warning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarningKind {
+	/// Raw tab character in a string literal, which is often accidentally copied from
+	/// indentation.
+	RawTab,
+	/// Identifier which is reserved for future use.
+	ReservedWord(Box<[u8]>),
+}
+
+
+impl std::fmt::Display for WarningKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::RawTab => "raw tab character in string literal, consider using '\\t'".fmt(f),
+
+			Self::ReservedWord(word) => {
+				write!(f, "'{}' is a reserved word", String::from_utf8_lossy(word))
+			}
+		}
+	}
+}
+
+
+/// A lexical warning. Warnings don't prevent tokens from being produced.
+#[derive(Debug, Clone)]
+pub struct Warning {
+	pub warning: WarningKind,
+	pub pos: SourcePos,
+}
+
+
+impl Warning {
+	pub fn raw_tab(pos: SourcePos) -> Self {
+		Self { warning: WarningKind::RawTab, pos }
+	}
+
+	pub fn reserved_word(word: &[u8], pos: SourcePos) -> Self {
+		Self { warning: WarningKind::ReservedWord(word.into()), pos }
+	}
+}
+
+
+impl<'a> Display<'a> for Warning {
+	type Context = &'a symbol::Interner;
+
+	fn fmt(&self, f: &mut std::fmt::Formatter, context: Self::Context) -> std::fmt::Result {
+		write!(f, "{} - {}.", fmt::Show(self.pos, context), self.warning)
+	}
+}
@@ -1,4 +1,5 @@
 pub mod ast;
+pub mod completion;
 pub mod error;
 pub mod lexer;
 pub mod parser;
@@ -9,12 +10,12 @@ mod tests;
 
 use std::cell::RefCell;
 
-use crate::symbol;
+use crate::{diagnostic, symbol};
 pub use ast::Ast;
 pub use error::{Error, Errors};
 use lexer::Lexer;
 use parser::Parser;
-pub use source::{Source, SourcePos};
+pub use source::{Source, SourcePos, Span};
 pub use fmt::AnalysisDisplayContext;
 
 
@@ -25,41 +26,93 @@ pub struct Analysis {
 	pub ast: Ast,
 	/// Syntax errors.
 	pub errors: Errors,
+	/// Lexical warnings, which don't prevent the program from running.
+	pub warnings: Box<[lexer::Warning]>,
 }
 
 
 impl Analysis {
 	/// Perform syntax analysis in the given source.
 	pub fn analyze(source: &Source, interner: &mut symbol::Interner) -> Self {
+		Self::analyze_with_config(source, interner, lexer::Config::default())
+	}
+
+
+	/// Perform syntax analysis in the given source, using the given lexer configuration,
+	/// which determines the enabled warnings.
+	pub fn analyze_with_config(
+		source: &Source,
+		interner: &mut symbol::Interner,
+		config: lexer::Config,
+	) -> Self {
+		let mut errors = Vec::new();
+
+		let (ast, warnings) = Self::parse(source, interner, config, |error| errors.push(error));
+
+		Analysis {
+			ast,
+			errors: Errors(errors.into()),
+			warnings,
+		}
+	}
+
+
+	/// Perform syntax analysis in the given source, emitting errors as diagnostics.
+	pub fn analyze_with<R>(
+		source: &Source,
+		interner: &mut symbol::Interner,
+		reporter: &mut R
+	) -> Ast
+	where
+		R: diagnostic::Reporter,
+	{
+		let (ast, _) = Self::parse(source, interner, lexer::Config::default(), |error| {
+			reporter.report(error.into())
+		});
+
+		ast
+	}
+
+
+	/// Parse the given source, reporting errors to the given function. Returns the AST and
+	/// the enabled lexical warnings.
+	fn parse<F>(
+		source: &Source,
+		interner: &mut symbol::Interner,
+		config: lexer::Config,
+		report: F,
+	) -> (Ast, Box<[lexer::Warning]>)
+	where
+		F: FnMut(Error),
+	{
 		let cursor = lexer::Cursor::from(source);
-		let lexer = Lexer::new(cursor, interner);
+		let mut lexer = Lexer::with_config(cursor, interner, config);
 
 		// Errors will be produced by the lexer and the parser alternatively.
 		// There won't be borrow issues here because the lexer will always run a complete
 		// iteration (producing a token or an error) before yielding to the parser.
-		let errors = RefCell::new(Vec::new());
+		let report = RefCell::new(report);
 
-		let tokens = lexer.filter_map(|result| match result {
+		let tokens = lexer.by_ref().filter_map(|result| match result {
 			Ok(token) => Some(token),
 			Err(error) => {
-				errors.borrow_mut().push(Error::Lexer(error));
+				(report.borrow_mut())(Error::Lexer(error));
 				None
 			}
 		});
 
 		let parser = Parser::new(tokens, |error| {
-			errors.borrow_mut().push(Error::Parser(error))
+			(report.borrow_mut())(Error::Parser(error))
 		});
 
 		let statements = parser.parse();
 
-		Analysis {
-			ast: Ast {
-				source: source.path,
-				statements
-			},
-			errors: Errors(errors.into_inner().into()),
-		}
+		let ast = Ast {
+			source: source.path,
+			statements,
+		};
+
+		(ast, lexer.warnings().into())
 	}
 
 
@@ -67,4 +120,12 @@ impl Analysis {
 	pub fn is_ok(&self) -> bool {
 		self.errors.is_empty()
 	}
+
+
+	/// Check if any fatal error occurred, meaning the program can't be run. Syntax errors
+	/// are always fatal, while warnings never are, so a program with only warnings is
+	/// runnable.
+	pub fn has_fatal_errors(&self) -> bool {
+		!self.errors.is_empty()
+	}
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::{io::{self, FileDescriptor}, semantic::program::SourcePos};
 use super::{
 	ast,
@@ -6,7 +8,11 @@ use super::{
 	ArgUnit,
 	CommandOperator as Operator,
 	Error,
+	ErrorKind,
 	ErrorReporter,
+	Keyword,
+	Literal,
+	ParseContext,
 	Parser,
 	Token,
 	TokenKind,
@@ -20,6 +26,17 @@ where
 {
 	/// Parse a command block.
 	pub(super) fn parse_command_block(&mut self) -> sync::Result<ast::CommandBlock, Error> {
+		// The capture keyword is an explicit spelling of the capture block: `capture { ... }`
+		// is equivalent to `${ ... }`.
+		if let Some(Token { kind: TokenKind::Keyword(Keyword::Capture), .. }) = self.token {
+			self.step();
+
+			self.expect(TokenKind::Command)
+				.with_sync(sync::Strategy::skip_one())?;
+
+			return self.parse_command_block_of(ast::CommandBlockKind::Capture);
+		}
+
 		let kind = self
 			.eat(
 				|token| ast::CommandBlockKind
@@ -30,8 +47,78 @@ where
 			)
 			.with_sync(sync::Strategy::skip_one())?;
 
+		self.parse_command_block_of(kind)
+	}
+
+
+	/// Parse a command block of the given kind, after the opening token.
+	fn parse_command_block_of(
+		&mut self,
+		kind: ast::CommandBlockKind,
+	) -> sync::Result<ast::CommandBlock, Error> {
+		let context = std::mem::replace(&mut self.context, ParseContext::Command);
+
+		let result = self.parse_command_block_body(kind);
+
+		self.context = context;
+
+		// Errors may be propagated out of the block before being reported.
+		let block = result.map_err(
+			|(error, strategy)| (error.in_context(ParseContext::Command), strategy)
+		)?;
+
+		let timeout = self.parse_timeout()?;
+
+		Ok(ast::CommandBlock { timeout, ..block })
+	}
+
+
+	/// Parse an optional timeout annotation after a command block: a colon followed by
+	/// the number of seconds. A colon that ends the enclosing expression, as in
+	/// `c ? { a } : 1`, or that isn't followed by a number is left for the enclosing
+	/// construct.
+	fn parse_timeout(&mut self) -> sync::Result<Option<Duration>, Error> {
+		let is_timeout = matches!(self.token, Some(Token { kind: TokenKind::Colon, .. }))
+			&& self.colon_depth != Some(self.expr_depth)
+			&& matches!(
+				self.peek(),
+				Some(Token { kind: TokenKind::Literal(Literal::Int(_) | Literal::Float(_)), .. })
+			);
+
+		if !is_timeout {
+			return Ok(None);
+		}
+
+		self.step();
+
+		self
+			.eat(
+				|token| match token {
+					// Number literals are never negative.
+					Token { kind: TokenKind::Literal(Literal::Int(secs)), .. } => {
+						Ok(Duration::from_secs(secs as u64))
+					}
+
+					Token { kind: TokenKind::Literal(Literal::Float(secs)), .. } => {
+						Duration::try_from_secs_f64(secs)
+							.map_err(|_| (Error::unexpected_msg(token.clone(), "timeout in seconds"), token))
+					}
+
+					token => Err((Error::unexpected_msg(token.clone(), "timeout in seconds"), token)),
+				}
+			)
+			.map(Some)
+			.with_sync(sync::Strategy::keep())
+	}
+
+
+	/// Parse the commands of a command block, after the opening token.
+	fn parse_command_block_body(
+		&mut self,
+		kind: ast::CommandBlockKind,
+	) -> sync::Result<ast::CommandBlock, Error> {
 		// Check empty command block.
-		if let Some(Token { kind: TokenKind::CloseCommand, pos }) = &self.token {
+		if let Some(Token { kind: TokenKind::CloseCommand, pos, .. }) = &self.token {
 			return Err(Error::empty_command_block(*pos))
 				.with_sync(sync::Strategy::skip_one())?;
 		}
@@ -54,12 +141,35 @@ where
 		self.expect(TokenKind::CloseCommand)
 			.with_sync(sync::Strategy::token(TokenKind::CloseCommand))?;
 
-		Ok(ast::CommandBlock { kind, head, tail })
+		Ok(ast::CommandBlock { kind, head, tail, timeout: None })
 	}
 
 
-	/// Parse a complete command, including pipelines.
+	/// Parse a complete command, including pipelines and conditionals.
 	fn parse_command(&mut self) -> ast::Command {
+		if let Some(Token { pos, .. }) = &self.token {
+			if self.is_command_keyword(Keyword::If) {
+				let pos = *pos;
+
+				// Conditionals may be nested in their branches, so they count towards the
+				// nesting limit. A conditional nested too deep is skipped as a whole.
+				return self
+					.nested(
+						|parser| {
+							parser.step();
+							parser.parse_conditional(pos)
+						}
+					)
+					.map_err(
+						|(error, strategy)| match error.kind {
+							ErrorKind::NestingTooDeep { .. } => (error, sync::Strategy::conditional()),
+							_ => (error, strategy),
+						}
+					)
+					.synchronize(self);
+			}
+		}
+
 		let mut tail = Vec::new();
 
 		let head = self.parse_basic_command()
@@ -75,22 +185,123 @@ where
 			tail.push(basic_command);
 		}
 
-		ast::Command {
+		ast::Command::Pipeline {
 			head,
 			tail: tail.into(),
 		}
 	}
 
 
+	/// Parse a conditional command after the if keyword.
+	fn parse_conditional(&mut self, pos: SourcePos) -> sync::Result<ast::Command, Error> {
+		let condition = self.parse_command();
+
+		self.expect(TokenKind::Semicolon)
+			.with_sync(sync::Strategy::keep())
+			.synchronize(self);
+
+		self.expect_command_keyword(Keyword::Then)
+			.with_sync(sync::Strategy::keep())
+			.synchronize(self);
+
+		let then = self.parse_command_sequence(&[Keyword::Else, Keyword::End]);
+
+		let otherwise =
+			if self.is_command_keyword(Keyword::Else) {
+				self.step();
+				self.parse_command_sequence(&[Keyword::End])
+			} else {
+				Default::default()
+			};
+
+		self.expect_command_keyword(Keyword::End)
+			.with_sync(sync::Strategy::basic_command_terminator())?;
+
+		Ok(
+			ast::Command::Conditional {
+				condition: Box::new(condition),
+				then,
+				otherwise,
+				pos,
+			}
+		)
+	}
+
+
+	/// Parse semicolon terminated commands, until one of the given keywords is found.
+	fn parse_command_sequence(&mut self, terminators: &[Keyword]) -> Box<[ast::Command]> {
+		let mut commands = Vec::new();
+
+		loop {
+			match &self.token {
+				Some(Token { kind: TokenKind::CloseCommand, .. }) | None => break,
+				_ if terminators.iter().any(|&keyword| self.is_command_keyword(keyword)) => break,
+				_ => (),
+			}
+
+			commands.push(self.parse_command());
+
+			if let Some(Token { kind: TokenKind::Semicolon, .. }) = self.token {
+				self.step();
+			} else {
+				break;
+			}
+		}
+
+		commands.into()
+	}
+
+
+	/// Check whether the current token is the given keyword, as a single unquoted word.
+	/// Keywords are not lexed in command blocks, so they are recognized here instead.
+	fn is_command_keyword(&self, keyword: Keyword) -> bool {
+		matches!(&self.token, Some(token) if token.kind.is_command_keyword(keyword))
+	}
+
+
+	/// Consume the expected keyword in a command block, or produce an error.
+	fn expect_command_keyword(&mut self, keyword: Keyword) -> Result<(), Error> {
+		if self.is_command_keyword(keyword) {
+			self.step();
+			return Ok(());
+		}
+
+		self.eat(|token| Err((Error::unexpected_msg(token.clone(), keyword.as_str()), token)))
+	}
+
+
 	/// Parse a single basic command, including redirections and try operator.
 	fn parse_basic_command(&mut self) -> sync::Result<ast::BasicCommand, Error> {
+		// The label precedes the environment variables.
+		let label = match self.token {
+			Some(Token { kind: TokenKind::Label(label), .. }) => {
+				self.step();
+				Some(label)
+			}
+
+			_ => None,
+		};
+
 		let env = std::iter::from_fn(|| self.parse_env_assign()).collect();
 
+		// A terminator in place of the program name, which is usually a stray separator.
+		if let Some(Token { kind, pos, .. }) = &self.token {
+			if kind.is_basic_command_terminator() {
+				return Err(Error::empty_program(*pos))
+					.with_sync(sync::Strategy::keep());
+			}
+		}
+
 		let command = self.parse_argument()
 			.with_sync(sync::Strategy::basic_command_terminator())?;
 
 		let pos = command.pos;
 
+		// Quoted empty arguments are allowed, but not as the program name.
+		if command.parts.is_empty() {
+			self.error_reporter.report(Error::empty_program(pos).in_context(ParseContext::Command));
+		}
+
 		let mut arguments = Vec::new();
 		loop {
 			let is_redirection = matches!(
@@ -123,6 +334,7 @@ where
 
 		Ok(
 			ast::BasicCommand {
+				label,
 				program: command,
 				env,
 				arguments: arguments.into(),
@@ -137,10 +349,16 @@ where
 	/// Parse a single argument.
 	fn parse_argument(&mut self) -> Result<ast::Argument, Error> {
 		let (arg_parts, pos) = self.eat(|token| match token {
-			Token { kind: TokenKind::Argument(parts), pos } => Ok((parts, pos)),
+			Token { kind: TokenKind::Argument(parts), pos, .. } => Ok((parts, pos)),
 			token => Err((Error::unexpected_msg(token.clone(), "argument"), token)),
 		})?;
 
+		// The lexer should never produce such argument, but an empty quoted argument is
+		// fine.
+		if arg_parts.is_empty() {
+			self.error_reporter.report(Error::empty_argument(pos).in_context(ParseContext::Command));
+		}
+
 		Ok(
 			Self::build_arg(
 				arg_parts.into_vec(), // Use vec's owned iterator.
@@ -152,7 +370,7 @@ where
 	/// Parse an env-assign.
 	fn parse_env_assign(&mut self) -> Option<(ast::ArgUnit, ast::Argument)> {
 		self.eat(|token| match token {
-			Token { kind: TokenKind::Argument(parts), pos }
+			Token { kind: TokenKind::Argument(parts), pos, .. }
 			if matches!(&parts[..], [ ArgPart::Unquoted(_), ArgPart::EnvAssign, .. ]) => {
 				let mut parts = parts.into_vec(); // Use vec's owned iterator.
 
@@ -168,7 +386,7 @@ where
 
 				Ok(Some((key, value)))
 			},
-			token => Err((Error::InvalidEnvAssign, token)),
+			token => Err((ErrorKind::InvalidEnvAssign.into(), token)),
 		})
 			.unwrap_or(None)
 	}
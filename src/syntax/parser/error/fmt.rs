@@ -1,6 +1,6 @@
 use std::fmt::Display as _;
 
-use super::{Error, Expected, Token};
+use super::{Error, ErrorKind, Expected, ParseContext, Token};
 use crate::{
 	fmt::{self, Display},
 	symbol,
@@ -24,7 +24,7 @@ impl<'a> Display<'a> for Expected {
 }
 
 
-impl<'a> Display<'a> for Error {
+impl<'a> Display<'a> for ErrorKind {
 	type Context = &'a symbol::Interner;
 
 	fn fmt(&self, f: &mut std::fmt::Formatter, context: Self::Context) -> std::fmt::Result {
@@ -33,7 +33,7 @@ impl<'a> Display<'a> for Error {
 
 			Self::UnexpectedEof => "unexpected end of file".fmt(f),
 
-			Self::Unexpected { token: Token { kind, pos }, expected } => {
+			Self::Unexpected { token: Token { kind, pos, .. }, expected } => {
 				write!(f, "{} - unexpected '", fmt::Show(pos, context))?;
 				kind.fmt(f, context)?;
 				"', expected ".fmt(f)?;
@@ -43,6 +43,85 @@ impl<'a> Display<'a> for Error {
 			Self::EmptyCommandBlock { pos } => {
 				write!(f, "{} - empty command block", fmt::Show(pos, context))
 			}
+
+			Self::EmptyArgument { pos } => {
+				write!(f, "{} - empty argument", fmt::Show(pos, context))
+			}
+
+			Self::EmptyProgram { pos } => {
+				write!(f, "{} - empty program name", fmt::Show(pos, context))
+			}
+
+			Self::AssignAsEquality { pos } => {
+				write!(f, "{} - unexpected '=', did you mean '=='?", fmt::Show(pos, context))
+			}
+
+			Self::DetachedTry { pos } => {
+				write!(
+					f,
+					"{} - expected ':' after '?', the try operator must be attached to its operand",
+					fmt::Show(pos, context)
+				)
+			}
+
+			Self::AssignCountMismatch { targets, values, pos } => {
+				write!(
+					f,
+					"{} - {} assignment targets, but {} values",
+					fmt::Show(pos, context),
+					targets,
+					values,
+				)
+			}
+
+			Self::RequiredAfterDefault { pos } => {
+				write!(
+					f,
+					"{} - parameter without default value follows a defaulted parameter",
+					fmt::Show(pos, context),
+				)
+			}
+
+			Self::StatementInInterpolation { keyword, pos } => {
+				write!(
+					f,
+					"{} - unexpected '{}' statement, string interpolation only embeds expressions",
+					fmt::Show(pos, context),
+					keyword.as_str(),
+				)
+			}
+
+			Self::NestingTooDeep { pos } => {
+				write!(f, "{} - nesting too deep", fmt::Show(pos, context))
+			}
+
+			Self::Unterminated { construct, pos } => {
+				write!(f, "{} - unterminated {}, opened here", fmt::Show(pos, context), construct)
+			}
+
+			Self::DuplicateMatchArm { pos, .. } => {
+				write!(f, "{} - duplicate match arm", fmt::Show(pos, context))
+			}
+
+			Self::TrailingInput { token: Token { kind, pos, .. } } => {
+				write!(f, "{} - unexpected trailing input '", fmt::Show(pos, context))?;
+				kind.fmt(f, context)?;
+				"'".fmt(f)
+			}
+		}
+	}
+}
+
+
+impl<'a> Display<'a> for Error {
+	type Context = &'a symbol::Interner;
+
+	fn fmt(&self, f: &mut std::fmt::Formatter, context: Self::Context) -> std::fmt::Result {
+		self.kind.fmt(f, context)?;
+
+		match self.context {
+			ParseContext::Expression => Ok(()),
+			ParseContext::Command => " in command block".fmt(f),
 		}
 	}
 }
@@ -1,53 +1,213 @@
 mod fmt;
 
-use super::{SourcePos, Token, TokenKind};
+use super::{Keyword, SourcePos, Span, Token, TokenKind};
 
 
 /// The kind of token the parser was expecting.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expected {
 	Token(TokenKind),
 	Message(&'static str),
 }
 
 
-/// A parser error.
-#[derive(Debug)]
-pub enum Error {
+/// Whether the parser was in an expression or in a command block.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParseContext {
+	#[default]
+	Expression,
+	Command,
+}
+
+
+/// The kind of parser error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
 	/// Premature EOF.
 	UnexpectedEof,
 	/// Unexpected token.
 	Unexpected { token: Token, expected: Expected },
 	/// Command blocks must have at least one command.
 	EmptyCommandBlock { pos: SourcePos },
+	/// Arguments must have at least one part.
+	EmptyArgument { pos: SourcePos },
+	/// Commands must have a non-empty program name.
+	EmptyProgram { pos: SourcePos },
+	/// Assignment operator used where the equality operator was expected.
+	AssignAsEquality { pos: SourcePos },
+	/// Conditional operator without a colon, likely meant as a try operator. The position
+	/// is the one of the question mark.
+	DetachedTry { pos: SourcePos },
+	/// Statements or expressions nested beyond the configured limit.
+	NestingTooDeep { pos: SourcePos },
+	/// Premature EOF inside a construct, which was opened in the given position.
+	Unterminated { construct: &'static str, pos: SourcePos },
+	/// Match arm with the same constant pattern as a previous arm, which is never taken.
+	DuplicateMatchArm { pos: SourcePos, first: SourcePos },
+	/// Multiple assignment with different numbers of targets and values. The position is
+	/// the one of the assignment operator.
+	AssignCountMismatch { targets: usize, values: usize, pos: SourcePos },
+	/// A parameter without a default value following one with a default value.
+	RequiredAfterDefault { pos: SourcePos },
+	/// A statement embedded in a string interpolation, which only accepts expressions. The
+	/// position is the one of the statement keyword.
+	StatementInInterpolation { keyword: Keyword, pos: SourcePos },
+	/// Tokens left after the top-level block is complete, as in statements following a
+	/// top-level return. The token is the first one left.
+	TrailingInput { token: Token },
 	/// Invalid env-assign. This is a spurious error while parsing, and should be handled
 	/// internally.
 	InvalidEnvAssign,
 }
 
 
+/// A parser error.
+#[derive(Debug)]
+pub struct Error {
+	pub kind: ErrorKind,
+	/// Where the error occurred.
+	pub context: ParseContext,
+	/// The tokens involved in the failed construct, from its first token up to the token
+	/// where it failed, if known.
+	pub span: Option<Span>,
+}
+
+
 impl Error {
 	/// Create an error signaling unexpected EOF.
 	pub fn unexpected_eof() -> Self {
-		Self::UnexpectedEof
+		ErrorKind::UnexpectedEof.into()
 	}
 
 
 	/// Create an error signaling an unexpected token, and what was expected.
 	pub fn unexpected(token: Token, expected: TokenKind) -> Self {
-		Self::Unexpected { token, expected: Expected::Token(expected) }
+		ErrorKind::Unexpected { token, expected: Expected::Token(expected) }.into()
 	}
 
 
 	/// Create an error signaling an unexpected token, and a message.
 	pub fn unexpected_msg(token: Token, message: &'static str) -> Self {
-		Self::Unexpected { token, expected: Expected::Message(message) }
+		ErrorKind::Unexpected { token, expected: Expected::Message(message) }.into()
+	}
+
+
+	/// Create an error signaling tokens were left after the top-level block, starting at
+	/// the given token.
+	pub fn trailing_input(token: Token) -> Self {
+		ErrorKind::TrailingInput { token }.into()
 	}
 
 
 	/// Create an error signaling a command block is empty.
 	pub fn empty_command_block(pos: SourcePos) -> Self {
-		Self::EmptyCommandBlock { pos }
+		ErrorKind::EmptyCommandBlock { pos }.into()
+	}
+
+
+	/// Create an error signaling an argument has no parts.
+	pub fn empty_argument(pos: SourcePos) -> Self {
+		ErrorKind::EmptyArgument { pos }.into()
+	}
+
+
+	/// Create an error signaling a command has an empty program name.
+	pub fn empty_program(pos: SourcePos) -> Self {
+		ErrorKind::EmptyProgram { pos }.into()
+	}
+
+
+	/// Create an error signaling an assignment operator where the equality operator was
+	/// expected.
+	pub fn assign_as_equality(pos: SourcePos) -> Self {
+		ErrorKind::AssignAsEquality { pos }.into()
+	}
+
+
+	/// Create an error signaling a conditional operator without a colon, given the position
+	/// of the question mark.
+	pub fn detached_try(pos: SourcePos) -> Self {
+		ErrorKind::DetachedTry { pos }.into()
+	}
+
+
+	/// Create an error signaling a multiple assignment with different numbers of targets and
+	/// values.
+	pub fn assign_count_mismatch(targets: usize, values: usize, pos: SourcePos) -> Self {
+		ErrorKind::AssignCountMismatch { targets, values, pos }.into()
+	}
+
+
+	/// Create an error signaling a required parameter following a defaulted one.
+	pub fn required_after_default(pos: SourcePos) -> Self {
+		ErrorKind::RequiredAfterDefault { pos }.into()
+	}
+
+
+	/// Create an error signaling a statement embedded in a string interpolation.
+	pub fn statement_in_interpolation(keyword: Keyword, pos: SourcePos) -> Self {
+		ErrorKind::StatementInInterpolation { keyword, pos }.into()
+	}
+
+
+	/// Create an error signaling the maximum nesting depth was exceeded.
+	pub fn nesting_too_deep(pos: SourcePos) -> Self {
+		ErrorKind::NestingTooDeep { pos }.into()
+	}
+
+
+	/// Create an error signaling premature EOF inside a construct, given the position of
+	/// its opener.
+	pub fn unterminated(construct: &'static str, pos: SourcePos) -> Self {
+		ErrorKind::Unterminated { construct, pos }.into()
+	}
+
+
+	/// Create an error signaling a match arm duplicates the pattern of the arm in the
+	/// given position.
+	pub fn duplicate_match_arm(pos: SourcePos, first: SourcePos) -> Self {
+		ErrorKind::DuplicateMatchArm { pos, first }.into()
+	}
+
+
+	/// The position of the error, if known. The parser doesn't track the position of the
+	/// end of file.
+	pub fn pos(&self) -> Option<SourcePos> {
+		match &self.kind {
+			ErrorKind::Unexpected { token, .. } | ErrorKind::TrailingInput { token } => Some(token.pos),
+			ErrorKind::EmptyCommandBlock { pos }
+			| ErrorKind::EmptyArgument { pos }
+			| ErrorKind::EmptyProgram { pos }
+			| ErrorKind::AssignAsEquality { pos }
+			| ErrorKind::DetachedTry { pos }
+			| ErrorKind::AssignCountMismatch { pos, .. }
+			| ErrorKind::RequiredAfterDefault { pos }
+			| ErrorKind::StatementInInterpolation { pos, .. }
+			| ErrorKind::NestingTooDeep { pos }
+			| ErrorKind::Unterminated { pos, .. }
+			| ErrorKind::DuplicateMatchArm { pos, .. } => Some(*pos),
+			ErrorKind::UnexpectedEof | ErrorKind::InvalidEnvAssign => None,
+		}
+	}
+
+
+	/// The same error, in the given context.
+	pub fn in_context(self, context: ParseContext) -> Self {
+		Self { context, ..self }
+	}
+
+
+	/// The same error, covering the given span. The innermost construct takes precedence,
+	/// so an error that already has a span is kept as is.
+	pub fn with_span(self, span: Span) -> Self {
+		Self { span: self.span.or(Some(span)), ..self }
+	}
+}
+
+
+impl From<ErrorKind> for Error {
+	fn from(kind: ErrorKind) -> Self {
+		Self { kind, context: ParseContext::default(), span: None }
 	}
 }
 
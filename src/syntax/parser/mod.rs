@@ -1,16 +1,26 @@
+// Parser errors carry the offending token, which makes them large.
+#![allow(clippy::result_large_err)]
+
 mod command;
 mod error;
 mod sync;
+#[cfg(test)]
+mod tests;
 
-use std::iter::Peekable;
+use std::{
+	collections::{hash_map::Entry, HashMap},
+	iter::Peekable,
+};
 
 use super::{
 	SourcePos,
-	ast,
+	Span,
+	ast::{self, constants::Constant, IllFormed},
 	lexer::{
 		ArgPart,
 		ArgUnit,
 		Keyword,
+		Literal,
 		Token,
 		TokenKind,
 		Operator,
@@ -18,7 +28,7 @@ use super::{
 	}
 };
 use sync::{ResultExt, WithSync, Synchronizable};
-pub use error::Error;
+pub use error::{Error, ErrorKind, ParseContext};
 
 
 /// The parser may report multiple errors before finishing. Instead of allocating those in
@@ -38,6 +48,114 @@ where
 }
 
 
+/// For the common case, errors may be simply collected in a vector.
+impl ErrorReporter for Vec<Error> {
+	fn report(&mut self, error: Error) {
+		self.push(error)
+	}
+}
+
+
+/// An error reporter adaptor that suppresses consecutive identical errors, such as the
+/// cascade produced by a single malformed token. Errors without a position, such as
+/// premature EOF, are never suppressed, as their occurrences can't be told apart.
+#[derive(Debug)]
+pub struct DedupReporter<E> {
+	inner: E,
+	/// The kind and context of the last forwarded error, if it has a position.
+	last: Option<(ErrorKind, ParseContext)>,
+	suppressed: usize,
+}
+
+
+impl<E> DedupReporter<E> {
+	pub fn new(inner: E) -> Self {
+		Self { inner, last: None, suppressed: 0 }
+	}
+
+
+	/// The number of suppressed duplicate errors.
+	pub fn suppressed(&self) -> usize {
+		self.suppressed
+	}
+
+
+	/// Get the inner reporter.
+	pub fn into_inner(self) -> E {
+		self.inner
+	}
+}
+
+
+impl<E> ErrorReporter for DedupReporter<E>
+where
+	E: ErrorReporter,
+{
+	fn report(&mut self, error: Error) {
+		let key = error.pos().map(|_| (error.kind.clone(), error.context));
+
+		if key.is_some() && self.last == key {
+			self.suppressed += 1;
+		} else {
+			self.last = key;
+			self.inner.report(error);
+		}
+	}
+}
+
+
+/// Parser configuration.
+#[derive(Debug, Clone)]
+pub struct Config {
+	/// The maximum nesting depth of statements and expressions. Deeper constructs are
+	/// reported as errors instead of overflowing the stack.
+	pub max_depth: usize,
+	/// The tokens where recovery resumes after an ill-formed statement. The offending
+	/// token is always skipped, and then every token up to one of these. If empty, which
+	/// is the default, only the offending token is skipped.
+	pub sync_tokens: Vec<TokenKind>,
+	/// Whether to accept loops with a single statement body, introduced by a colon instead
+	/// of `do ... end`: `while cond: stmt` and `for x in xs: stmt`.
+	pub single_line_loops: bool,
+	/// Whether to ignore the tokens left after the top-level block is complete, as in
+	/// statements following a top-level return. Otherwise, they are reported as an error.
+	pub ignore_trailing_input: bool,
+}
+
+
+/// Synchronization tokens that resume recovery at the next keyword statement: the
+/// statement-starting keywords and block terminators. Statements that don't start with a
+/// keyword, such as calls and assignments, are skipped when recovering with this set.
+pub const STATEMENT_SYNC_TOKENS: &[TokenKind] = &[
+	TokenKind::Keyword(Keyword::Let),
+	TokenKind::Keyword(Keyword::Const),
+	TokenKind::Keyword(Keyword::For),
+	TokenKind::Keyword(Keyword::While),
+	TokenKind::Keyword(Keyword::Return),
+	TokenKind::Keyword(Keyword::Break),
+	TokenKind::Keyword(Keyword::Continue),
+	TokenKind::Keyword(Keyword::Begin),
+	TokenKind::Keyword(Keyword::Function),
+	TokenKind::Keyword(Keyword::Assert),
+	TokenKind::Keyword(Keyword::End),
+	TokenKind::Keyword(Keyword::Else),
+	TokenKind::Keyword(Keyword::ElseIf),
+	TokenKind::Keyword(Keyword::With),
+];
+
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			max_depth: 128,
+			sync_tokens: Vec::new(),
+			single_line_loops: false,
+			ignore_trailing_input: false,
+		}
+	}
+}
+
+
 /// The parser for Hush syntax.
 #[derive(Debug)]
 pub struct Parser<I, E>
@@ -49,6 +167,28 @@ where
 	cursor: Peekable<I>,
 	token: Option<Token>,
 	error_reporter: E,
+	config: Config,
+	/// The current nesting depth, shared by all recursive constructs.
+	depth: usize,
+	/// How many expressions are currently being parsed.
+	expr_depth: usize,
+	/// The expression depth in which an assignment operator is expected, if any.
+	/// Anywhere else, an assignment operator is a typo for the equality operator.
+	assign_depth: Option<usize>,
+	/// The expression depth in which a colon ends the expression, if any. Anywhere else, a
+	/// colon after a command block starts its timeout.
+	colon_depth: Option<usize>,
+	/// Whether the parser is in an expression or in a command block.
+	context: ParseContext,
+	/// The position of the most recently read token, which is kept after EOF is reached.
+	last_pos: Option<SourcePos>,
+	/// The end of the current token, which is kept even if the token is taken.
+	token_end: Option<SourcePos>,
+	/// The end of the most recently consumed token, which is where spans end.
+	last_end: Option<SourcePos>,
+	/// The comments skipped so far and not yet attached, which are only present in the
+	/// input if the lexer preserves comments.
+	comments: Vec<(SourcePos, Box<[u8]>)>,
 }
 
 
@@ -58,22 +198,85 @@ where
 	E: ErrorReporter,
 {
 	/// Create a new parser for the given input.
-	pub fn new(mut cursor: I, error_reporter: E) -> Self {
-		let token = cursor.next();
+	pub fn new(cursor: I, error_reporter: E) -> Self {
+		Self::with_config(cursor, error_reporter, Config::default())
+	}
+
+
+	/// Create a new parser for the given input, using the given configuration.
+	pub fn with_config(cursor: I, error_reporter: E, config: Config) -> Self {
+		let mut parser = Self {
+			cursor: cursor.peekable(),
+			token: None,
+			error_reporter,
+			config,
+			depth: 0,
+			expr_depth: 0,
+			assign_depth: None,
+			colon_depth: None,
+			context: ParseContext::default(),
+			last_pos: None,
+			token_end: None,
+			last_end: None,
+			comments: Vec::new(),
+		};
 
-		Self { cursor: cursor.peekable(), token, error_reporter }
+		parser.step();
+
+		parser
 	}
 
 
 	/// Peek the next token.
 	fn peek(&mut self) -> Option<&Token> {
+		self.skip_comments();
 		self.cursor.peek()
 	}
 
 
 	/// Step the cursor, placing the next token on self.token.
 	fn step(&mut self) {
+		self.last_end = self.token_end.or(self.last_end);
+
+		self.skip_comments();
 		self.token = self.cursor.next();
+		self.token_end = self.token.as_ref().map(|token| token.end);
+
+		if let Some(token) = &self.token {
+			self.last_pos = Some(token.pos);
+		}
+	}
+
+
+	/// Skip the comments in the cursor, keeping them to be attached.
+	fn skip_comments(&mut self) {
+		while let Some(Token { kind: TokenKind::Comment(_), .. }) = self.cursor.peek() {
+			if let Some(Token { kind: TokenKind::Comment(text), pos, .. }) = self.cursor.next() {
+				self.comments.push((pos, text));
+			}
+		}
+	}
+
+
+	/// Attach the comments skipped since the given mark to the given items, removing them.
+	/// A comment is attached to the last item before it, if they are on the same line.
+	fn attach_comments<T, F>(&mut self, mark: usize, items: &mut [T], mut slot: F)
+	where
+		F: FnMut(&mut T) -> (SourcePos, &mut Option<Box<[u8]>>),
+	{
+		for (pos, text) in self.comments.drain(mark ..) {
+			let item = items
+				.iter_mut()
+				.rev()
+				.map(&mut slot)
+				.find(|(item_pos, _)| item_pos.offset < pos.offset);
+
+			if let Some((item_pos, comment)) = item {
+				if item_pos.line == pos.line && comment.is_none() {
+					*comment = Some(text);
+				}
+			}
+		}
 	}
 
 
@@ -102,15 +305,68 @@ where
 	}
 
 
+	/// The span from the given start up to the end of the most recently consumed token.
+	fn span_from(&self, start: SourcePos) -> Span {
+		Span { start, end: self.last_end.unwrap_or(start) }
+	}
+
+
+	/// The span from the start of the given expression up to the end of the most recently
+	/// consumed token. The given position is used as the start if the expression is
+	/// ill-formed.
+	fn span_after(&self, expr: &ast::Expr, pos: SourcePos) -> Span {
+		self.span_from(expr.span().map_or(pos, |span| span.start))
+	}
+
+
 	/// Consume the expected token, or produce an error.
 	fn expect(&mut self, expected: TokenKind) -> Result<SourcePos, Error> {
 		self.eat(|token| match token {
-			Token { kind: token, pos } if token == expected => Ok(pos),
+			Token { kind: token, pos, .. } if token == expected => Ok(pos),
 			token => Err((Error::unexpected(token.clone(), expected), token)),
 		})
 	}
 
 
+	/// Parse a nested construct, failing if the maximum nesting depth is exceeded.
+	/// Errors from the construct are given a span from its first token up to the token where
+	/// it failed.
+	fn nested<P, R>(&mut self, parse: P) -> sync::Result<R, Error>
+	where
+		P: FnOnce(&mut Self) -> sync::Result<R, Error>,
+	{
+		if self.depth >= self.config.max_depth {
+			if let Some(Token { pos, .. }) = &self.token {
+				return Err(Error::nesting_too_deep(*pos))
+					.with_sync(sync::Strategy::balanced());
+			}
+		}
+
+		let start = self.last_pos;
+
+		self.depth += 1;
+		let mut result = parse(self);
+		self.depth -= 1;
+
+		// The error is updated in place, as moving results around would grow the stack
+		// frame of every level of nesting.
+		if let Err((error, _)) = &mut result {
+			self.span_error(error, start);
+		}
+
+		result
+	}
+
+
+	/// Give an error the span from the given start position up to the end of the last read
+	/// token.
+	fn span_error(&self, error: &mut Error, start: Option<SourcePos>) {
+		if let Some((start, end)) = start.zip(self.token_end.or(self.last_end)) {
+			error.span.get_or_insert(Span::point(start).merge(end.into()));
+		}
+	}
+
+
 	/// Items divided by a separator.
 	/// A ending trailing separator is optional.
 	fn sep_by<P, R, Sep, End>(&mut self, mut parse: P, mut sep: Sep, end: End) -> Box<[R]>
@@ -167,12 +423,29 @@ where
 }
 
 
+impl<I> Parser<I, Vec<Error>>
+where
+	I: Iterator<Item = Token>,
+{
+	/// Parse the input, producing a top-level block and the collected errors.
+	pub fn into_errors(mut self) -> (ast::Block, Vec<Error>) {
+		let block = self.parse_top_level();
+		(block, self.error_reporter)
+	}
+}
+
+
 impl<I, E> Synchronizable<Error> for Parser<I, E>
 where
 	I: Iterator<Item = Token>,
 	E: ErrorReporter,
 {
 	fn synchronize(&mut self, error: Error, mut strategy: sync::Strategy) {
+		let error = match self.context {
+			ParseContext::Expression => error,
+			ParseContext::Command => error.in_context(ParseContext::Command),
+		};
+
 		self.error_reporter.report(error);
 
 		while let Some(Token { kind: token, .. }) = &self.token {
@@ -193,6 +466,13 @@ where
 {
 	/// Parse the input, producing a top-level block.
 	pub fn parse(mut self) -> ast::Block {
+		self.parse_top_level()
+	}
+
+
+	/// Parse the input until EOF or until the top-level block is complete, producing a
+	/// top-level block.
+	fn parse_top_level(&mut self) -> ast::Block {
 		loop {
 			let block = self.parse_block();
 
@@ -202,22 +482,28 @@ where
 				Some(token) if token.kind.is_block_terminator() => {
 					Err(Error::unexpected_msg(token, "statement"))
 						.with_sync(sync::Strategy::skip_one())
-						.synchronize(&mut self)
+						.synchronize(self)
 				}
 
 				// Stop on EOF.
 				None => return block,
 
-				token => {
-					self.token = token;
+				// The block is complete after a return or continue, and the remaining tokens
+				// are not parsed.
+				Some(token) => {
+					if !self.config.ignore_trailing_input {
+						self.error_reporter.report(Error::trailing_input(token));
+					}
+
+					return block;
 				},
 			}
 		}
 	}
 
 
-	/// Parse a block of statements, stopping when ELSE, ELSEIF, END of EOF are reached, or after a
-	/// return is parsed. The Lua-like grammar requires stopping after such conditions.
+	/// Parse a block of statements, stopping when ELSE, ELSEIF, WITH, END of EOF are reached, or
+	/// after a return is parsed. The Lua-like grammar requires stopping after such conditions.
 	/// This method synchronizes on all errors, producing an empty block if no statements
 	/// can be parsed.
 	fn parse_block(&mut self) -> ast::Block {
@@ -233,16 +519,19 @@ where
 
 				Some(_) => {
 					let statement = self
-						.parse_statement()
-						.force_sync_skip() // Prevent the parser from getting stuck.
+						.nested(Self::parse_statement)
+						.force_sync_tokens(&self.config.sync_tokens) // Prevent the parser from getting stuck.
 						.synchronize(self);
 
-					let is_return = matches!(statement, ast::Statement::Return { .. });
+					let is_terminal = matches!(
+						statement,
+						ast::Statement::Return { .. } | ast::Statement::Continue { .. }
+					);
 
 					block.push(statement);
 
-					if is_return {
-						// There may be no statements following a return in a block.
+					if is_terminal {
+						// There may be no statements following a return or continue in a block.
 						break;
 					}
 				}
@@ -253,19 +542,122 @@ where
 	}
 
 
+	/// Parse the condition or the iterable of a loop, which is followed by the colon of a
+	/// single line body if those are enabled.
+	fn parse_loop_header(&mut self) -> sync::Result<ast::Expr, Error> {
+		if self.config.single_line_loops {
+			self.parse_expression_before_colon()
+		} else {
+			self.parse_expression()
+		}
+	}
+
+
+	/// Parse the body of a loop: `do block end`, or `: statement` if single line loops are
+	/// enabled.
+	fn parse_loop_body(&mut self) -> sync::Result<ast::Block, Error> {
+		if self.config.single_line_loops {
+			if let Some(Token { kind: TokenKind::Colon, .. }) = &self.token {
+				self.step();
+
+				// Don't synchronize here because the statement is the last part of the loop.
+				let statement = self.nested(Self::parse_statement)?;
+
+				return Ok(ast::Block::Block(Box::new([statement])));
+			}
+		}
+
+		self.expect(TokenKind::Keyword(Keyword::Do))
+			.with_sync(sync::Strategy::keep())
+			.synchronize(self);
+
+		let block = self.parse_block();
+
+		self.expect(TokenKind::Keyword(Keyword::End))
+			.with_sync(sync::Strategy::keyword(Keyword::End))?;
+
+		Ok(block)
+	}
+
+
 	/// Parse a single statement.
 	fn parse_statement(&mut self) -> sync::Result<ast::Statement, Error> {
 		match self.token.take() {
+			// Let dict destructuring.
+			Some(Token { kind: TokenKind::Keyword(Keyword::Let), pos, .. })
+				if matches!(self.peek(), Some(Token { kind: TokenKind::OpenDict, .. })) => {
+					self.step();
+					self.step(); // Skip the @[.
+
+					let fields = self.comma_sep(
+						Self::parse_field_binding,
+						|token| *token == TokenKind::CloseBracket,
+					);
+
+					self.expect(TokenKind::CloseBracket)
+						.with_sync(sync::Strategy::token(TokenKind::CloseBracket))
+						.synchronize(self);
+
+					self.expect(TokenKind::Operator(Operator::Assign))
+						.with_sync(sync::Strategy::keep())
+						.synchronize(self);
+
+					// Don't synchronize here because this expression is the last part of the statement.
+					let init = self.parse_expression()?;
+
+					Ok(ast::Statement::LetDict { fields, init, pos, span: self.span_from(pos) })
+				}
+
+			// Let array destructuring.
+			Some(Token { kind: TokenKind::Keyword(Keyword::Let), pos, .. })
+				if matches!(self.peek(), Some(Token { kind: TokenKind::OpenBracket, .. })) => {
+					self.step();
+					self.step(); // Skip the [.
+
+					let items = self.comma_sep(
+						Self::parse_item_binding,
+						|token| *token == TokenKind::CloseBracket,
+					);
+
+					self.expect(TokenKind::CloseBracket)
+						.with_sync(sync::Strategy::token(TokenKind::CloseBracket))
+						.synchronize(self);
+
+					self.expect(TokenKind::Operator(Operator::Assign))
+						.with_sync(sync::Strategy::keep())
+						.synchronize(self);
+
+					// Don't synchronize here because this expression is the last part of the statement.
+					let init = self.parse_expression()?;
+
+					Ok(ast::Statement::LetArray { items, init, pos, span: self.span_from(pos) })
+				}
+
 			// Let.
-			Some(Token { kind: TokenKind::Keyword(Keyword::Let), .. }) => {
+			Some(Token { kind: TokenKind::Keyword(keyword @ (Keyword::Let | Keyword::Const)), pos: start, .. }) => {
 				self.step();
 
+				let is_const = keyword == Keyword::Const;
+
 				let (identifier, pos) = self
 					.parse_identifier()
 					.synchronize(self);
 
+				let annotation = self.parse_annotation();
+
 				let init =
-					if matches!(self.token, Some(Token { kind: TokenKind::Operator(Operator::Assign), .. })) {
+					if is_const {
+						// Constants must be initialized. Without the assignment, the initializer is
+						// ill-formed, and the following tokens are left for the next statement.
+						match self.expect(TokenKind::Operator(Operator::Assign)) {
+							// Don't synchronize here because this expression is the last part of the statement.
+							Ok(_) => self.parse_expression()?,
+							Err(error) => {
+								Synchronizable::synchronize(self, error, sync::Strategy::keep());
+								ast::Expr::ill_formed()
+							}
+						}
+					} else if matches!(self.token, Some(Token { kind: TokenKind::Operator(Operator::Assign), .. })) {
 						self.step();
 						// Don't synchronize here because this expression is the last part of the statement.
 						self.parse_expression()?
@@ -273,14 +665,15 @@ where
 						ast::Expr::Literal {
 							literal: ast::Literal::default(),
 							pos,
+							span: Span::point(pos),
 						}
 					};
 
-				Ok(ast::Statement::Let { identifier, init, pos })
+				Ok(ast::Statement::Let { identifier, annotation, init, is_const, pos, span: self.span_from(start) })
 			}
 
 			// Let function.
-			Some(Token { kind: TokenKind::Keyword(Keyword::Function), pos })
+			Some(Token { kind: TokenKind::Keyword(Keyword::Function), pos, .. })
 				if matches!(self.peek(), Some(Token { kind: TokenKind::Identifier(_), .. })) => {
 					self.step();
 
@@ -289,19 +682,23 @@ where
 						.parse_identifier()
 						.expect("there should be an identifier");
 
-					let (params, body) = self.parse_function()?;
+					let literal = self.parse_function(pos)?;
+					let span = self.span_from(pos);
 
 					Ok(
 						ast::Statement::Let {
 							identifier,
-							init: ast::Expr::Literal { literal: ast::Literal::Function { params, body }, pos },
+							annotation: None,
+							init: ast::Expr::Literal { literal, pos, span },
+							is_const: false,
 							pos: id_pos,
+							span,
 						}
 					)
 				}
 
 			// Return.
-			Some(Token { kind: TokenKind::Keyword(Keyword::Return), pos }) => {
+			Some(Token { kind: TokenKind::Keyword(Keyword::Return), pos, .. }) => {
 				self.step();
 
 				// Don't synchronize here because this expression is the last part of the statement.
@@ -309,42 +706,51 @@ where
 					Some(Token { kind, .. }) if kind.is_block_terminator() => ast::Expr::Literal {
 						literal: ast::Literal::Nil,
 						pos,
+						span: Span::point(pos),
 					},
 
-					_ => self.parse_expression()?,
+					_ => self.parse_return_values(pos)?,
 				};
 
-				Ok(ast::Statement::Return { expr, pos })
+				Ok(ast::Statement::Return { expr, pos, span: self.span_from(pos) })
 			}
 
 			// Break.
-			Some(Token { kind: TokenKind::Keyword(Keyword::Break), pos }) => {
+			Some(Token { kind: TokenKind::Keyword(Keyword::Break), pos, .. }) => {
 				self.step();
 
-				Ok(ast::Statement::Break { pos })
+				// Don't synchronize here because this expression is the last part of the statement.
+				// As break doesn't end the block, the value must start in the same line, or else
+				// the following statement would be taken as the value.
+				let expr = match &self.token {
+					Some(Token { kind, pos: value_pos, .. })
+						if kind.is_expr_starter() && value_pos.line == pos.line => Some(self.parse_expression()?),
+					_ => None,
+				};
+
+				Ok(ast::Statement::Break { expr, pos, span: self.span_from(pos) })
 			}
 
-			// While.
-			Some(Token { kind: TokenKind::Keyword(Keyword::While), pos }) => {
+			// Continue.
+			Some(Token { kind: TokenKind::Keyword(Keyword::Continue), pos, .. }) => {
 				self.step();
+				Ok(ast::Statement::Continue { pos, span: self.span_from(pos) })
+			}
 
-				let condition = self.parse_expression()
-					.synchronize(self);
+			// While.
+			Some(Token { kind: TokenKind::Keyword(Keyword::While), pos, .. }) => {
+				self.step();
 
-				self.expect(TokenKind::Keyword(Keyword::Do))
-					.with_sync(sync::Strategy::keep())
+				let condition = self.parse_loop_header()
 					.synchronize(self);
 
-				let block = self.parse_block();
-
-				self.expect(TokenKind::Keyword(Keyword::End))
-					.with_sync(sync::Strategy::keyword(Keyword::End))?;
+				let block = self.parse_loop_body()?;
 
-				Ok(ast::Statement::While { condition, block, pos })
+				Ok(ast::Statement::While { condition, block, pos, span: self.span_from(pos) })
 			}
 
 			// For.
-			Some(Token { kind: TokenKind::Keyword(Keyword::For), .. }) => {
+			Some(Token { kind: TokenKind::Keyword(Keyword::For), pos: start, .. }) => {
 				self.step();
 
 				let (identifier, pos) = self.parse_identifier()
@@ -354,41 +760,99 @@ where
 					.with_sync(sync::Strategy::skip_one())
 					.synchronize(self);
 
-				let expr = self.parse_expression()
+				let expr = self.parse_loop_header()
 					.synchronize(self);
 
-				self.expect(TokenKind::Keyword(Keyword::Do))
-					.with_sync(sync::Strategy::keep())
-					.synchronize(self);
+				let block = self.parse_loop_body()?;
+
+				Ok(ast::Statement::For { identifier, expr, block, pos, span: self.span_from(start) })
+			}
+
+			// Scope.
+			Some(Token { kind: TokenKind::Keyword(Keyword::Begin), pos, .. }) => {
+				self.step();
 
 				let block = self.parse_block();
 
 				self.expect(TokenKind::Keyword(Keyword::End))
+					.map_err(
+						|error| match error.kind {
+							ErrorKind::UnexpectedEof => Error::unterminated("scope", pos),
+							_ => error,
+						}
+					)
 					.with_sync(sync::Strategy::keyword(Keyword::End))?;
 
-				Ok(ast::Statement::For { identifier, expr, block, pos })
+				Ok(ast::Statement::Scope { block, pos, span: self.span_from(pos) })
+			}
+
+			// Assert.
+			Some(Token { kind: TokenKind::Keyword(Keyword::Assert), pos, .. }) => {
+				self.step();
+
+				// Don't synchronize here because these expressions are the last part of the
+				// statement.
+				let condition = self.parse_expression()?;
+
+				let message = match self.token {
+					Some(Token { kind: TokenKind::Comma, .. }) => {
+						self.step();
+						Some(self.parse_expression()?)
+					}
+					_ => None,
+				};
+
+				Ok(ast::Statement::Assert { condition, message, pos, span: self.span_from(pos) })
 			}
 
 			// Expr.
 			Some(token) => {
 				self.token = Some(token);
 
+				// The assignment operator is only allowed after the top level expression.
+				let assign_depth = self.assign_depth.replace(self.expr_depth + 1);
+				let expr = self.parse_expression();
+				self.assign_depth = assign_depth;
+
 				// Don't synchronize here because this expression may be the last part of the statement.
-				let expr = self.parse_expression()?;
+				let expr = expr?;
+
+				// Multiple assignment, as in `a, b = b, a`.
+				if matches!(self.token, Some(Token { kind: TokenKind::Comma, .. })) {
+					return self.parse_multi_assign(expr);
+				}
 
-				let pos = match &self.token {
-					Some(Token { kind: TokenKind::Operator(Operator::Assign), pos }) => Some(*pos),
+				// The binary operator of compound assignments, as in `x += 1`.
+				let assign = match &self.token {
+					Some(Token { kind: TokenKind::Operator(Operator::Assign), pos, .. }) => Some((None, *pos)),
+					Some(Token { kind: TokenKind::Operator(op), pos, .. }) => op
+						.compound_assign()
+						.map(|op| (Some(op), *pos)),
 					_ => None
 				};
 
-				if let Some(pos) = pos {
+				if let Some((op, pos)) = assign {
 					self.step();
 
 					// Don't synchronize here because this expression is the last part of the statement.
 					let right = self.parse_expression()?;
 
+					let span = self.span_after(&expr, pos);
+
+					// Compound assignments are desugared, so `x += 1` becomes `x = x + 1`.
+					let right = match op {
+						Some(op) => ast::Expr::BinaryOp {
+							left: Box::new(expr.clone()),
+							op: op.into(),
+							right: right.into(),
+							pos,
+							span,
+						},
+						None => right,
+					};
+
 					Ok(
-						ast::Statement::Assign { left: expr, right, pos }
+						ast::Statement::Assign { left: expr, right, pos, span }
 					)
 				} else {
 					Ok(ast::Statement::Expr(expr))
@@ -402,6 +866,45 @@ where
 	}
 
 
+	/// Parse the remainder of a multiple assignment, given the first target. The number of
+	/// targets and values must match.
+	fn parse_multi_assign(&mut self, first: ast::Expr) -> sync::Result<ast::Statement, Error> {
+		let mut left = vec![first];
+
+		while matches!(self.token, Some(Token { kind: TokenKind::Comma, .. })) {
+			self.step();
+
+			let assign_depth = self.assign_depth.replace(self.expr_depth + 1);
+			let target = self.parse_expression();
+			self.assign_depth = assign_depth;
+
+			left.push(target?);
+		}
+
+		let pos = self.expect(TokenKind::Operator(Operator::Assign))
+			.with_sync(sync::Strategy::keep())?;
+
+		let mut right = vec![self.parse_expression()?];
+
+		while matches!(self.token, Some(Token { kind: TokenKind::Comma, .. })) {
+			self.step();
+			// Don't synchronize here because this expression may be the last part of the statement.
+			right.push(self.parse_expression()?);
+		}
+
+		if left.len() != right.len() {
+			return Err(Error::assign_count_mismatch(left.len(), right.len(), pos))
+				.with_sync(sync::Strategy::keep());
+		}
+
+		let span = self.span_after(&left[0], pos);
+
+		Ok(
+			ast::Statement::MultiAssign { left: left.into(), right: right.into(), pos, span }
+		)
+	}
+
+
 	/// Parse a single expression.
 	fn parse_expression(&mut self) -> sync::Result<ast::Expr, Error> {
 		macro_rules! binop {
@@ -413,65 +916,238 @@ where
 		let parse_factor     = binop!(Self::parse_prefix, Operator::is_factor);
 		let parse_term       = binop!(parse_factor,     Operator::is_term);
 		let parse_concat     = binop!(parse_term,       |&op| op == Operator::Concat);
-		let parse_comparison = binop!(parse_concat,     Operator::is_comparison);
+		let parse_pipeline   = move |parser: &mut Self| parser.parse_pipeline(parse_concat);
+		let parse_comparison = binop!(parse_pipeline,   Operator::is_comparison);
 		let parse_equality   = binop!(parse_comparison, Operator::is_equality);
 		let parse_and        = binop!(parse_equality,   |&op| op == Operator::And);
 		let parse_or         = binop!(parse_and,        |&op| op == Operator::Or);
 
-		parse_or(self)
+		self.expr_depth += 1;
+		let expr = self.parse_ternary(parse_or);
+		self.expr_depth -= 1;
+
+		expr
 	}
 
 
-	/// Parse a higher precedence expression, optionally ending as a logical OR.
-	fn parse_binop<P, F>(
+	/// Parse an expression followed by a colon that belongs to the enclosing construct, as
+	/// in `c ? a : b`. A command block ending such an expression has no timeout, unless it
+	/// is parenthesized.
+	fn parse_expression_before_colon(&mut self) -> sync::Result<ast::Expr, Error> {
+		let colon_depth = self.colon_depth.replace(self.expr_depth + 1);
+		let expr = self.parse_expression();
+		self.colon_depth = colon_depth;
+
+		expr
+	}
+
+
+	/// Parse a range expression, optionally followed by a conditional operator, as in
+	/// `condition ? then : otherwise`. The conditional operator is right associative, and
+	/// is parsed as an if-else expression. Question marks that are try operators have
+	/// already been taken as postfix operators, see Parser::is_try_operator.
+	fn parse_ternary<P>(&mut self, parse_higher_prec_op: P) -> sync::Result<ast::Expr, Error>
+	where
+		P: FnMut(&mut Self) -> sync::Result<ast::Expr, Error> + Copy,
+	{
+		let condition = self.parse_range(parse_higher_prec_op)?;
+
+		match self.token {
+			Some(Token { kind: TokenKind::Operator(Operator::Try), pos, .. }) => {
+				self.step();
+				self.parse_conditional_op(condition, pos, parse_higher_prec_op)
+			}
+
+			_ => Ok(condition),
+		}
+	}
+
+
+	/// Parse the branches of a conditional operator, given its condition and the position
+	/// of the question mark.
+	fn parse_conditional_op<P>(
 		&mut self,
-		mut parse_higher_prec_op: P,
-		mut check: F,
+		condition: ast::Expr,
+		pos: SourcePos,
+		parse_higher_prec_op: P,
 	) -> sync::Result<ast::Expr, Error>
+	where
+		P: FnMut(&mut Self) -> sync::Result<ast::Expr, Error> + Copy,
+	{
+		let then = self.parse_expression_before_colon()
+			.synchronize(self);
+
+		let block = |expr| ast::Block::Block(Box::new([ast::Statement::Expr(expr)]));
+
+		// Without the colon, the question mark was likely meant as a try operator, so
+		// there's no else branch to parse.
+		let otherwise = match self.expect(TokenKind::Colon) {
+			Ok(_) => block(self.parse_ternary(parse_higher_prec_op)?),
+			Err(_) => {
+				self.error_reporter.report(Error::detached_try(pos));
+				ast::Block::IllFormed
+			}
+		};
+
+		Ok(ast::Expr::If {
+			span: self.span_after(&condition, pos),
+			condition: condition.into(),
+			then: block(then),
+			otherwise,
+			pos,
+		})
+	}
+
+
+	/// Parse a higher precedence expression, optionally followed by a range operator.
+	/// Ranges have the lowest precedence, and can't be chained.
+	fn parse_range<P>(&mut self, mut parse_higher_prec_op: P) -> sync::Result<ast::Expr, Error>
 	where
 		P: FnMut(&mut Self) -> sync::Result<ast::Expr, Error>,
-		F: FnMut(&Operator) -> bool,
 	{
-		let mut expr = parse_higher_prec_op(self)?;
+		let start = parse_higher_prec_op(self)?;
 
-		loop {
-			match self.token.take() {
-				Some(Token { kind: TokenKind::Operator(op), pos }) if check(&op) => {
+		match self.token {
+			Some(Token { kind: TokenKind::Operator(op), pos, .. })
+				if op == Operator::Range || op == Operator::RangeInclusive => {
 					self.step();
 
-					let right = parse_higher_prec_op(self)?;
+					let end = parse_higher_prec_op(self)?;
 
-					expr = ast::Expr::BinaryOp {
-						left: expr.into(),
-						op: op.into(),
-						right: right.into(),
-						pos,
-					};
+					Ok(self.range(start, end, op == Operator::RangeInclusive, pos))
 				}
 
-				token => {
-					self.token = token;
-					break;
-				}
-			}
+			_ => Ok(start),
 		}
-
-		Ok(expr)
 	}
 
 
-	/// Parse a higher precedence expression, optionally starting with a prefix operator.
-	fn parse_prefix(&mut self) -> sync::Result<ast::Expr, Error> {
-		match self.token.take() {
-			Some(Token { kind: TokenKind::Operator(op), pos }) if op.is_prefix() => {
-				self.step();
+	/// Build a range expression from its bounds.
+	fn range(&self, start: ast::Expr, end: ast::Expr, inclusive: bool, pos: SourcePos) -> ast::Expr {
+		ast::Expr::Range {
+			span: self.span_after(&start, pos),
+			start: start.into(),
+			end: end.into(),
+			inclusive,
+			pos,
+		}
+	}
 
-				let operand = self.parse_prefix()?;
 
-				Ok(ast::Expr::UnaryOp {
-					op: op.into(),
-					operand: operand.into(),
+	/// Parse a higher precedence expression, optionally ending as a logical OR.
+	fn parse_binop<P, F>(
+		&mut self,
+		mut parse_higher_prec_op: P,
+		mut check: F,
+	) -> sync::Result<ast::Expr, Error>
+	where
+		P: FnMut(&mut Self) -> sync::Result<ast::Expr, Error>,
+		F: FnMut(&Operator) -> bool,
+	{
+		let mut expr = parse_higher_prec_op(self)?;
+
+		while let Some((op, pos)) = self.binary_operator(&mut check) {
+			self.step();
+
+			let right = parse_higher_prec_op(self)?;
+
+			expr = ast::Expr::BinaryOp {
+				span: self.span_after(&expr, pos),
+				left: expr.into(),
+				op,
+				right: right.into(),
+				pos,
+			};
+		}
+
+		Ok(expr)
+	}
+
+
+	/// The binary operator in the current token, if accepted by the given check. This is
+	/// kept out of Parser::parse_binop, which is on the recursive path of expressions.
+	fn binary_operator<F>(&mut self, check: &mut F) -> Option<(ast::BinaryOp, SourcePos)>
+	where
+		F: FnMut(&Operator) -> bool,
+	{
+		match &self.token {
+			Some(Token { kind: TokenKind::Operator(op), pos, .. }) if check(op) => Some(((*op).into(), *pos)),
+
+			// Assignment where equality is expected, which is a common typo.
+			Some(Token { kind: TokenKind::Operator(Operator::Assign), pos, .. })
+				if check(&Operator::Equals) && self.assign_depth != Some(self.expr_depth) => {
+					self.error_reporter.report(Error::assign_as_equality(*pos));
+
+					// Recover as if it were the equality operator.
+					Some((ast::BinaryOp::Equals, *pos))
+				}
+
+			_ => None,
+		}
+	}
+
+
+	/// Parse a higher precedence expression, optionally followed by pipeline operators, as
+	/// in `x |> f |> g(2)`. The piped value is inserted as the first argument of the call
+	/// to the right, so the example is parsed as `g(f(x), 2)`. If the right side is not a
+	/// call, it is called with the piped value as the only argument. So is a parenthesized
+	/// call, as in `x |> (make(1))`.
+	fn parse_pipeline<P>(&mut self, mut parse_higher_prec_op: P) -> sync::Result<ast::Expr, Error>
+	where
+		P: FnMut(&mut Self) -> sync::Result<ast::Expr, Error>,
+	{
+		let mut expr = parse_higher_prec_op(self)?;
+
+		while let Some(Token { kind: TokenKind::Operator(Operator::Pipeline), pos, .. }) = self.token {
+			self.step();
+
+			let right = parse_higher_prec_op(self)?;
+
+			expr = self.pipe(expr, right, pos);
+		}
+
+		Ok(expr)
+	}
+
+
+	/// Build the call for a pipeline operator, given the piped value, the right side and
+	/// the position of the operator. Like Parser::binary_operator, this is kept out of the
+	/// recursive path.
+	fn pipe(&self, value: ast::Expr, right: ast::Expr, pos: SourcePos) -> ast::Expr {
+		let span = self.span_after(&value, pos);
+
+		match right {
+			ast::Expr::Call { function, args, pos, .. } => {
+				let args = std::iter::once(value)
+					.chain(args.into_vec()) // Use vec's owned iterator.
+					.collect();
+
+				ast::Expr::Call { function, args, pos, span }
+			}
+
+			function => ast::Expr::Call {
+				function: function.into(),
+				args: Box::new([value]),
+				pos,
+				span,
+			},
+		}
+	}
+
+
+	/// Parse a higher precedence expression, optionally starting with a prefix operator.
+	fn parse_prefix(&mut self) -> sync::Result<ast::Expr, Error> {
+		match self.token.take() {
+			Some(Token { kind: TokenKind::Operator(op), pos, .. }) if op.is_prefix() => {
+				self.step();
+
+				let operand = self.nested(Self::parse_prefix)?;
+
+				Ok(ast::Expr::UnaryOp {
+					op: op.into(),
+					operand: operand.into(),
 					pos,
+					span: self.span_from(pos),
 				})
 			}
 
@@ -485,76 +1161,48 @@ where
 
 	/// Parse a primary expression followed by a postfix operator.
 	fn parse_postfix(&mut self) -> sync::Result<ast::Expr, Error> {
-		let mut expr = self.parse_primary()?;
+		let mut expr = self.nested(Self::parse_primary)?;
 
 		loop {
 			match self.token.take() {
 				// Function call.
-				Some(Token { kind: TokenKind::OpenParens, pos }) => {
+				Some(Token { kind: TokenKind::OpenParens, pos, .. }) => {
 					self.step();
-
-					let args = self.comma_sep(
-						Self::parse_expression,
-						|token| *token == TokenKind::CloseParens,
-					);
-
-					self.expect(TokenKind::CloseParens)
-						.with_sync(sync::Strategy::token(TokenKind::CloseParens))?;
-
-					expr = ast::Expr::Call {
-						function: expr.into(),
-						args,
-						pos,
-					}
+					expr = self.parse_call(expr, pos)?;
 				},
 
 				// Subscript operator.
-				Some(Token { kind: TokenKind::OpenBracket, pos }) => {
+				Some(Token { kind: TokenKind::OpenBracket, pos, .. }) => {
 					self.step();
-
-					let field = self.parse_expression()
-						.synchronize(self);
-
-					self.expect(TokenKind::CloseBracket)
-						.with_sync(sync::Strategy::token(TokenKind::CloseBracket))?;
-
-					expr = ast::Expr::Access {
-						object: expr.into(),
-						field: field.into(),
-						pos,
-					}
+					expr = self.parse_access(expr, pos)?;
 				},
 
 				// Dot access operator.
-				Some(Token { kind: TokenKind::Operator(Operator::Dot), pos }) => {
+				Some(Token { kind: TokenKind::Operator(Operator::Dot), pos, .. }) => {
 					self.step();
 
-					// Here, the identifier is a literal, and not a variable name. Hence, `var.id`
-					// is equivalent to `var["id"]`, and not from `var[id]`.
-					let (identifier, id_pos) = self.parse_identifier()?;
-
-					let field = ast::Expr::Literal {
-						literal: ast::Literal::Identifier(identifier),
-						pos: id_pos,
-					};
+					let (name, name_pos) = self.parse_identifier()?;
 
-					expr = ast::Expr::Access {
+					expr = ast::Expr::Field {
+						span: self.span_after(&expr, pos),
 						object: expr.into(),
-						field: field.into(),
-						pos,
+						name,
+						pos: name_pos,
 					}
 				},
 
-				// Try operator.
-				Some(Token { kind: TokenKind::Operator(Operator::Try), pos }) => {
-					self.step();
+				// Try operator. See Parser::is_try_operator.
+				Some(Token { kind: TokenKind::Operator(Operator::Try), pos, .. })
+					if self.is_try_operator(pos) => {
+						self.step();
 
-					expr = ast::Expr::UnaryOp {
-						op: ast::UnaryOp::Try,
-						operand: Box::new(expr),
-						pos,
-					}
-				},
+						expr = ast::Expr::UnaryOp {
+							span: self.span_after(&expr, pos),
+							op: ast::UnaryOp::Try,
+							operand: Box::new(expr),
+							pos,
+						}
+					},
 
 				token => {
 					self.token = token;
@@ -567,82 +1215,127 @@ where
 	}
 
 
+	/// Check if the current question mark, in the given position, is a try operator rather
+	/// than a conditional operator. It is when attached to the operand, as in `result?`, or
+	/// when no expression follows in the same line, as the conditional operator requires a
+	/// branch, as in `result ?` at the end of a statement. A detached question mark followed
+	/// by an expression in the same line starts a conditional operator, which then requires
+	/// a colon.
+	fn is_try_operator(&mut self, pos: SourcePos) -> bool {
+		self.last_end.map(|end| end.offset) == Some(pos.offset)
+			|| !self.peek().is_some_and(|token| token.kind.is_expr_starter() && token.pos.line == pos.line)
+	}
+
+
+	/// Parse the arguments of a call to the given function, given the position of the
+	/// opening parenthesis.
+	fn parse_call(&mut self, function: ast::Expr, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		let args = self.comma_sep(
+			Self::parse_item,
+			|token| *token == TokenKind::CloseParens,
+		);
+
+		self.expect(TokenKind::CloseParens)
+			.with_sync(sync::Strategy::token(TokenKind::CloseParens))?;
+
+		Ok(ast::Expr::Call {
+			span: self.span_after(&function, pos),
+			function: function.into(),
+			args,
+			pos,
+		})
+	}
+
+
+	/// Parse the field of a subscript of the given object, given the position of the
+	/// opening bracket.
+	fn parse_access(&mut self, object: ast::Expr, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		let field = self.parse_expression()
+			.synchronize(self);
+
+		self.expect(TokenKind::CloseBracket)
+			.with_sync(sync::Strategy::token(TokenKind::CloseBracket))?;
+
+		Ok(ast::Expr::Access {
+			span: self.span_after(&object, pos),
+			object: object.into(),
+			field: field.into(),
+			pos,
+		})
+	}
+
+
+	/// Parse an array item or a call argument, which may be spread: `...expr`.
+	fn parse_item(&mut self) -> sync::Result<ast::Expr, Error> {
+		match self.token.take() {
+			Some(Token { kind: TokenKind::Operator(Operator::Spread), pos, .. }) => {
+				self.step();
+
+				let operand = self.parse_expression()?;
+
+				Ok(ast::Expr::Spread { operand: operand.into(), pos, span: self.span_from(pos) })
+			}
+
+			token => {
+				self.token = token;
+				self.parse_expression()
+			}
+		}
+	}
+
+
 	/// Parse a primary (highest precedence) expression.
 	fn parse_primary(&mut self) -> sync::Result<ast::Expr, Error> {
 		match self.token.take() {
 			// Identifier.
-			Some(Token { kind: TokenKind::Identifier(identifier), pos }) => {
+			Some(Token { kind: TokenKind::Identifier(identifier), pos, .. }) => {
 				self.step();
 
-				Ok(ast::Expr::Identifier { identifier, pos })
+				Ok(ast::Expr::Identifier { identifier, pos, span: self.span_from(pos) })
 			}
 
 			// Self.
-			Some(Token { kind: TokenKind::Keyword(Keyword::Self_), pos }) => {
+			Some(Token { kind: TokenKind::Keyword(Keyword::Self_), pos, .. }) => {
 				self.step();
 
-				Ok(ast::Expr::Self_ { pos })
+				Ok(ast::Expr::Self_ { pos, span: self.span_from(pos) })
 			}
 
 			// Basic literal.
-			Some(Token { kind: TokenKind::Literal(literal), pos }) => {
+			Some(Token { kind: TokenKind::Literal(literal), pos, .. }) => {
 				self.step();
 
-				Ok(ast::Expr::Literal { literal: literal.into(), pos })
+				Ok(ast::Expr::Literal { literal: literal.into(), pos, span: self.span_from(pos) })
 			}
 
-			// Array literal.
-			Some(Token { kind: TokenKind::OpenBracket, pos }) => {
+			// Interpolated string.
+			Some(Token { kind: TokenKind::StringHead(head), pos, .. }) => {
 				self.step();
 
-				let items = self.comma_sep(
-					Self::parse_expression,
-					|token| *token == TokenKind::CloseBracket,
-				);
+				self.parse_interpolation(head, pos)
+			}
 
-				self.expect(TokenKind::CloseBracket)
-					.with_sync(sync::Strategy::token(TokenKind::CloseBracket))?;
+			// Array literal or comprehension.
+			Some(Token { kind: TokenKind::OpenBracket, pos, .. }) => {
+				self.step();
 
-				Ok(ast::Expr::Literal {
-					literal: ast::Literal::Array(items),
-					pos,
-				})
+				self.parse_array(pos)
 			}
 
 			// Dict literal.
-			Some(Token { kind: TokenKind::OpenDict, pos }) => {
+			Some(Token { kind: TokenKind::OpenDict, pos, .. }) => {
 				self.step();
 
-				let items = self.comma_sep(
-					|parser| {
-						let key = parser.parse_identifier()
-							.with_sync(sync::Strategy::skip_one())
-							.synchronize(parser);
-
-						parser.expect(TokenKind::Colon)
-							.with_sync(sync::Strategy::keep())
-							.synchronize(parser);
-
-						let value = parser.parse_expression()?;
-
-						Ok((key, value))
-					},
-					|token| *token == TokenKind::CloseBracket,
-				);
-
-				self.expect(TokenKind::CloseBracket)
-					.with_sync(sync::Strategy::token(TokenKind::CloseBracket))?;
-
-				Ok(ast::Expr::Literal { literal: ast::Literal::Dict(items), pos })
+				self.parse_dict(pos)
 			}
 
 			// Function literal.
-			Some(Token { kind: TokenKind::Keyword(Keyword::Function), pos }) => {
+			Some(Token { kind: TokenKind::Keyword(Keyword::Function), pos, .. }) => {
 				self.step();
 
-				let (params, body) = self.parse_function()?;
+				let literal = self.parse_function(pos)?;
 
-				Ok(ast::Expr::Literal { literal: ast::Literal::Function { params, body }, pos })
+				Ok(ast::Expr::Literal { literal, pos, span: self.span_from(pos) })
 			}
 
 			// Command blocks.
@@ -655,40 +1348,39 @@ where
 
 				Ok(
 					ast::Expr::CommandBlock {
-						block,
-						pos
+						block: block.into(),
+						pos,
+						span: self.span_from(pos),
 					}
 				)
 			}
 
 			// If conditional.
-			Some(Token { kind: TokenKind::Keyword(Keyword::If), pos }) => {
+			Some(Token { kind: TokenKind::Keyword(Keyword::If), pos, .. }) => {
 				self.step();
 
-				let (condition, then, otherwise) = self.parse_condblock()?;
+				self.parse_if(pos)
+			}
 
-				self.expect(TokenKind::Keyword(Keyword::End))
-					.with_sync(sync::Strategy::keyword(Keyword::End))?;
+			// Guarded value. The value is a full expression, hence the lowest precedence.
+			Some(Token { kind: TokenKind::Keyword(Keyword::When), pos, .. }) => {
+				self.step();
 
-				Ok(ast::Expr::If {
-					condition: condition.into(),
-					then,
-					otherwise,
-					pos,
-				})
+				self.parse_when(pos)
 			}
 
-			// Parenthesis.
-			Some(Token { kind: TokenKind::OpenParens, .. }) => {
+			// Match.
+			Some(Token { kind: TokenKind::Keyword(Keyword::Match), pos, .. }) => {
 				self.step();
 
-				let expr = self.parse_expression()
-					.synchronize(self);
+				self.parse_match(pos)
+			}
 
-				self.expect(TokenKind::CloseParens)
-					.with_sync(sync::Strategy::token(TokenKind::CloseParens))?;
+			// Parenthesis.
+			Some(Token { kind: TokenKind::OpenParens, pos, .. }) => {
+				self.step();
 
-				Ok(expr)
+				self.parse_parenthesized(pos)
 			}
 
 			// Some other unexpected token.
@@ -704,12 +1396,349 @@ where
 	}
 
 
+	/// Parse the rest of an interpolated string, given its head and the position of the
+	/// string. The string is folded into a concatenation of its parts, as in `"a ${x} b"` to
+	/// `"a " ++ x ++ " b"`. Empty parts are omitted, except for the first one, which ensures
+	/// the result is a string concatenation.
+	fn parse_interpolation(&mut self, head: Box<[u8]>, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		let concat = |left, right, span| ast::Expr::BinaryOp {
+			left: Box::new(left),
+			op: ast::BinaryOp::Concat,
+			right: Box::new(right),
+			pos,
+			span,
+		};
+
+		let mut expr = ast::Expr::Literal {
+			literal: ast::Literal::String(head),
+			pos,
+			span: self.span_from(pos),
+		};
+
+		loop {
+			// Only expressions may be embedded. Statements such as return are reported
+			// once, skipping the rest of the embedded tokens.
+			let embedded = match &self.token {
+				Some(Token { kind: TokenKind::Keyword(keyword), pos, .. }) if keyword.is_statement_starter() => {
+					Err(Error::statement_in_interpolation(*keyword, *pos))
+						.with_sync(sync::Strategy::interpolation_end())
+				}
+
+				_ => self.parse_expression()
+					.with_sync(sync::Strategy::interpolation_end()),
+			};
+
+			let embedded = embedded.synchronize(self);
+
+			expr = concat(expr, embedded, self.span_from(pos));
+
+			let (part, part_pos, more) = self
+				.eat(
+					|token| match token {
+						Token { kind: TokenKind::StringMiddle(part), pos, .. } => Ok((part, pos, true)),
+						Token { kind: TokenKind::StringTail(part), pos, .. } => Ok((part, pos, false)),
+						token => Err((Error::unexpected_msg(token.clone(), "closing brace"), token)),
+					}
+				)
+				.map_err(
+					// Point to the opener, as the end of file is far from the actual error.
+					|error| match error.kind {
+						ErrorKind::UnexpectedEof => Error::unterminated("string interpolation", pos),
+						_ => error,
+					}
+				)
+				.with_sync(sync::Strategy::keep())?;
+
+			if !part.is_empty() {
+				let literal = ast::Expr::Literal {
+					literal: ast::Literal::String(part),
+					pos: part_pos,
+					span: self.span_from(part_pos),
+				};
+
+				expr = concat(expr, literal, self.span_from(pos));
+			}
+
+			if !more {
+				break;
+			}
+		}
+
+		Ok(expr)
+	}
+
+
+	/// Parse the rest of an array literal or comprehension, given the position of the
+	/// opening bracket.
+	fn parse_array(&mut self, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		if matches!(self.token, Some(Token { kind: TokenKind::CloseBracket, .. })) {
+			self.step();
+
+			return Ok(ast::Expr::Literal {
+				literal: ast::Literal::Array(Box::default()),
+				pos,
+				span: self.span_from(pos),
+			});
+		}
+
+		let first = self.parse_item()
+			.synchronize(self);
+
+		if matches!(self.token, Some(Token { kind: TokenKind::Keyword(Keyword::For), .. })) {
+			self.step();
+			return self.parse_comprehension(first, pos);
+		}
+
+		let mut items = vec![first];
+
+		if matches!(self.token, Some(Token { kind: TokenKind::Comma, .. })) {
+			self.step();
+
+			let tail = self.comma_sep(
+				Self::parse_item,
+				|token| *token == TokenKind::CloseBracket,
+			);
+
+			items.extend(tail.into_vec());
+		}
+
+		self.expect(TokenKind::CloseBracket)
+			.with_sync(sync::Strategy::token(TokenKind::CloseBracket))?;
+
+		Ok(ast::Expr::Literal {
+			literal: ast::Literal::Array(items.into()),
+			pos,
+			span: self.span_from(pos),
+		})
+	}
+
+
+	/// Parse the rest of a dict literal, given the position of the opening token.
+	fn parse_dict(&mut self, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		let mark = self.comments.len();
+
+		let mut items = self.comma_sep(
+			|parser| {
+				let (key, key_pos) = match &parser.token {
+					// Computed key.
+					Some(Token { kind: TokenKind::OpenBracket, pos, .. }) => {
+						let pos = *pos;
+						parser.step();
+
+						let key = parser.parse_expression()?;
+
+						parser.expect(TokenKind::CloseBracket)
+							.with_sync(sync::Strategy::keep())
+							.synchronize(parser);
+
+						(ast::DictKey::Expr(key.into()), pos)
+					}
+
+					// Identifier key.
+					_ => {
+						let (key, pos) = parser.parse_identifier()
+							.with_sync(sync::Strategy::skip_one())
+							.synchronize(parser);
+
+						(ast::DictKey::Symbol(key), pos)
+					}
+				};
+
+				parser.expect(TokenKind::Colon)
+					.with_sync(sync::Strategy::keep())
+					.synchronize(parser);
+
+				let value = parser.parse_expression()?;
+
+				Ok(ast::DictEntry { key, pos: key_pos, value, comment: None })
+			},
+			|token| *token == TokenKind::CloseBracket,
+		);
+
+		self.attach_comments(mark, &mut items, |item| (item.pos, &mut item.comment));
+
+		self.expect(TokenKind::CloseBracket)
+			.with_sync(sync::Strategy::token(TokenKind::CloseBracket))?;
+
+		Ok(ast::Expr::Literal { literal: ast::Literal::Dict(items), pos, span: self.span_from(pos) })
+	}
+
+
+	/// Parse the rest of an if expression, given the position of the if keyword.
+	fn parse_if(&mut self, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		let (condition, then, otherwise) = self.parse_condblock()?;
+
+		self.expect(TokenKind::Keyword(Keyword::End))
+			.with_sync(sync::Strategy::keyword(Keyword::End))?;
+
+		Ok(ast::Expr::If {
+			condition: condition.into(),
+			then,
+			otherwise,
+			pos,
+			span: self.span_from(pos),
+		})
+	}
+
+
+	/// Parse the rest of a guarded value, given the position of the when keyword.
+	fn parse_when(&mut self, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		let condition = self.parse_expression_before_colon()
+			.synchronize(self);
+
+		self.expect(TokenKind::Colon)
+			.with_sync(sync::Strategy::keep())
+			.synchronize(self);
+
+		// Don't synchronize here because this expression is the last part of the guard.
+		let value = self.parse_expression()?;
+
+		Ok(ast::Expr::When {
+			condition: condition.into(),
+			value: value.into(),
+			pos,
+			span: self.span_from(pos),
+		})
+	}
+
+
+	/// Parse the rest of a match expression, given the position of the match keyword.
+	fn parse_match(&mut self, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		let scrutinee = self.parse_expression()
+			.synchronize(self);
+
+		let mut arms = Vec::new();
+
+		// There must be at least one arm.
+		loop {
+			self.expect(TokenKind::Keyword(Keyword::With))
+				.with_sync(sync::Strategy::keep())
+				.synchronize(self);
+
+			let pattern = self.parse_expression()
+				.synchronize(self);
+
+			self.expect(TokenKind::Keyword(Keyword::Then))
+				.with_sync(sync::Strategy::keep())
+				.synchronize(self);
+
+			let block = self.parse_block();
+
+			arms.push((pattern, block));
+
+			if !matches!(self.token, Some(Token { kind: TokenKind::Keyword(Keyword::With), .. })) {
+				break;
+			}
+		}
+
+		self.check_match_arms(&arms);
+
+		let otherwise =
+			if matches!(self.token, Some(Token { kind: TokenKind::Keyword(Keyword::Else), .. })) {
+				self.step();
+				self.parse_block()
+			} else {
+				ast::Block::default()
+			};
+
+		self.expect(TokenKind::Keyword(Keyword::End))
+			.with_sync(sync::Strategy::keyword(Keyword::End))?;
+
+		Ok(ast::Expr::Match {
+			scrutinee: scrutinee.into(),
+			arms: arms.into_boxed_slice(),
+			otherwise,
+			pos,
+			span: self.span_from(pos),
+		})
+	}
+
+
+	/// Parse the rest of a parenthesized expression, given the position of the opening
+	/// parenthesis.
+	fn parse_parenthesized(&mut self, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		// Empty parenthesis.
+		if let Some(token @ Token { kind: TokenKind::CloseParens, .. }) = &self.token {
+			return Err(Error::unexpected_msg(token.clone(), "expression inside parentheses"))
+				.with_sync(sync::Strategy::token(TokenKind::CloseParens));
+		}
+
+		let expr = self.parse_expression()
+			.synchronize(self);
+
+		self.expect(TokenKind::CloseParens)
+			.with_sync(sync::Strategy::token(TokenKind::CloseParens))?;
+
+		// The span includes the parentheses, but the position is kept.
+		Ok(
+			match expr.pos() {
+				Some(inner_pos) => ast::Expr::Parenthesized {
+					expr: expr.into(),
+					pos: inner_pos,
+					span: self.span_from(pos),
+				},
+
+				None => expr,
+			}
+		)
+	}
+
+
+	/// Report match arms with a constant pattern already matched by a previous arm, as they
+	/// are never taken. Arms with non-constant patterns are not checked.
+	fn check_match_arms(&mut self, arms: &[(ast::Expr, ast::Block)]) {
+		let mut patterns = HashMap::new();
+
+		for (pattern, _) in arms {
+			let (constant, pos) = match pattern {
+				ast::Expr::Literal { literal, pos, .. } => match Constant::from_literal(literal) {
+					Some(constant) => (constant, *pos),
+					None => continue,
+				},
+				_ => continue,
+			};
+
+			match patterns.entry(constant) {
+				Entry::Occupied(first) => self.error_reporter.report(Error::duplicate_match_arm(pos, *first.get())),
+				Entry::Vacant(entry) => {
+					entry.insert(pos);
+				}
+			}
+		}
+	}
+
+
+	/// Parse the values of a return statement, given the position of the return keyword.
+	/// Multiple comma separated values are packed into an array literal.
+	fn parse_return_values(&mut self, pos: SourcePos) -> sync::Result<ast::Expr, Error> {
+		let expr = self.parse_expression()?;
+
+		if !matches!(self.token, Some(Token { kind: TokenKind::Comma, .. })) {
+			return Ok(expr);
+		}
+
+		let start = expr.span().map_or(pos, |span| span.start);
+		let mut items = vec![expr];
+
+		while let Some(Token { kind: TokenKind::Comma, .. }) = self.token {
+			self.step();
+			items.push(self.parse_expression()?);
+		}
+
+		Ok(ast::Expr::Literal {
+			literal: ast::Literal::Array(items.into()),
+			pos,
+			span: self.span_from(start),
+		})
+	}
+
+
 	/// Parse a identifier.
 	fn parse_identifier(&mut self) -> sync::Result<(ast::Symbol, SourcePos), Error> {
 		self
 			.eat(
 				|token| match token {
-					Token { kind: TokenKind::Identifier(symbol), pos } => Ok((symbol, pos)),
+					Token { kind: TokenKind::Identifier(symbol), pos, .. } => Ok((symbol, pos)),
 					token => Err((Error::unexpected_msg(token.clone(), "identifier"), token)),
 				}
 			)
@@ -717,12 +1746,155 @@ where
 	}
 
 
-	/// Parse a function literal after the function keyword.
-	/// Returns a pair of parameters and body.
-	#[allow(clippy::type_complexity)]
-	fn parse_function(
-		&mut self
-	) -> sync::Result<(Box<[(ast::Symbol, SourcePos)]>, ast::Block), Error> {
+	/// Parse an optional type annotation: a colon followed by a type.
+	fn parse_annotation(&mut self) -> Option<ast::TypeExpr> {
+		if matches!(self.token, Some(Token { kind: TokenKind::Colon, .. })) {
+			self.step();
+
+			let annotation = self.parse_type()
+				.synchronize(self);
+
+			Some(annotation)
+		} else {
+			None
+		}
+	}
+
+
+	/// Parse a type expression, with any number of nullable markers.
+	fn parse_type(&mut self) -> sync::Result<ast::TypeExpr, Error> {
+		let mut type_expr = self.parse_base_type()?;
+
+		while let Some(Token { kind: TokenKind::Operator(Operator::Try), pos, .. }) = self.token {
+			self.step();
+			type_expr = ast::TypeExpr::Nullable { inner: type_expr.into(), pos };
+		}
+
+		Ok(type_expr)
+	}
+
+
+	/// Parse a type expression without nullable markers.
+	fn parse_base_type(&mut self) -> sync::Result<ast::TypeExpr, Error> {
+		match self.token.take() {
+			// Named type.
+			Some(Token { kind: TokenKind::Identifier(name), pos, .. }) => {
+				self.step();
+				Ok(ast::TypeExpr::Named { name, pos })
+			}
+
+			// Array type.
+			Some(Token { kind: TokenKind::OpenBracket, pos, .. }) => {
+				self.step();
+
+				let item = self.parse_type()
+					.synchronize(self);
+
+				self.expect(TokenKind::CloseBracket)
+					.with_sync(sync::Strategy::token(TokenKind::CloseBracket))?;
+
+				Ok(ast::TypeExpr::Array { item: item.into(), pos })
+			}
+
+			// Dict type.
+			Some(Token { kind: TokenKind::OpenDict, pos, .. }) => {
+				self.step();
+
+				let key = self.parse_type()
+					.synchronize(self);
+
+				self.expect(TokenKind::Colon)
+					.with_sync(sync::Strategy::keep())
+					.synchronize(self);
+
+				let value = self.parse_type()
+					.synchronize(self);
+
+				self.expect(TokenKind::CloseBracket)
+					.with_sync(sync::Strategy::token(TokenKind::CloseBracket))?;
+
+				Ok(ast::TypeExpr::Dict { key: key.into(), value: value.into(), pos })
+			}
+
+			// Function type.
+			Some(Token { kind: TokenKind::Keyword(Keyword::Function), pos, .. }) => {
+				self.step();
+
+				self.expect(TokenKind::OpenParens)
+					.with_sync(sync::Strategy::keep())
+					.synchronize(self);
+
+				let params = self.comma_sep(
+					Self::parse_type,
+					|token| *token == TokenKind::CloseParens,
+				);
+
+				self.expect(TokenKind::CloseParens)
+					.with_sync(sync::Strategy::token(TokenKind::CloseParens))?;
+
+				let return_type = self.parse_annotation()
+					.map(Box::new);
+
+				Ok(ast::TypeExpr::Function { params, return_type, pos })
+			}
+
+			// Some other unexpected token.
+			Some(token) => {
+				self.token = Some(token.clone());
+				Err(Error::unexpected_msg(token, "type"))
+					.with_sync(sync::Strategy::keep())
+			}
+
+			None => Err(Error::unexpected_eof())
+				.with_sync(sync::Strategy::eof()),
+		}
+	}
+
+
+	/// Parse a function parameter, with an optional type annotation and default value.
+	fn parse_param(&mut self) -> sync::Result<ast::Param, Error> {
+		let (identifier, pos) = self.parse_identifier()?;
+		let annotation = self.parse_annotation();
+
+		let default =
+			if matches!(self.token, Some(Token { kind: TokenKind::Operator(Operator::Assign), .. })) {
+				self.step();
+				Some(self.parse_expression().synchronize(self))
+			} else {
+				None
+			};
+
+		Ok(ast::Param { identifier, annotation, default, pos, comment: None })
+	}
+
+
+	/// Parse a field binding in a dict destructuring pattern: `field` or `field: identifier`.
+	fn parse_field_binding(&mut self) -> sync::Result<ast::FieldBinding, Error> {
+		let (field, pos) = self.parse_identifier()?;
+
+		let identifier =
+			if matches!(self.token, Some(Token { kind: TokenKind::Colon, .. })) {
+				self.step();
+				self.parse_identifier()?.0
+			} else {
+				field
+			};
+
+		Ok(ast::FieldBinding { field, identifier, pos })
+	}
+
+
+	/// Parse an item binding in an array destructuring pattern: `identifier`.
+	fn parse_item_binding(&mut self) -> sync::Result<ast::ItemBinding, Error> {
+		let (identifier, pos) = self.parse_identifier()?;
+
+		Ok(ast::ItemBinding { identifier, pos })
+	}
+
+
+	/// Parse a function literal after the function keyword, given the position of the
+	/// keyword. Returns the function literal.
+	fn parse_function(&mut self, pos: SourcePos) -> sync::Result<ast::Literal, Error> {
 		let result = self.expect(TokenKind::OpenParens)
 			.with_sync(sync::Strategy::keep());
 
@@ -730,11 +1902,25 @@ where
 
 		result.synchronize(self);
 
-		let params = self.comma_sep(
-			Self::parse_identifier,
+		let mark = self.comments.len();
+
+		let mut params = self.comma_sep(
+			Self::parse_param,
 			|token| *token == TokenKind::CloseParens,
 		);
 
+		self.attach_comments(mark, &mut params, |param| (param.pos, &mut param.comment));
+
+		// Defaulted parameters must come last, so that arguments are matched by position.
+		let mut defaulted = false;
+		for param in params.iter().filter(|param| !param.is_ill_formed()) {
+			if param.default.is_some() {
+				defaulted = true;
+			} else if defaulted {
+				self.error_reporter.report(Error::required_after_default(param.pos));
+			}
+		}
+
 		self.expect(TokenKind::CloseParens)
 			.with_sync(
 				if open_parens {
@@ -745,12 +1931,65 @@ where
 			)
 			.synchronize(self);
 
+		// As statements never start with a colon, this can't be mistaken for the body.
+		let return_type = self.parse_annotation();
+
 		let body = self.parse_block();
 
 		self.expect(TokenKind::Keyword(Keyword::End))
+			.map_err(
+				// Point to the opener, as the end of file is far from the actual error.
+				|error| match error.kind {
+					ErrorKind::UnexpectedEof => Error::unterminated("function body", pos),
+					_ => error,
+				}
+			)
 			.with_sync(sync::Strategy::keyword(Keyword::End))?;
 
-		Ok((params, body))
+		Ok(ast::Literal::Function { params, return_type, body })
+	}
+
+
+	/// Parse an array comprehension after the for keyword, given the already parsed output
+	/// expression and the position of the opening bracket.
+	fn parse_comprehension(
+		&mut self,
+		output: ast::Expr,
+		pos: SourcePos,
+	) -> sync::Result<ast::Expr, Error> {
+		let (identifier, _) = self.parse_identifier()
+			.synchronize(self);
+
+		self.expect(TokenKind::Keyword(Keyword::In))
+			.with_sync(sync::Strategy::skip_one())
+			.synchronize(self);
+
+		let source = self.parse_expression()
+			.synchronize(self);
+
+		let filter =
+			if matches!(self.token, Some(Token { kind: TokenKind::Keyword(Keyword::If), .. })) {
+				self.step();
+
+				let filter = self.parse_expression()
+					.synchronize(self);
+
+				Some(Box::new(filter))
+			} else {
+				None
+			};
+
+		self.expect(TokenKind::CloseBracket)
+			.with_sync(sync::Strategy::token(TokenKind::CloseBracket))?;
+
+		Ok(ast::Expr::Comprehension {
+			output: Box::new(output),
+			identifier,
+			source: Box::new(source),
+			filter,
+			pos,
+			span: self.span_from(pos),
+		})
 	}
 
 
@@ -777,7 +2016,9 @@ where
 
 				let (condition, then, otherwise) = self.parse_condblock()?;
 
-				let stmt = ast::Statement::Expr(ast::Expr::If { condition, then, otherwise, pos, });
+				let stmt = ast::Statement::Expr(
+					ast::Expr::If { condition, then, otherwise, pos, span: self.span_from(pos) }
+				);
 
 				ast::Block::Block(Box::new([stmt]))
 			},
@@ -30,6 +30,29 @@ pub enum Strategy {
 	BasicCommandTerminator {
 		skipped: bool,
 	},
+	/// Skip the current token, and if it opens a nested construct, skip until after the
+	/// matching closing token.
+	Balanced {
+		depth: usize,
+		skipped: bool,
+	},
+	/// Skip the current token, and then until one of the given tokens is found. The found
+	/// token is not skipped.
+	Tokens {
+		tokens: Box<[TokenKind]>,
+		skipped: bool,
+	},
+	/// Skip until the end of the expression embedded in a string interpolation, skipping
+	/// nested interpolated strings. The following string part is not skipped.
+	InterpolationEnd {
+		depth: usize,
+	},
+	/// Skip a conditional command, up to and including its matching end keyword. The end
+	/// of the command block is not skipped, as the conditional may be unterminated.
+	Conditional {
+		depth: usize,
+		skipped: bool,
+	},
 }
 
 
@@ -79,6 +102,32 @@ impl Strategy {
 	}
 
 
+	/// Skip the current token, and if it opens a nested construct, skip until after the
+	/// matching closing token.
+	pub fn balanced() -> Self {
+		Self::Balanced { depth: 0, skipped: false }
+	}
+
+
+	/// Skip the current token, and then until one of the given tokens is found. The found
+	/// token is not skipped.
+	pub fn tokens(tokens: Box<[TokenKind]>) -> Self {
+		Self::Tokens { tokens, skipped: false }
+	}
+
+
+	/// Skip until the end of an embedded expression in a string interpolation.
+	pub fn interpolation_end() -> Self {
+		Self::InterpolationEnd { depth: 0 }
+	}
+
+
+	/// Skip a conditional command, starting at its if keyword.
+	pub fn conditional() -> Self {
+		Self::Conditional { depth: 0, skipped: false }
+	}
+
+
 	/// Indicates whether the stream has been synchronized.
 	/// When this method returns false, the token should be skipped.
 	pub fn synchronized(&mut self, token: &TokenKind) -> bool {
@@ -114,6 +163,54 @@ impl Strategy {
 				*skipped = token.is_basic_command_terminator();
 				false
 			},
+
+			Self::Balanced { skipped: true, .. } => true,
+			Self::Balanced { depth, skipped } => {
+				if token.is_nesting_opener() {
+					*depth += 1;
+				} else if token.is_nesting_closer() {
+					*depth = depth.saturating_sub(1);
+				}
+
+				*skipped = *depth == 0;
+				false
+			},
+
+			Self::Tokens { tokens, skipped: true } => tokens.contains(token),
+			Self::Tokens { skipped, .. } => {
+				*skipped = true;
+				false
+			},
+
+			Self::InterpolationEnd { depth } => match token {
+				TokenKind::StringHead(_) => {
+					*depth += 1;
+					false
+				}
+
+				TokenKind::StringMiddle(_) | TokenKind::StringTail(_) if *depth == 0 => true,
+
+				TokenKind::StringTail(_) => {
+					*depth -= 1;
+					false
+				}
+
+				_ => false,
+			},
+
+			Self::Conditional { skipped: true, .. } => true,
+			Self::Conditional { depth, skipped } => {
+				if token.is_command_keyword(Keyword::If) {
+					*depth += 1;
+				} else if token.is_command_keyword(Keyword::End) {
+					*depth = depth.saturating_sub(1);
+				} else if *token == TokenKind::CloseCommand {
+					return true;
+				}
+
+				*skipped = *depth == 0;
+				false
+			},
 		}
 	}
 }
@@ -158,6 +255,10 @@ pub trait ResultExt<T, E> {
 
 	/// If the sync strategy is `keep`, replace it with `skip_one`.
 	fn force_sync_skip(self) -> Self;
+
+	/// If the sync strategy is `keep`, replace it with skipping until one of the given
+	/// tokens, or with `skip_one` if there are none.
+	fn force_sync_tokens(self, tokens: &[TokenKind]) -> Self;
 }
 
 
@@ -185,4 +286,15 @@ where
 			}
 		)
 	}
+
+
+	fn force_sync_tokens(self, tokens: &[TokenKind]) -> Self {
+		self.map_err(
+			|(error, strategy)| match strategy {
+				Strategy::Keep if tokens.is_empty() => (error, Strategy::skip_one()),
+				Strategy::Keep => (error, Strategy::tokens(tokens.into())),
+				_ => (error, strategy)
+			}
+		)
+	}
 }
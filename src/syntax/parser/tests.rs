@@ -0,0 +1,2185 @@
+use std::time::Duration;
+
+use assert_matches::assert_matches;
+
+use crate::{fmt, symbol};
+use super::super::{lexer::{self, Cursor, Keyword, Lexer, Token, TokenKind}, Analysis, Source, SourcePos, Span};
+use super::ast::{
+	ArgExpansion,
+	ArgPart,
+	ArgUnit,
+	BinaryOp,
+	Block,
+	Command,
+	CommandBlock,
+	CommandBlockKind,
+	DictEntry,
+	DictKey,
+	Expr,
+	FieldBinding,
+	ItemBinding,
+	Literal,
+	Param,
+	Statement,
+	TypeExpr,
+	UnaryOp,
+};
+use super::{
+	error::Expected,
+	Config,
+	DedupReporter,
+	Error,
+	ErrorKind,
+	ErrorReporter,
+	ParseContext,
+	Parser,
+	STATEMENT_SYNC_TOKENS,
+};
+
+
+/// Parse the given input, asserting that there are no syntax errors.
+fn parse(input: &str, interner: &mut symbol::Interner) -> Box<[Statement]> {
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let analysis = Analysis::analyze(&source, interner);
+
+	assert!(analysis.errors.is_empty(), "unexpected syntax errors");
+
+	match analysis.ast.statements {
+		Block::Block(statements) => statements,
+		Block::IllFormed => panic!("ill-formed block"),
+	}
+}
+
+
+/// Parse the given input with the given configuration, returning the parser errors.
+/// The input is expected to contain no lexical errors.
+fn parse_errors(input: &str, config: Config, interner: &mut symbol::Interner) -> Vec<Error> {
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let tokens = Lexer::new(cursor, interner).map(|result| result.expect("lexer error"));
+
+	let (_, errors) = Parser::with_config(tokens, Vec::new(), config).into_errors();
+
+	errors
+}
+
+
+#[test]
+fn test_break() {
+	let input = r#"
+		while true do
+			break
+		end
+
+		while true do
+			break 1 + 2
+		end
+
+		while true do
+			if true then break end
+			let x = 1
+		end
+
+		while true do
+			break
+			std.println("unreachable")
+		end
+	"#;
+
+	let mut interner = symbol::Interner::new();
+	let statements = parse(input, &mut interner);
+
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::While { block: Block::Block(first), .. },
+			Statement::While { block: Block::Block(second), .. },
+			Statement::While { block: Block::Block(third), .. },
+			Statement::While { block: Block::Block(fourth), .. },
+		]
+			=> {
+				assert_matches!(first.as_ref(), [Statement::Break { expr: None, .. }]);
+				assert_matches!(
+					second.as_ref(),
+					[Statement::Break { expr: Some(Expr::BinaryOp { op: BinaryOp::Plus, .. }), .. }]
+				);
+				assert_matches!(third.as_ref(), [Statement::Expr(Expr::If { .. }), Statement::Let { .. }]);
+				// A value in the following line is a separate statement.
+				assert_matches!(
+					fourth.as_ref(),
+					[Statement::Break { expr: None, .. }, Statement::Expr(Expr::Call { .. })]
+				);
+			}
+	);
+}
+
+
+#[test]
+fn test_continue() {
+	let mut interner = symbol::Interner::new();
+	let statements = parse("while true do continue end", &mut interner);
+
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::While {
+				condition: Expr::Literal { literal: Literal::Bool(true), .. },
+				block: Block::Block(block),
+				..
+			},
+		]
+			=> assert_matches!(block.as_ref(), [Statement::Continue { .. }])
+	);
+
+	// No statements may follow a continue in the same block.
+	let errors = parse_errors("while true do continue let x = 1 end", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_compound_assign() {
+	let mut interner = symbol::Interner::new();
+
+	let input = "x += 1\nx -= 1\nx *= 1\nx /= 1\nx %= 1\nx.y ++= \"a\"";
+	let statements = parse(input, &mut interner);
+
+	let ops: Vec<&BinaryOp> = statements
+		.iter()
+		.map(
+			|statement| match statement {
+				Statement::Assign { right: Expr::BinaryOp { op, .. }, .. } => op,
+				other => panic!("expected compound assignment, got {:?}", other),
+			}
+		)
+		.collect();
+
+	assert_eq!(
+		ops,
+		[
+			&BinaryOp::Plus,
+			&BinaryOp::Minus,
+			&BinaryOp::Times,
+			&BinaryOp::Div,
+			&BinaryOp::Mod,
+			&BinaryOp::Concat,
+		]
+	);
+
+	// The left operand is duplicated, including its position.
+	assert_matches!(
+		statements.last(),
+		Some(
+			Statement::Assign {
+				left: left @ Expr::Field { pos: left_pos, .. },
+				right: Expr::BinaryOp { left: operand, right, pos, .. },
+				..
+			}
+		) => {
+			assert_eq!(operand.as_ref(), left);
+			assert_eq!((left_pos.line, left_pos.column), (6, 2));
+			assert_eq!((pos.line, pos.column), (6, 4));
+			assert_matches!(right.as_ref(), Expr::Literal { literal: Literal::String(_), .. });
+		}
+	);
+}
+
+
+#[test]
+fn test_max_depth() {
+	let mut interner = symbol::Interner::new();
+	let config = || Config { max_depth: 8, ..Config::default() };
+
+	let shallow = "let x = [[[1]]]";
+	assert!(parse_errors(shallow, config(), &mut interner).is_empty());
+
+	let arrays = format!("let x = {}1{}\nlet y = 2", "[".repeat(20), "]".repeat(20));
+	let errors = parse_errors(&arrays, config(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::NestingTooDeep { pos }, .. }] if pos.line == 1
+	);
+
+	let commands = format!("let x = {{ {}echo{} }}\nlet y = 2", "if true; then ".repeat(20), "; end".repeat(20));
+	let errors = parse_errors(&commands, config(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::NestingTooDeep { pos }, .. }] if pos.line == 1
+	);
+
+	let blocks = format!("{}{}", "while true do ".repeat(20), "end ".repeat(20));
+	let errors = parse_errors(&blocks, config(), &mut interner);
+	assert!(!errors.is_empty());
+	assert!(errors.iter().all(|error| matches!(error.kind, ErrorKind::NestingTooDeep { .. })));
+}
+
+
+/// Check that the default limit prevents stack overflows.
+#[test]
+fn test_default_max_depth() {
+	// Test threads have a smaller stack than the main thread, which is where the parser
+	// usually runs.
+	let thread = std::thread::Builder::new()
+		.stack_size(8 * 1024 * 1024)
+		.spawn(|| {
+			let mut interner = symbol::Interner::new();
+
+			let arrays = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+			let errors = parse_errors(&arrays, Config::default(), &mut interner);
+			assert_matches!(errors.as_slice(), [Error { kind: ErrorKind::NestingTooDeep { .. }, .. }]);
+
+			let commands = format!("{{ {}echo{} }}", "if true; then ".repeat(100_000), "; end".repeat(100_000));
+			let errors = parse_errors(&commands, Config::default(), &mut interner);
+			assert_matches!(errors.as_slice(), [Error { kind: ErrorKind::NestingTooDeep { .. }, .. }]);
+		})
+		.expect("failed to spawn thread");
+
+	thread.join().expect("thread panicked");
+}
+
+
+#[test]
+fn test_assign_as_equality() {
+	let mut interner = symbol::Interner::new();
+
+	let errors = parse_errors("a = b == c", Config::default(), &mut interner);
+	assert!(errors.is_empty());
+
+	let input = r#"
+		if a = b == c then
+			a = b
+		end
+	"#;
+
+	let errors = parse_errors(input, Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::AssignAsEquality { pos }, .. }] if (pos.line, pos.column) == (2, 7)
+	);
+
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let analysis = Analysis::analyze(&source, &mut interner);
+
+	assert_matches!(
+		analysis.ast.statements,
+		Block::Block(statements) => assert_matches!(
+			statements.as_ref(),
+			[Statement::Expr(Expr::If { condition, then: Block::Block(then), .. })] => {
+				assert_matches!(
+					condition.as_ref(),
+					Expr::BinaryOp {
+						left: equality,
+						op: BinaryOp::Equals,
+						..
+					} if matches!(equality.as_ref(), Expr::BinaryOp { op: BinaryOp::Equals, .. })
+				);
+				assert_matches!(then.as_ref(), [Statement::Assign { .. }]);
+			}
+		)
+	);
+}
+
+
+#[test]
+fn test_elseif_chain() {
+	let input = "if a then 1 elseif b then 2 else 3 end\n\
+	             if a then 1\nelseif b then 2\nelseif c then 3\nend";
+
+	let mut interner = symbol::Interner::new();
+	let statements = parse(input, &mut interner);
+
+	let position = |expr: &Expr| expr.pos().map(|pos| (pos.line, pos.column));
+
+	// A single end closes the whole chain, with each elseif nested in the previous otherwise.
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Expr(Expr::If { condition: a1, otherwise: Block::Block(two), .. }),
+			Statement::Expr(Expr::If { condition: a2, otherwise: Block::Block(three), .. }),
+		]
+			=> {
+				assert_eq!(position(a1), Some((1, 3)));
+				assert_matches!(
+					two.as_ref(),
+					[Statement::Expr(Expr::If { condition: b, otherwise: Block::Block(otherwise), .. })]
+						=> {
+							assert_eq!(position(b), Some((1, 19)));
+							assert_matches!(otherwise.as_ref(), [Statement::Expr(Expr::Literal { .. })]);
+						}
+				);
+
+				assert_eq!(position(a2), Some((2, 3)));
+				assert_matches!(
+					three.as_ref(),
+					[Statement::Expr(Expr::If { condition: b, otherwise: Block::Block(otherwise), .. })]
+						=> {
+							assert_eq!(position(b), Some((3, 7)));
+							assert_matches!(
+								otherwise.as_ref(),
+								[Statement::Expr(Expr::If { condition: c, otherwise, .. })]
+									=> {
+										assert_eq!(position(c), Some((4, 7)));
+										assert!(otherwise.is_empty());
+									}
+							);
+						}
+				);
+			}
+	);
+}
+
+
+#[test]
+fn test_short_circuit_assign() {
+	let mut interner = symbol::Interner::new();
+
+	// Assignments are statements, so an assignment in the operand of a short-circuiting
+	// operator is rejected instead of silently depending on the left operand.
+	let input = "a or (y = 2)\na and (x = 1)";
+	let errors = parse_errors(input, Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[
+			Error { kind: ErrorKind::AssignAsEquality { pos: first }, .. },
+			Error { kind: ErrorKind::AssignAsEquality { pos: second }, .. },
+		] if (first.line, first.column, second.line, second.column) == (1, 8, 2, 9)
+	);
+}
+
+
+#[test]
+fn test_unterminated_function() {
+	let mut interner = symbol::Interner::new();
+
+	let input = "let x = 1\nfunction fun(a)\n\treturn a\n";
+	let errors = parse_errors(input, Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unterminated { construct: "function body", pos }, .. }] if (pos.line, pos.column) == (2, 0)
+	);
+
+	let input = "let fun = function()\n\tlet y = 2\n";
+	let errors = parse_errors(input, Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unterminated { construct: "function body", pos }, .. }] if (pos.line, pos.column) == (1, 10)
+	);
+}
+
+
+#[test]
+fn test_error_context() {
+	let mut interner = symbol::Interner::new();
+
+	// Unexpected token inside the command block.
+	let errors = parse_errors("{ echo > }", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unexpected { .. }, context: ParseContext::Command, .. }, ..]
+	);
+	assert!(errors.iter().all(|error| error.context == ParseContext::Command));
+
+	// Unterminated command block, reported after leaving the block.
+	let errors = parse_errors("let x = { echo", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::UnexpectedEof, context: ParseContext::Command, .. }, ..]
+	);
+
+	// Unexpected token in an expression.
+	let errors = parse_errors("let x = 1 + )", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unexpected { .. }, context: ParseContext::Expression, .. }]
+	);
+}
+
+
+#[test]
+fn test_command_flags() {
+	let mut interner = symbol::Interner::new();
+
+	let statements = parse("{ cmd -n --count=3 -rf -- - }", &mut interner);
+
+	let literal = |arg: &str| [ArgPart::Unit(ArgUnit::Literal(arg.as_bytes().into()))];
+
+	assert_matches!(
+		statements.as_ref(),
+		[Statement::Expr(Expr::CommandBlock { block, .. })] => assert_matches!(
+			block.as_ref(),
+			CommandBlock { head: Command::Pipeline { head, tail }, .. }
+				if tail.is_empty() => {
+					assert_eq!(head.program.parts.as_ref(), literal("cmd"));
+
+					let arguments: Vec<&[ArgPart]> = head.arguments
+						.iter()
+						.map(|argument| argument.parts.as_ref())
+						.collect();
+
+					assert_eq!(
+						arguments,
+						[
+							literal("-n").as_ref(),
+							literal("--count=3").as_ref(),
+							literal("-rf").as_ref(),
+							literal("--").as_ref(),
+							literal("-").as_ref(),
+						]
+					);
+				}
+		)
+	);
+}
+
+
+#[test]
+fn test_command_splat() {
+	let mut interner = symbol::Interner::new();
+
+	let statements = parse("{ cmd @args $args user@host @types/node }", &mut interner);
+	let args = interner.get_or_intern("args");
+
+	let literal = |arg: &str| [ArgPart::Unit(ArgUnit::Literal(arg.as_bytes().into()))];
+
+	assert_matches!(
+		statements.as_ref(),
+		[Statement::Expr(Expr::CommandBlock { block, .. })] => assert_matches!(
+			block.as_ref(),
+			CommandBlock { head: Command::Pipeline { head, .. }, .. }
+				=> {
+					let arguments: Vec<&[ArgPart]> = head.arguments
+						.iter()
+						.map(|argument| argument.parts.as_ref())
+						.collect();
+
+					assert_matches!(
+						arguments.as_slice(),
+						[
+							[ArgPart::Expansion(ArgExpansion::Splat { symbol: splat, .. })],
+							[ArgPart::Unit(ArgUnit::Dollar { symbol: dollar, .. })],
+							user_host,
+							scoped,
+						] => {
+							assert_eq!((*splat, *dollar), (args, args));
+
+							// Splats must span the whole argument.
+							assert_eq!(*user_host, literal("user@host"));
+							assert_eq!(*scoped, literal("@types/node"));
+						}
+					);
+				}
+		)
+	);
+}
+
+
+#[test]
+fn test_scope() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		let x = 1
+		begin
+			let y = 2
+			x = y
+		end
+		let z = x
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let { .. },
+			Statement::Scope { block: Block::Block(block), pos, .. },
+			Statement::Let { .. },
+		] if pos.line == 3 => assert_matches!(
+			block.as_ref(),
+			[Statement::Let { .. }, Statement::Assign { .. }]
+		)
+	);
+
+	// Nested scopes and loops share the end keyword.
+	let statements = parse("begin while true do begin end end end", &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[Statement::Scope { block: Block::Block(block), .. }] => assert_matches!(
+			block.as_ref(),
+			[Statement::While { .. }]
+		)
+	);
+
+	let errors = parse_errors("begin\n\tlet x = 1\n", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unterminated { construct: "scope", pos }, .. }] if pos.line == 1
+	);
+}
+
+
+#[test]
+fn test_field_access() {
+	let mut interner = symbol::Interner::new();
+	let b = interner.get_or_intern("b");
+
+	let statements = parse("a.b\na[b]\na.b.c", &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Expr(Expr::Field { object: first, name, pos, .. }),
+			Statement::Expr(Expr::Access { object: second, field, .. }),
+			Statement::Expr(Expr::Field { object: third, .. }),
+		] if *name == b && pos.column == 2 => {
+			assert_matches!(first.as_ref(), Expr::Identifier { .. });
+			assert_matches!(second.as_ref(), Expr::Identifier { .. });
+			assert_matches!(field.as_ref(), Expr::Identifier { identifier, .. } if *identifier == b);
+			assert_matches!(third.as_ref(), Expr::Field { name, .. } if *name == b);
+		}
+	);
+}
+
+
+#[test]
+fn test_type_annotations() {
+	let mut interner = symbol::Interner::new();
+	let int = interner.get_or_intern("Int");
+	let string = interner.get_or_intern("String");
+
+	let input = r#"
+		let x: Int = 1
+		let y = 2
+		let z: [@[String: Int]]
+		let f = function(a: Int, b) return a end
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let { annotation: Some(TypeExpr::Named { name, .. }), .. },
+			Statement::Let { annotation: None, .. },
+			Statement::Let { annotation: Some(TypeExpr::Array { item, .. }), .. },
+			Statement::Let {
+				annotation: None,
+				init: Expr::Literal { literal: Literal::Function { params, .. }, .. },
+				..
+			},
+		] if *name == int => {
+			assert_matches!(
+				item.as_ref(),
+				TypeExpr::Dict { key, value, .. } if matches!(
+					(key.as_ref(), value.as_ref()),
+					(TypeExpr::Named { name: k, .. }, TypeExpr::Named { name: v, .. }) if *k == string && *v == int
+				)
+			);
+
+			assert_matches!(
+				params.as_ref(),
+				[
+					Param { annotation: Some(TypeExpr::Named { name, .. }), .. },
+					Param { annotation: None, .. },
+				] if *name == int
+			);
+		}
+	);
+
+	let errors = parse_errors("let x: = 1", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unexpected { .. }, .. }]
+	);
+}
+
+
+#[test]
+fn test_return_type_annotation() {
+	let mut interner = symbol::Interner::new();
+	let int = interner.get_or_intern("Int");
+
+	let input = r#"
+		let f = function(a): [Int]
+			return @[ a: a ]
+		end
+		function g(): Int return 1 end
+		let h = function() end
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let {
+				init: Expr::Literal {
+					literal: Literal::Function { return_type: Some(TypeExpr::Array { item, .. }), body: Block::Block(body), .. },
+					..
+				},
+				..
+			},
+			Statement::Let {
+				init: Expr::Literal {
+					literal: Literal::Function { return_type: Some(TypeExpr::Named { name, .. }), .. },
+					..
+				},
+				..
+			},
+			Statement::Let {
+				init: Expr::Literal { literal: Literal::Function { return_type: None, .. }, .. },
+				..
+			},
+		] if *name == int => {
+			assert_matches!(item.as_ref(), TypeExpr::Named { name, .. } if *name == int);
+			assert_matches!(
+				body.as_ref(),
+				[Statement::Return { expr: Expr::Literal { literal: Literal::Dict(_), .. }, .. }]
+			);
+		}
+	);
+}
+
+
+#[test]
+fn test_type_expressions() {
+	let mut interner = symbol::Interner::new();
+	let int = interner.get_or_intern("Int");
+	let string = interner.get_or_intern("String");
+	let bool_ = interner.get_or_intern("Bool");
+
+	let annotation = |input: &str, interner: &mut symbol::Interner| {
+		let statements = parse(input, interner);
+		match Vec::from(statements).pop() {
+			Some(Statement::Let { annotation: Some(annotation), .. }) => annotation,
+			statement => panic!("expected annotated let, got {:?}", statement),
+		}
+	};
+
+	assert_matches!(
+		annotation("let x: Int", &mut interner),
+		TypeExpr::Named { name, .. } if name == int
+	);
+
+	assert_matches!(
+		annotation("let x: [String]", &mut interner),
+		TypeExpr::Array { item, .. } if matches!(item.as_ref(), TypeExpr::Named { name, .. } if *name == string)
+	);
+
+	assert_matches!(
+		annotation("let x: @[String: Int]", &mut interner),
+		TypeExpr::Dict { key, value, .. } if matches!(
+			(key.as_ref(), value.as_ref()),
+			(TypeExpr::Named { name: k, .. }, TypeExpr::Named { name: v, .. }) if *k == string && *v == int
+		)
+	);
+
+	assert_matches!(
+		annotation("let x: Int?", &mut interner),
+		TypeExpr::Nullable { inner, .. } if matches!(inner.as_ref(), TypeExpr::Named { name, .. } if *name == int)
+	);
+
+	assert_matches!(
+		annotation("let x: function(Int, String): Bool", &mut interner),
+		TypeExpr::Function { params, return_type: Some(return_type), .. } => {
+			assert_matches!(
+				params.as_ref(),
+				[TypeExpr::Named { name: a, .. }, TypeExpr::Named { name: b, .. }] if *a == int && *b == string
+			);
+			assert_matches!(*return_type, TypeExpr::Named { name, .. } if name == bool_);
+		}
+	);
+
+	// A function taking a callback and returning a nullable function.
+	assert_matches!(
+		annotation("let x: function(function(Int): Bool): function()?", &mut interner),
+		TypeExpr::Function { params, return_type: Some(return_type), .. } => {
+			assert_matches!(
+				params.as_ref(),
+				[TypeExpr::Function { params, return_type: Some(return_type), .. }] => {
+					assert_matches!(params.as_ref(), [TypeExpr::Named { name, .. }] if *name == int);
+					assert_matches!(return_type.as_ref(), TypeExpr::Named { name, .. } if *name == bool_);
+				}
+			);
+			assert_matches!(
+				*return_type,
+				TypeExpr::Nullable { inner, .. } if matches!(
+					inner.as_ref(),
+					TypeExpr::Function { params, return_type: None, .. } if params.is_empty()
+				)
+			);
+		}
+	);
+
+	let errors = parse_errors("let x: function(Int", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_empty_arguments() {
+	let mut interner = symbol::Interner::new();
+
+	// Empty quoted arguments are fine.
+	parse("{ echo '' \"\" > '' }", &mut interner);
+
+	let errors = parse_errors("{ echo 1; '' a }", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[
+			Error {
+				kind: ErrorKind::EmptyProgram { pos },
+				context: ParseContext::Command,
+				..
+			}
+		] if pos.column == 10
+	);
+
+	// A stray separator leaves an empty command.
+	let errors = parse_errors("{ echo a;; echo b }", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::EmptyProgram { pos }, .. }] if pos.column == 9
+	);
+
+	let errors = parse_errors("{ echo a | }", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::EmptyProgram { pos }, .. }] if pos.column == 11
+	);
+}
+
+
+#[test]
+fn test_owned_errors() {
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: b"let x = 1\nlet = 2\nlet y = )\nlet z = 3".as_ref().into() };
+	let cursor = Cursor::from(&source);
+	let tokens = Lexer::new(cursor, &mut interner).map(|result| result.expect("lexer error"));
+
+	let (block, errors) = Parser::new(tokens, Vec::new()).into_errors();
+
+	assert_matches!(
+		block,
+		Block::Block(statements) => assert_matches!(
+			statements.as_ref(),
+			[
+				Statement::Let { .. },
+				Statement::Let { .. },
+				Statement::IllFormed,
+				Statement::Let { .. },
+			]
+		)
+	);
+	assert_matches!(
+		errors.as_slice(),
+		[
+			Error { kind: ErrorKind::Unexpected { token: first, .. }, .. },
+			Error { kind: ErrorKind::Unexpected { token: second, .. }, .. },
+		] if (first.pos.line, second.pos.line) == (2, 3)
+	);
+}
+
+
+#[test]
+fn test_statement_recovery() {
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let input = "let a = )\nlet b = 1\nassert ]\nlet c = 2\nreturn )";
+	let source = Source { path, contents: input.as_bytes().into() };
+	let cursor = Cursor::from(&source);
+	let tokens = Lexer::new(cursor, &mut interner).map(|result| result.expect("lexer error"));
+
+	let (block, errors) = Parser::new(tokens, Vec::new()).into_errors();
+
+	// Each broken statement is reported and replaced, and parsing resumes at the next one.
+	assert_matches!(
+		block,
+		Block::Block(statements) => assert_matches!(
+			statements.as_ref(),
+			[
+				Statement::IllFormed,
+				Statement::Let { .. },
+				Statement::IllFormed,
+				Statement::Let { .. },
+				Statement::IllFormed,
+			]
+		)
+	);
+	assert_eq!(errors.len(), 3);
+}
+
+
+#[test]
+fn test_dedup_reporter() {
+	let mut interner = symbol::Interner::new();
+	let path = interner.get_or_intern("<test>");
+	let pos = |line| SourcePos { line, column: 0, offset: 0, path };
+
+	let mut reporter = DedupReporter::new(Vec::new());
+
+	reporter.report(Error::empty_argument(pos(1)));
+	reporter.report(Error::empty_argument(pos(1))); // Duplicate.
+	reporter.report(Error::empty_program(pos(1))); // Same position, different kind.
+	reporter.report(Error::empty_program(pos(2))); // Same kind, different position.
+	reporter.report(Error::empty_argument(pos(1))); // Not consecutive.
+	reporter.report(Error::empty_argument(pos(1)).in_context(ParseContext::Command)); // Different context.
+	reporter.report(Error::unterminated("string", pos(1)));
+	reporter.report(Error::unterminated("block", pos(1))); // Different payload.
+	reporter.report(Error::unexpected_eof());
+	reporter.report(Error::unexpected_eof()); // Without position.
+
+	assert_eq!(reporter.suppressed(), 1);
+
+	assert_matches!(
+		reporter.into_inner().as_slice(),
+		[
+			Error { kind: ErrorKind::EmptyArgument { .. }, .. },
+			Error { kind: ErrorKind::EmptyProgram { .. }, .. },
+			Error { kind: ErrorKind::EmptyProgram { .. }, .. },
+			Error { kind: ErrorKind::EmptyArgument { .. }, context: ParseContext::Expression, .. },
+			Error { kind: ErrorKind::EmptyArgument { .. }, context: ParseContext::Command, .. },
+			Error { kind: ErrorKind::Unterminated { construct: "string", .. }, .. },
+			Error { kind: ErrorKind::Unterminated { construct: "block", .. }, .. },
+			Error { kind: ErrorKind::UnexpectedEof, .. },
+			Error { kind: ErrorKind::UnexpectedEof, .. },
+		]
+	);
+}
+
+
+#[test]
+fn test_command_block_timeout() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		{ sleep 10 }: 5
+		let out = ${ curl example.com }: 2.5
+		{ ls }
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Expr(Expr::CommandBlock { block: first, .. }),
+			Statement::Let { init: Expr::CommandBlock { block: second, .. }, .. },
+			Statement::Expr(Expr::CommandBlock { block: third, .. }),
+		] => {
+			assert_eq!(first.timeout, Some(Duration::from_secs(5)));
+			assert_eq!(second.timeout, Some(Duration::from_millis(2500)));
+			assert_eq!(third.timeout, None);
+		}
+	);
+
+	let errors = parse_errors("{ ls }: x", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unexpected { .. }, .. }, ..]
+	);
+
+	// A colon that belongs to the enclosing construct is not a timeout.
+	let input = r#"
+		let x = c ? ${ a } : 1
+		let y = when ${ a }: 2
+		let z = c ? (${ a }: 3) : ${ b }
+	"#;
+
+	let timeout = |expr: &Expr| match expr {
+		Expr::CommandBlock { block, .. } => block.timeout,
+		Expr::Parenthesized { expr, .. } => match expr.as_ref() {
+			Expr::CommandBlock { block, .. } => block.timeout,
+			other => panic!("expected command block, got {:?}", other),
+		},
+		other => panic!("expected command block, got {:?}", other),
+	};
+	let branch = |block: &Block| match block {
+		Block::Block(statements) => match statements.as_ref() {
+			[ Statement::Expr(expr) ] => timeout(expr),
+			other => panic!("expected single expression, got {:?}", other),
+		},
+		Block::IllFormed => panic!("ill-formed block"),
+	};
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let { init: Expr::If { then: ternary, otherwise: Block::Block(one), .. }, .. },
+			Statement::Let { init: Expr::When { condition, value, .. }, .. },
+			Statement::Let { init: Expr::If { then: parenthesized, otherwise, .. }, .. },
+		] => {
+			assert_eq!(branch(ternary), None);
+			assert_matches!(one.as_ref(), [ Statement::Expr(Expr::Literal { literal: Literal::Int(1), .. }) ]);
+
+			assert_eq!(timeout(condition), None);
+			assert_matches!(value.as_ref(), Expr::Literal { literal: Literal::Int(2), .. });
+
+			assert_eq!(branch(parenthesized), Some(Duration::from_secs(3)));
+			assert_eq!(branch(otherwise), None);
+		}
+	);
+
+	// Nor is the colon of a single line loop.
+	let config = Config { single_line_loops: true, ..Config::default() };
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: b"while ${ test -f lock }: 1".as_ref().into() };
+	let tokens = Lexer::new(Cursor::from(&source), &mut interner)
+		.map(|result| result.expect("lexer error"));
+
+	let (block, errors) = Parser::with_config(tokens, Vec::new(), config).into_errors();
+	assert!(errors.is_empty(), "unexpected syntax errors: {:?}", errors);
+	assert_matches!(
+		block,
+		Block::Block(statements) => assert_matches!(
+			statements.as_ref(),
+			[ Statement::While { condition, block: Block::Block(body), .. } ] => {
+				assert_eq!(timeout(condition), None);
+				assert_matches!(body.as_ref(), [ Statement::Expr(Expr::Literal { literal: Literal::Int(1), .. }) ]);
+			}
+		)
+	);
+}
+
+
+#[test]
+fn test_empty_parens() {
+	let mut interner = symbol::Interner::new();
+
+	let errors = parse_errors("let x = ()\nlet y = 1", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[
+			Error {
+				kind: ErrorKind::Unexpected {
+					token: Token { kind: TokenKind::CloseParens, pos, .. },
+					expected: Expected::Message("expression inside parentheses"),
+				},
+				..
+			}
+		] if (pos.line, pos.column) == (1, 9)
+	);
+
+	// Empty argument lists are fine.
+	parse("let f = function() end\nf()", &mut interner);
+}
+
+
+#[test]
+fn test_return_values() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		function f()
+			return 1
+		end
+		function g()
+			return 1, 2
+		end
+	"#;
+
+	let statements = parse(input, &mut interner);
+
+	let returned: Vec<_> = statements
+		.iter()
+		.map(
+			|statement| match statement {
+				Statement::Let {
+					init: Expr::Literal { literal: Literal::Function { body: Block::Block(body), .. }, .. },
+					..
+				} => match body.as_ref() {
+					[Statement::Return { expr, .. }] => expr,
+					body => panic!("unexpected body: {:?}", body),
+				},
+				statement => panic!("unexpected statement: {:?}", statement),
+			}
+		)
+		.collect();
+
+	assert_matches!(
+		returned.as_slice(),
+		[
+			Expr::Literal { literal: Literal::Int(1), .. },
+			Expr::Literal { literal: Literal::Array(items), pos, .. },
+		] if pos.line == 6 => assert_matches!(
+			items.as_ref(),
+			[
+				Expr::Literal { literal: Literal::Int(1), .. },
+				Expr::Literal { literal: Literal::Int(2), .. },
+			]
+		)
+	);
+}
+
+
+#[test]
+fn test_combined_capture() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		let result = @{ ls; false }
+		let output = ${ ls }
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let { init: Expr::CommandBlock { block: combined, .. }, .. },
+			Statement::Let { init: Expr::CommandBlock { block: capture, .. }, .. },
+		] => {
+			assert_eq!(combined.kind, CommandBlockKind::CombinedCapture);
+			assert_eq!(combined.tail.len(), 1);
+			assert_eq!(capture.kind, CommandBlockKind::Capture);
+		}
+	);
+}
+
+
+#[test]
+fn test_assert() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		assert x > 0
+		assert x > 0, "must be positive"
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Assert {
+				condition: Expr::BinaryOp { op: BinaryOp::Greater, .. },
+				message: None,
+				..
+			},
+			Statement::Assert {
+				condition: Expr::BinaryOp { op: BinaryOp::Greater, .. },
+				message: Some(Expr::Literal { literal: Literal::String(message), .. }),
+				..
+			},
+		] => assert_eq!(message.as_ref(), b"must be positive")
+	);
+}
+
+
+#[test]
+fn test_capture_keyword() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		let result = capture { ls -la }
+		let output = capture { echo a; echo b }: 2
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let { init: Expr::CommandBlock { block: first, .. }, .. },
+			Statement::Let { init: Expr::CommandBlock { block: second, .. }, .. },
+		] => {
+			assert_eq!(first.kind, CommandBlockKind::Capture);
+			assert_eq!(second.kind, CommandBlockKind::Capture);
+			assert_eq!(second.tail.len(), 1);
+			assert_eq!(second.timeout, Some(Duration::from_secs(2)));
+		}
+	);
+
+	// Only synchronous blocks may follow the keyword.
+	let errors = parse_errors("let x = capture &{ ls }", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unexpected { .. }, .. }, ..]
+	);
+}
+
+
+#[test]
+fn test_command_conditional() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		if true then
+			{ grep -q x file | wc -l; if test -f a; then echo yes; echo again; else echo no; end; ls }
+		end
+	"#;
+
+	let literal = |arg: &str| [ArgPart::Unit(ArgUnit::Literal(arg.as_bytes().into()))];
+
+	// The expression-level if contains a command block, whose second command is a conditional.
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[Statement::Expr(Expr::If { then: Block::Block(then), .. })] => assert_matches!(
+			then.as_ref(),
+			[Statement::Expr(Expr::CommandBlock { block, .. })] => {
+				assert_matches!(&block.head, Command::Pipeline { tail, .. } if tail.len() == 1);
+
+				assert_matches!(
+					block.tail.as_ref(),
+					[
+						Command::Conditional { condition, then, otherwise, pos },
+						Command::Pipeline { .. },
+					] => {
+						assert_eq!((pos.line, pos.column), (3, 29));
+
+						assert_matches!(
+							condition.as_ref(),
+							Command::Pipeline { head, .. } if head.program.parts.as_ref() == literal("test")
+						);
+						assert_eq!(then.len(), 2);
+						assert_eq!(otherwise.len(), 1);
+					}
+				);
+			}
+		)
+	);
+
+	// The branch keywords must start a command, and the conditional must be closed.
+	let errors = parse_errors("{ if test -f a; then echo yes end }", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+
+	let errors = parse_errors("{ if test -f a then echo yes; end }", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_sync_tokens() {
+	let mut interner = symbol::Interner::new();
+
+	let parse_with = |input: &str, config: Config, interner: &mut symbol::Interner| {
+		let path = interner.get_or_intern("<test>");
+		let source = Source { path, contents: input.as_bytes().into() };
+		let tokens = Lexer::new(Cursor::from(&source), interner).map(|result| result.expect("lexer error"));
+
+		match Parser::with_config(tokens, Vec::new(), config).into_errors() {
+			(Block::Block(statements), errors) => (statements, errors),
+			(Block::IllFormed, _) => panic!("ill-formed block"),
+		}
+	};
+
+	let input = ") x\nlet y = 2\nassert y == 2";
+
+	// By default, only the offending token is skipped.
+	let (statements, errors) = parse_with(input, Config::default(), &mut interner);
+	assert_eq!(errors.len(), 1);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::IllFormed,
+			Statement::Expr(Expr::Identifier { .. }),
+			Statement::Let { .. },
+			Statement::Assert { .. },
+		]
+	);
+
+	// With the keyword set, recovery resumes at the next statement-starting keyword.
+	let config = Config {
+		sync_tokens: STATEMENT_SYNC_TOKENS.to_vec(),
+		..Config::default()
+	};
+	let (statements, errors) = parse_with(input, config, &mut interner);
+	assert_eq!(errors.len(), 1);
+	assert_matches!(
+		statements.as_ref(),
+		[Statement::IllFormed, Statement::Let { .. }, Statement::Assert { .. }]
+	);
+
+	// Without let in the synchronization set, the let statement is skipped as well.
+	let config = Config {
+		sync_tokens: vec![TokenKind::Keyword(Keyword::Assert)],
+		..Config::default()
+	};
+	let (statements, errors) = parse_with(input, config, &mut interner);
+	assert_eq!(errors.len(), 1);
+	assert_matches!(statements.as_ref(), [Statement::IllFormed, Statement::Assert { .. }]);
+
+	// A statement following a broken one is still parsed, and its errors reported.
+	let input = "let x = )\nf(1)\nlet y = ]";
+	let (statements, errors) = parse_with(input, Config::default(), &mut interner);
+	assert_eq!(errors.len(), 2);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::IllFormed,
+			Statement::Expr(Expr::Call { .. }),
+			Statement::IllFormed,
+		]
+	);
+}
+
+
+#[test]
+fn test_when() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		let x = when a > 0: a + 1
+		let y = @[ key: when a: 1, other: 2 ]
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let {
+				init: Expr::When { condition, value, .. },
+				..
+			},
+			Statement::Let {
+				init: Expr::Literal { literal: Literal::Dict(items), .. },
+				..
+			},
+		] => {
+			assert_matches!(condition.as_ref(), Expr::BinaryOp { op: BinaryOp::Greater, .. });
+			// The guard has the lowest precedence, so the value is the whole sum.
+			assert_matches!(value.as_ref(), Expr::BinaryOp { op: BinaryOp::Plus, .. });
+			assert_matches!(
+				items.as_ref(),
+				[
+					DictEntry { value: Expr::When { .. }, .. },
+					DictEntry { value: Expr::Literal { literal: Literal::Int(2), .. }, .. },
+				]
+			);
+		}
+	);
+}
+
+
+#[test]
+fn test_ternary() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		let x = a ? b : c ? d : e
+		let y = f()? ? (1) : -1
+		let z = (f() ?) + g() ?
+		let w = std.int("12") ?
+		std.println(w)
+	"#;
+
+	let statements = parse(input, &mut interner);
+
+	let expr = |block: &Block| match block {
+		Block::Block(statements) => match statements.as_ref() {
+			[Statement::Expr(expr)] => expr.clone(),
+			_ => panic!("expected a single expression"),
+		},
+		Block::IllFormed => panic!("ill-formed block"),
+	};
+
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let { init: Expr::If { condition, then, otherwise, .. }, .. },
+			Statement::Let { init: Expr::If { condition: try_, .. }, .. },
+			Statement::Let { init: Expr::BinaryOp { left, right, .. }, .. },
+			Statement::Let { init: Expr::UnaryOp { op: UnaryOp::Try, .. }, .. },
+			Statement::Expr(Expr::Call { .. }),
+		] => {
+			assert_matches!(condition.as_ref(), Expr::Identifier { .. });
+			assert_matches!(expr(then), Expr::Identifier { .. });
+			// The conditional operator associates to the right.
+			assert_matches!(
+				expr(otherwise),
+				Expr::If { condition, .. } if matches!(condition.as_ref(), Expr::Identifier { .. })
+			);
+
+			// An attached question mark is the try operator.
+			assert_matches!(try_.as_ref(), Expr::UnaryOp { op: UnaryOp::Try, .. });
+
+			// So is a detached one that isn't followed by an expression in the same line.
+			assert_matches!(
+				left.as_ref(),
+				Expr::Parenthesized { expr, .. } if matches!(expr.as_ref(), Expr::UnaryOp { op: UnaryOp::Try, .. })
+			);
+			assert_matches!(right.as_ref(), Expr::UnaryOp { op: UnaryOp::Try, .. });
+		}
+	);
+
+	// A detached question mark followed by an expression requires the colon.
+	let errors = parse_errors("let x = a ? b", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::DetachedTry { pos }, .. }] if (pos.line, pos.column) == (1, 10)
+	);
+
+	let errors = parse_errors("let x = f() ? g()", Config::default(), &mut interner);
+	assert_matches!(errors.as_slice(), [Error { kind: ErrorKind::DetachedTry { .. }, .. }]);
+}
+
+
+#[test]
+fn test_match() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		let x = match value
+			with 1 then "one"
+			with 2 then "two"
+			with "three" then 3
+			else nil
+		end
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let {
+				init: Expr::Match { scrutinee, arms, otherwise, .. },
+				..
+			},
+		] => {
+			assert_matches!(scrutinee.as_ref(), Expr::Identifier { .. });
+			// Arms are kept in source order.
+			assert_matches!(
+				arms.as_ref(),
+				[
+					(Expr::Literal { literal: Literal::Int(1), .. }, Block::Block(one)),
+					(Expr::Literal { literal: Literal::Int(2), .. }, Block::Block(two)),
+					(Expr::Literal { literal: Literal::String(three), .. }, Block::Block(three_block)),
+				] => {
+					assert_matches!(
+						one.as_ref(),
+						[ Statement::Expr(Expr::Literal { literal: Literal::String(s), .. }) ] if s.as_ref() == b"one"
+					);
+					assert_matches!(
+						two.as_ref(),
+						[ Statement::Expr(Expr::Literal { literal: Literal::String(s), .. }) ] if s.as_ref() == b"two"
+					);
+					assert_eq!(three.as_ref(), b"three");
+					assert_matches!(
+						three_block.as_ref(),
+						[ Statement::Expr(Expr::Literal { literal: Literal::Int(3), .. }) ]
+					);
+				}
+			);
+			assert_matches!(
+				otherwise,
+				Block::Block(block) if matches!(
+					block.as_ref(),
+					[ Statement::Expr(Expr::Literal { literal: Literal::Nil, .. }) ]
+				)
+			);
+		}
+	);
+
+	// The else arm is optional.
+	let statements = parse("match x with 1 then 2 end", &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[ Statement::Expr(Expr::Match { otherwise, .. }) ] if otherwise.is_empty()
+	);
+
+	// A guard in an arm ends at the next arm.
+	let statements = parse("match x with 1 then when y: 2 + 3 with 2 then 4 end", &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[ Statement::Expr(Expr::Match { arms, .. }) ] => {
+			assert_matches!(
+				arms.as_ref(),
+				[
+					(_, Block::Block(guarded)),
+					(Expr::Literal { literal: Literal::Int(2), .. }, _),
+				] => assert_matches!(
+					guarded.as_ref(),
+					[ Statement::Expr(Expr::When { value, .. }) ]
+						if matches!(value.as_ref(), Expr::BinaryOp { op: BinaryOp::Plus, .. })
+				)
+			);
+		}
+	);
+}
+
+
+#[test]
+fn test_duplicate_match_arms() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		match x
+			with 1 then "one"
+			with "1" then "string"
+			with y then "variable"
+			with 1 then "again"
+		end
+	"#;
+
+	let errors = parse_errors(input, Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::DuplicateMatchArm { pos, first }, .. }] => {
+			assert_eq!((pos.line, pos.column), (6, 8));
+			assert_eq!((first.line, first.column), (3, 8));
+		}
+	);
+
+	// Distinct constants and non-constant patterns are accepted.
+	let input = "match x with 1 then 1 with 2 then 2 with y then 3 with y then 4 with nil then 5 end";
+	assert!(parse_errors(input, Config::default(), &mut interner).is_empty());
+}
+
+
+#[test]
+fn test_single_line_loops() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		while x < 10: x = x + 1
+		for item in @[ key: [1, 2] ]: std.print(item)
+		for x in xs: for y in ys: std.print(x, y)
+		let y = 1
+	"#;
+
+	let config = || Config { single_line_loops: true, ..Config::default() };
+
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let tokens = Lexer::new(Cursor::from(&source), &mut interner)
+		.map(|result| result.expect("lexer error"));
+
+	let (block, errors) = Parser::with_config(tokens, Vec::new(), config()).into_errors();
+	assert!(errors.is_empty(), "unexpected syntax errors: {:?}", errors);
+
+	assert_matches!(
+		block,
+		Block::Block(statements) => assert_matches!(
+			statements.as_ref(),
+			[
+				Statement::While { block: Block::Block(while_body), .. },
+				Statement::For { block: Block::Block(for_body), .. },
+				Statement::For { block: Block::Block(outer_body), .. },
+				Statement::Let { .. },
+			] => {
+				assert_matches!(while_body.as_ref(), [ Statement::Assign { .. } ]);
+				assert_matches!(for_body.as_ref(), [ Statement::Expr(Expr::Call { .. }) ]);
+				assert_matches!(
+					outer_body.as_ref(),
+					[ Statement::For { block: Block::Block(inner_body), .. } ] if inner_body.len() == 1
+				);
+			}
+		)
+	);
+
+	// The usual form is still accepted.
+	let errors = parse_errors("while true do break end", config(), &mut interner);
+	assert!(errors.is_empty());
+
+	// Rejected when the flag is off.
+	let errors = parse_errors("while x < 10: x = x + 1", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+
+	let errors = parse_errors("for x in xs: std.print(x)", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_range() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		for i in 1..10 do end
+		for i in n - 1..=0 do end
+	"#;
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::For { expr: Expr::Range { start, end, inclusive: false, .. }, .. },
+			Statement::For { expr: Expr::Range { start: descending, inclusive: true, .. }, .. },
+		] => {
+			assert_matches!(start.as_ref(), Expr::Literal { literal: Literal::Int(1), .. });
+			assert_matches!(end.as_ref(), Expr::Literal { literal: Literal::Int(10), .. });
+			// Ranges have the lowest precedence, so the start is the whole difference.
+			assert_matches!(descending.as_ref(), Expr::BinaryOp { op: BinaryOp::Minus, .. });
+		}
+	);
+
+	// Ranges can't be chained.
+	let errors = parse_errors("let r = 1..2..3", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_trailing_input() {
+	let mut interner = symbol::Interner::new();
+
+	let input = "let x = 1\nreturn x\nstd.print(x)";
+
+	let parse_with = |config: Config, interner: &mut symbol::Interner| {
+		let path = interner.get_or_intern("<test>");
+		let source = Source { path, contents: input.as_bytes().into() };
+		let tokens = Lexer::new(Cursor::from(&source), interner)
+			.map(|result| result.expect("lexer error"));
+
+		Parser::with_config(tokens, Vec::new(), config).into_errors()
+	};
+
+	// The statements before the return are kept, and the first trailing token is reported.
+	let (block, errors) = parse_with(Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::TrailingInput { token: Token { kind: TokenKind::Identifier(_), pos, .. } }, .. }]
+			=> assert_eq!((pos.line, pos.column), (3, 0))
+	);
+	assert_matches!(
+		block,
+		Block::Block(statements) => assert_matches!(
+			statements.as_ref(),
+			[Statement::Let { .. }, Statement::Return { .. }]
+		)
+	);
+
+	let config = Config { ignore_trailing_input: true, ..Config::default() };
+	let (block, errors) = parse_with(config, &mut interner);
+	assert!(errors.is_empty(), "unexpected syntax errors: {:?}", errors);
+	assert_matches!(block, Block::Block(statements) if statements.len() == 2);
+}
+
+
+#[test]
+fn test_spread() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		let x = [ 1, ...xs, 2, ...ys ]
+		f(...args, 1)
+	"#;
+
+	let statements = parse(input, &mut interner);
+
+	let spreads = |items: &[Expr]| -> Vec<bool> {
+		items
+			.iter()
+			.map(|item| matches!(item, Expr::Spread { .. }))
+			.collect()
+	};
+
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let { init: Expr::Literal { literal: Literal::Array(items), .. }, .. },
+			Statement::Expr(Expr::Call { args, .. }),
+		] => {
+			assert_eq!(spreads(items), [false, true, false, true]);
+			assert_eq!(spreads(args), [true, false]);
+
+			assert_matches!(&items[1], Expr::Spread { operand, .. } if matches!(operand.as_ref(), Expr::Identifier { .. }));
+		}
+	);
+
+	// Spreads are only allowed as items or arguments.
+	let errors = parse_errors("let x = ...xs", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_dict_keys() {
+	let mut interner = symbol::Interner::new();
+
+	let statements = parse(r#"let x = @[ name: 1, [key]: 2, ["a" ++ "b"]: 3 ]"#, &mut interner);
+
+	assert_matches!(
+		statements.as_ref(),
+		[ Statement::Let { init: Expr::Literal { literal: Literal::Dict(items), .. }, .. } ] => {
+			assert_matches!(
+				items.as_ref(),
+				[
+					DictEntry { key: DictKey::Symbol(_), .. },
+					DictEntry { key: DictKey::Expr(first), .. },
+					DictEntry { key: DictKey::Expr(second), .. },
+				] => {
+					assert_matches!(first.as_ref(), Expr::Identifier { .. });
+					assert_matches!(second.as_ref(), Expr::BinaryOp { .. });
+				}
+			);
+		}
+	);
+
+	// The computed key must be closed.
+	let errors = parse_errors("let x = @[ [key: 1 ]", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_let_array_destructuring() {
+	let mut interner = symbol::Interner::new();
+
+	let statements = parse("let [ a, b,\n  c ] = array", &mut interner);
+	let a = interner.get_or_intern("a");
+	let b = interner.get_or_intern("b");
+	let c = interner.get_or_intern("c");
+	let array = interner.get_or_intern("array");
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::LetArray {
+				items,
+				init: Expr::Identifier { identifier, .. },
+				..
+			}
+		] if *identifier == array => assert_matches!(
+			items.as_ref(),
+			[
+				ItemBinding { identifier: id_a, pos: pos_a },
+				ItemBinding { identifier: id_b, pos: pos_b },
+				ItemBinding { identifier: id_c, pos: pos_c },
+			] => {
+				assert_eq!((*id_a, *id_b, *id_c), (a, b, c));
+				assert_eq!((pos_a.line, pos_a.column), (1, 6));
+				assert_eq!((pos_b.line, pos_b.column), (1, 9));
+				assert_eq!((pos_c.line, pos_c.column), (2, 2));
+			}
+		)
+	);
+
+	// Dict destructuring, in contrast, is introduced by `@[`.
+	let statements = parse("let @[ a ] = dict", &mut interner);
+	assert_matches!(statements.as_ref(), [Statement::LetDict { .. }]);
+
+	// The initializer is required.
+	let errors = parse_errors("let [ a ]", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_let_dict_destructuring() {
+	let mut interner = symbol::Interner::new();
+
+	let statements = parse("let @[ a, b ] = dict", &mut interner);
+	let a = interner.get_or_intern("a");
+	let b = interner.get_or_intern("b");
+	let dict = interner.get_or_intern("dict");
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::LetDict {
+				fields,
+				init: Expr::Identifier { identifier, .. },
+				..
+			}
+		] if *identifier == dict => assert_matches!(
+			fields.as_ref(),
+			[
+				FieldBinding { field: field_a, identifier: id_a, .. },
+				FieldBinding { field: field_b, identifier: id_b, .. },
+			] if (*field_a, *id_a, *field_b, *id_b) == (a, a, b, b)
+		)
+	);
+
+	// Renamed fields.
+	let statements = parse("let @[ a: x, b ] = @[ a: 1, b: 2 ]", &mut interner);
+	let x = interner.get_or_intern("x");
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::LetDict {
+				fields,
+				init: Expr::Literal { literal: Literal::Dict(_), .. },
+				..
+			}
+		] => assert_matches!(
+			fields.as_ref(),
+			[
+				FieldBinding { field: field_a, identifier: id_x, .. },
+				FieldBinding { field: field_b, identifier: id_b, .. },
+			] if (*field_a, *id_x, *field_b, *id_b) == (a, x, b, b)
+		)
+	);
+
+	// The initializer is required.
+	let errors = parse_errors("let @[ a ]", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_error_span() {
+	let mut interner = symbol::Interner::new();
+
+	// The span covers the whole call, up to the end of the offending token.
+	let errors = parse_errors("std.print(1 2)\nlet y = 2", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unexpected { token, .. }, span: Some(span), .. }] => {
+			assert_eq!(token.end, span.end);
+			assert_eq!((span.start.line, span.start.column), (1, 0));
+			assert_eq!((span.end.line, span.end.column), (1, 13));
+		}
+	);
+
+	// The innermost construct takes precedence.
+	let errors = parse_errors("let x = 1\nlet y = -(1 2)", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { span: Some(span), .. }] => {
+			assert_eq!((span.start.line, span.start.column), (2, 9));
+			assert_eq!((span.end.line, span.end.column), (2, 13));
+		}
+	);
+}
+
+
+#[test]
+fn test_expr_span() {
+	let mut interner = symbol::Interner::new();
+
+	let input = "let value = (1 + foo.bar) * baz(2)\ntotal += items[0].price";
+	let statements = parse(input, &mut interner);
+
+	let range = |span: Option<Span>| span.map(|span| ((span.start.line, span.start.column), (span.end.line, span.end.column)));
+
+	assert_matches!(
+		statements.as_ref(),
+		[let_, assign] => {
+			assert_eq!(range(let_.span()), Some(((1, 0), (1, 34))));
+
+			assert_matches!(
+				let_,
+				Statement::Let { init: Expr::BinaryOp { left, right, span, .. }, .. } => {
+					assert_eq!(range(Some(*span)), Some(((1, 12), (1, 34))));
+					assert_eq!(range(left.span()), Some(((1, 12), (1, 25))));
+					assert_eq!(range(right.span()), Some(((1, 28), (1, 34))));
+
+					// Parentheses are kept in the tree, with the position of the inner expression.
+					assert_matches!(
+						left.as_ref(),
+						Expr::Parenthesized { expr, pos, .. } => {
+							assert_eq!(Some(*pos), expr.pos());
+							assert_matches!(expr.span(), Some(span) if (span.start.line, span.start.column) == (1, 13));
+						}
+					);
+				}
+			);
+
+			// The position of an assignment is the operator, while its span starts at the target.
+			assert_matches!(
+				assign,
+				Statement::Assign { left, right, pos, span } => {
+					assert_eq!((pos.line, pos.column), (2, 6));
+					assert_eq!(range(Some(*span)), Some(((2, 0), (2, 23))));
+					assert_eq!(range(left.span()), Some(((2, 0), (2, 5))));
+					assert_eq!(range(right.span()), Some(((2, 0), (2, 23))));
+				}
+			);
+		}
+	);
+}
+
+
+#[test]
+fn test_string_interpolation() {
+	let mut interner = symbol::Interner::new();
+
+	let string = |literal: &Expr| match literal {
+		Expr::Literal { literal: Literal::String(value), .. } => Some(value.clone()),
+		_ => None,
+	};
+
+	// Folded into a left associative concatenation.
+	let statements = parse(r#"let x = "hello ${name}!""#, &mut interner);
+	let name = interner.get_or_intern("name");
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let {
+				init: Expr::BinaryOp { left, op: BinaryOp::Concat, right, .. },
+				..
+			}
+		] => {
+			assert_eq!(string(right).as_deref(), Some(&b"!"[..]));
+			assert_matches!(
+				left.as_ref(),
+				Expr::BinaryOp {
+					left,
+					op: BinaryOp::Concat,
+					right: box_expr,
+					..
+				} if string(left).as_deref() == Some(&b"hello "[..])
+					&& matches!(box_expr.as_ref(), Expr::Identifier { identifier, .. } if *identifier == name)
+			);
+		}
+	);
+
+	// Empty parts are omitted, except for the first one.
+	let statements = parse(r#""${a}${b}""#, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Expr(
+				Expr::BinaryOp {
+					left,
+					op: BinaryOp::Concat,
+					right,
+					..
+				}
+			)
+		] => {
+			assert_matches!(right.as_ref(), Expr::Identifier { .. });
+			assert_matches!(
+				left.as_ref(),
+				Expr::BinaryOp { left, right, .. }
+					if string(left).as_deref() == Some(&b""[..])
+						&& matches!(right.as_ref(), Expr::Identifier { .. })
+			);
+		}
+	);
+
+	// Arbitrary expressions, including nested interpolations.
+	let errors = parse_errors(r#"let x = "${ f(1, "${y}") + 1 }""#, Config::default(), &mut interner);
+	assert!(errors.is_empty());
+
+	// Unterminated interpolation.
+	let errors = parse_errors(r#""a ${b"#, Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unterminated { construct: "string interpolation", .. }, .. }]
+	);
+}
+
+
+#[test]
+fn test_interpolation_splice() {
+	let mut interner = symbol::Interner::new();
+
+	// Control flow expressions may be embedded.
+	let statements = parse(r#"let x = "${ if c then 1 else 2 end }""#, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let {
+				init: Expr::BinaryOp { op: BinaryOp::Concat, right, .. },
+				..
+			}
+		] => assert_matches!(right.as_ref(), Expr::If { otherwise: Block::Block(_), .. })
+	);
+
+	// Statements are reported once, and parsing resumes after the embedded expression.
+	let input = r#"let x = "${ return "${y}" } and ${ break } and ${z}""#;
+	let errors = parse_errors(input, Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[
+			Error { kind: ErrorKind::StatementInInterpolation { keyword: Keyword::Return, pos: first }, .. },
+			Error { kind: ErrorKind::StatementInInterpolation { keyword: Keyword::Break, pos: second }, .. },
+		] if first.column == 12 && second.column == 35
+	);
+
+	assert_eq!(
+		fmt::Show(&errors[0], &interner).to_string(),
+		"<test> (line 1, column 12) - unexpected 'return' statement, string interpolation only embeds expressions"
+	);
+}
+
+
+#[test]
+fn test_trailing_comments() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		# Not attached.
+		function f(
+			a, # The first.
+			b: Int, c # The third.
+			# Not attached.
+		) # Not attached.
+			return @[
+				x: [ 1 ], # The key.
+				y: @[ z: 2 ], # The nested dict.
+				w: 3
+			]
+		end
+	"#;
+
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: input.as_bytes().into() };
+	let config = lexer::Config { preserve_comments: true, ..lexer::Config::default() };
+	let tokens = Lexer::with_config(Cursor::from(&source), &mut interner, config)
+		.map(|result| result.expect("lexer error"));
+
+	let (block, errors) = Parser::new(tokens, Vec::new()).into_errors();
+	assert!(errors.is_empty(), "unexpected syntax errors: {:?}", errors);
+
+	let comment = |text: &'static str| Some(text.as_bytes().into());
+
+	assert_matches!(
+		block,
+		Block::Block(statements) => assert_matches!(
+			statements.as_ref(),
+			[
+				Statement::Let {
+					init: Expr::Literal { literal: Literal::Function { params, body, .. }, .. },
+					..
+				}
+			] => {
+				let comments: Vec<_> = params.iter().map(|param| param.comment.clone()).collect();
+				assert_eq!(comments, [comment("The first."), None, comment("The third.")]);
+
+				assert_matches!(
+					body,
+					Block::Block(body) => assert_matches!(
+						body.as_ref(),
+						[
+							Statement::Return {
+								expr: Expr::Literal { literal: Literal::Dict(items), .. },
+								..
+							}
+						] => {
+							let comments: Vec<_> = items.iter().map(|item| item.comment.clone()).collect();
+							assert_eq!(comments, [comment("The key."), comment("The nested dict."), None]);
+						}
+					)
+				);
+			}
+		)
+	);
+}
+
+
+#[test]
+fn test_command_labels() {
+	let mut interner = symbol::Interner::new();
+
+	let input = r#"
+		{
+			build: CC=gcc make all | log: tee out;
+			echo a: b: c;
+			if check: test -f x; then deploy: ./deploy; end
+		}
+	"#;
+
+	let statements = parse(input, &mut interner);
+
+	let label = |label: Option<symbol::Symbol>| label.and_then(|label| interner.resolve(label));
+
+	assert_matches!(
+		statements.as_ref(),
+		[Statement::Expr(Expr::CommandBlock { block, .. })] => assert_matches!(
+			block.as_ref(),
+			CommandBlock { head: Command::Pipeline { head, tail }, tail: commands, .. }
+				=> {
+					assert_eq!(label(head.label), Some(b"build".as_ref()));
+					assert_eq!(head.env.len(), 1);
+					assert_eq!(label(tail[0].label), Some(b"log".as_ref()));
+
+					// Only the first word of a command may be a label.
+					assert_matches!(
+						&commands[0],
+						Command::Pipeline { head, .. } if head.label.is_none() && head.arguments.len() == 3
+					);
+
+					assert_matches!(
+						&commands[1],
+						Command::Conditional { condition, then, .. } => {
+							assert_matches!(
+								condition.as_ref(),
+								Command::Pipeline { head, .. } if label(head.label) == Some(b"check".as_ref())
+							);
+							assert_matches!(
+								then.as_ref(),
+								[Command::Pipeline { head, .. }] if label(head.label) == Some(b"deploy".as_ref())
+							);
+						}
+					);
+				}
+		)
+	);
+}
+
+
+#[test]
+fn test_const() {
+	let mut interner = symbol::Interner::new();
+
+	let statements = parse("const PI = 3.14159\nlet x = 1\nfunction f() end", &mut interner);
+	let pi = interner.get_or_intern("PI");
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let { identifier, is_const: true, init: Expr::Literal { literal: Literal::Float(_), .. }, .. },
+			Statement::Let { is_const: false, .. },
+			Statement::Let { is_const: false, .. },
+		] if *identifier == pi
+	);
+
+	// The initializer is required, and the following statement is kept.
+	let path = interner.get_or_intern("<test>");
+	let source = Source { path, contents: b"const x\nlet y = 1".as_ref().into() };
+	let cursor = Cursor::from(&source);
+	let tokens = Lexer::new(cursor, &mut interner).map(|result| result.expect("lexer error"));
+
+	let (block, errors) = Parser::new(tokens, Vec::new()).into_errors();
+	let y = interner.get_or_intern("y");
+
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::Unexpected { .. }, .. }]
+	);
+	assert_matches!(
+		block,
+		Block::Block(statements) => assert_matches!(
+			statements.as_ref(),
+			[
+				Statement::Let { is_const: true, init: Expr::IllFormed, .. },
+				Statement::Let { identifier, is_const: false, .. },
+			] if *identifier == y
+		)
+	);
+}
+
+
+#[test]
+fn test_multi_assign() {
+	let mut interner = symbol::Interner::new();
+
+	let statements = parse("a, b = b, a\nx = 1", &mut interner);
+	let a = interner.get_or_intern("a");
+	let b = interner.get_or_intern("b");
+
+	let identifiers = |exprs: &[Expr]| -> Vec<Option<symbol::Symbol>> {
+		exprs
+			.iter()
+			.map(
+				|expr| match expr {
+					Expr::Identifier { identifier, .. } => Some(*identifier),
+					_ => None,
+				}
+			)
+			.collect()
+	};
+
+	assert_matches!(
+		statements.as_ref(),
+		[ Statement::MultiAssign { left, right, pos, .. }, Statement::Assign { .. } ] => {
+			assert_eq!(identifiers(left), [Some(a), Some(b)]);
+			assert_eq!(identifiers(right), [Some(b), Some(a)]);
+			assert_eq!((pos.line, pos.column), (1, 5));
+		}
+	);
+
+	// The number of targets and values must match.
+	let errors = parse_errors("a, b = 1, 2, 3", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[Error { kind: ErrorKind::AssignCountMismatch { targets: 2, values: 3, pos }, .. }]
+			if (pos.line, pos.column) == (1, 5)
+	);
+
+	// Multiple targets require an assignment.
+	let errors = parse_errors("a, b", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
+
+
+#[test]
+fn test_default_params() {
+	let mut interner = symbol::Interner::new();
+
+	let input = "function f(a, b: Int = 10, c = a + b) return a end";
+
+	let statements = parse(input, &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Let {
+				init: Expr::Literal { literal: Literal::Function { params, .. }, .. },
+				..
+			},
+		] => assert_matches!(
+			params.as_ref(),
+			[
+				Param { default: None, .. },
+				Param {
+					annotation: Some(TypeExpr::Named { .. }),
+					default: Some(Expr::Literal { literal: Literal::Int(10), .. }),
+					..
+				},
+				Param { default: Some(Expr::BinaryOp { op: BinaryOp::Plus, .. }), .. },
+			]
+		)
+	);
+
+	// Required parameters can't follow defaulted ones.
+	let errors = parse_errors("function f(a = 1, b, c) end", Config::default(), &mut interner);
+	assert_matches!(
+		errors.as_slice(),
+		[
+			Error { kind: ErrorKind::RequiredAfterDefault { pos: b }, .. },
+			Error { kind: ErrorKind::RequiredAfterDefault { pos: c }, .. },
+		] if b.column == 18 && c.column == 21
+	);
+}
+
+
+#[test]
+fn test_pipeline_operator() {
+	let mut interner = symbol::Interner::new();
+	let f = interner.get_or_intern("f");
+	let g = interner.get_or_intern("g");
+	let x = interner.get_or_intern("x");
+
+	let identifier = |expr: &Expr| match expr {
+		Expr::Identifier { identifier, .. } => Some(*identifier),
+		_ => None,
+	};
+
+	// Parsed as `g(f(x), 2)`.
+	let statements = parse("x |> f |> g(2)", &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[Statement::Expr(Expr::Call { function, args, .. })] if identifier(function) == Some(g) => {
+			assert_matches!(
+				args.as_ref(),
+				[
+					Expr::Call { function, args, .. },
+					Expr::Literal { literal: Literal::Int(2), .. },
+				] if identifier(function) == Some(f) => {
+					assert_matches!(args.as_ref(), [arg] if identifier(arg) == Some(x));
+				}
+			);
+		}
+	);
+
+	// Lower precedence than arithmetic and concatenation, but higher than comparisons.
+	let statements = parse("x + 1 |> f == y", &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[
+			Statement::Expr(Expr::BinaryOp { op: BinaryOp::Equals, left, .. })
+		] => assert_matches!(
+			left.as_ref(),
+			Expr::Call { function, args, .. } if identifier(function) == Some(f) => {
+				assert_matches!(args.as_ref(), [Expr::BinaryOp { op: BinaryOp::Plus, .. }]);
+			}
+		)
+	);
+
+	// Any expression may be called, as long as it is not a call.
+	let statements = parse("x |> obj.method |> (g(1))", &mut interner);
+	assert_matches!(
+		statements.as_ref(),
+		[Statement::Expr(Expr::Call { function, args, .. })] => {
+			assert_matches!(
+				function.as_ref(),
+				Expr::Parenthesized { expr, .. } if matches!(expr.as_ref(), Expr::Call { args, .. } if args.len() == 1)
+			);
+			assert_matches!(
+				args.as_ref(),
+				[Expr::Call { function, args, .. }] if matches!(function.as_ref(), Expr::Field { .. }) && args.len() == 1
+			);
+		}
+	);
+
+	let errors = parse_errors("x |>", Config::default(), &mut interner);
+	assert!(!errors.is_empty());
+}
@@ -1,9 +1,13 @@
 use std::{
+	cmp::Ordering,
 	ffi::OsStr,
 	fs::File,
 	os::unix::ffi::OsStrExt,
 };
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::{
 	fmt::{self, Display},
 	symbol::{self, Symbol},
@@ -44,18 +48,89 @@ impl Source {
 
 		Ok(Self { path, contents: contents.into() })
 	}
+
+
+	/// The byte offsets of the start of each line in the source code.
+	pub fn line_starts(&self) -> Vec<usize> {
+		line_starts(&self.contents)
+	}
+}
+
+
+/// Compute the byte offsets of the start of each line in the given source code.
+/// The first line always starts at offset zero, so the result is never empty. The offset
+/// of line `n` is at index `n - 1`.
+pub fn line_starts(contents: &[u8]) -> Vec<usize> {
+	let mut starts = vec![0];
+
+	starts.extend(
+		contents
+			.iter()
+			.enumerate()
+			.filter(|(_, &byte)| byte == b'\n')
+			.map(|(ix, _)| ix + 1)
+	);
+
+	starts
 }
 
 
 /// A human readable position in the source code.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SourcePos {
 	pub line: u32,
 	pub column: u32,
+	/// The byte offset from the start of the source.
+	pub offset: usize,
 	pub path: Symbol,
 }
 
 
+impl SourcePos {
+	/// Map the line and column to a byte offset, given the line starts of the source.
+	/// This is cheaper than scanning the source for line boundaries every time.
+	pub fn offset_in(&self, line_starts: &[usize]) -> Option<usize> {
+		let line = (self.line as usize).checked_sub(1)?;
+		let start = line_starts.get(line)?;
+		Some(start + self.column as usize)
+	}
+
+
+	/// Map a byte offset to a line and column, given the line starts of the source.
+	pub fn from_offset(offset: usize, line_starts: &[usize], path: Symbol) -> Self {
+		// The first line always starts at zero, so the partition point is never zero.
+		let line = line_starts.partition_point(|&start| start <= offset);
+		let start = line_starts[line - 1];
+
+		Self {
+			line: line as u32,
+			column: (offset - start) as u32,
+			offset,
+			path,
+		}
+	}
+}
+
+
+/// Positions are ordered by path, and then in source order. Paths are ordered by their
+/// symbols, which only groups positions in the same file. The ill-formed position sorts
+/// first.
+impl Ord for SourcePos {
+	fn cmp(&self, other: &Self) -> Ordering {
+		let key = |pos: &Self| (usize::from(pos.path), pos.line, pos.column, pos.offset);
+		key(self).cmp(&key(other))
+	}
+}
+
+
+impl PartialOrd for SourcePos {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+
 impl<'a> Display<'a> for SourcePos {
 	type Context = &'a symbol::Interner;
 
@@ -69,3 +144,42 @@ impl<'a> Display<'a> for SourcePos {
 		)
 	}
 }
+
+
+/// A range in the source code, from the start position up to the end position.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+	pub start: SourcePos,
+	pub end: SourcePos,
+}
+
+
+impl Span {
+	/// A span covering a single position.
+	pub fn point(pos: SourcePos) -> Self {
+		Self { start: pos, end: pos }
+	}
+
+
+	/// The smallest span covering both spans.
+	pub fn merge(self, other: Self) -> Self {
+		let start = if other.start.offset < self.start.offset { other.start } else { self.start };
+		let end = if other.end.offset > self.end.offset { other.end } else { self.end };
+
+		Self { start, end }
+	}
+
+
+	/// Check if the given position is in the span. The end position is not included.
+	pub fn contains(&self, pos: SourcePos) -> bool {
+		self.start.offset <= pos.offset && pos.offset < self.end.offset
+	}
+}
+
+
+impl From<SourcePos> for Span {
+	fn from(pos: SourcePos) -> Self {
+		Self::point(pos)
+	}
+}
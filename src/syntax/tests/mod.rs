@@ -5,7 +5,7 @@ use std::{
 };
 
 use crate::{fmt, symbol, syntax::AnalysisDisplayContext, tests};
-use super::{Analysis, Source};
+use super::{ast::IllFormed, Analysis, Source, SourcePos};
 
 
 fn test_dir<P, F>(path: P, mut check: F) -> io::Result<()>
@@ -63,3 +63,36 @@ fn test_negative() -> io::Result<()> {
 		|analysis| !analysis.errors.is_empty(),
 	)
 }
+
+
+#[test]
+fn test_source_pos_order() {
+	let mut interner = symbol::Interner::new();
+	let first = interner.get_or_intern("first.hsh");
+	let second = interner.get_or_intern("second.hsh");
+
+	let pos = |path, line, column, offset| SourcePos { line, column, offset, path };
+
+	let mut positions = vec![
+		pos(second, 1, 0, 0),
+		pos(first, 2, 3, 10),
+		pos(first, 1, 4, 4),
+		SourcePos::ill_formed(),
+		pos(first, 2, 0, 7),
+		pos(first, 1, 2, 2),
+	];
+
+	positions.sort();
+
+	assert_eq!(
+		positions,
+		[
+			SourcePos::ill_formed(),
+			pos(first, 1, 2, 2),
+			pos(first, 1, 4, 4),
+			pos(first, 2, 0, 7),
+			pos(first, 2, 3, 10),
+			pos(second, 1, 0, 0),
+		]
+	);
+}